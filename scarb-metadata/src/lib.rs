@@ -227,6 +227,28 @@ pub struct PackageMetadata {
     /// Targets provided by the package. (`lib`, `starknet-contract`, etc.).
     pub targets: Vec<TargetMetadata>,
 
+    /// Features declared in the `[features]` table of `Scarb.toml`, mapped to the list of
+    /// features and dependencies they enable.
+    #[cfg_attr(feature = "builder", builder(default))]
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+
+    /// Features enabled in this package, unified across the whole dependency graph.
+    ///
+    /// This list is empty if dependencies were not resolved (e.g. with `--no-deps` flag).
+    #[cfg_attr(feature = "builder", builder(default))]
+    #[serde(default)]
+    pub enabled_features: Vec<String>,
+
+    /// Index URLs of registries this package can be published to, as given by the `publish`
+    /// field of `Scarb.toml`.
+    ///
+    /// `None` means the package can be published to any registry, while an empty list means
+    /// it cannot be published at all.
+    #[cfg_attr(feature = "builder", builder(default))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish: Option<Vec<String>>,
+
     /// Various metadata fields from `Scarb.toml`.
     #[serde(flatten)]
     pub manifest_metadata: ManifestMetadata,
@@ -4,18 +4,21 @@
 
 use std::collections::BTreeMap;
 use std::ffi::OsString;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use camino::Utf8PathBuf;
 use clap::{CommandFactory, Parser, Subcommand};
-use scarb::ops::EmitTarget;
+use scarb::ops::{EmitTarget, FeaturesOpts};
+use semver::Version;
 use smol_str::SmolStr;
 use tracing::level_filters::LevelFilter;
 use tracing_log::AsTrace;
 use url::Url;
 
-use scarb::compiler::Profile;
-use scarb::core::PackageName;
+use scarb::compiler::{MessageFormat, Profile};
+use scarb::core::{FeatureName, PackageName};
 use scarb::manifest_editor::DepId;
 use scarb::manifest_editor::SectionArgs;
 use scarb::version;
@@ -38,10 +41,30 @@ pub struct ScarbArgs {
     #[arg(long)]
     pub json: bool,
 
+    /// Format of compiler diagnostics, `json` implies `--json`.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        env = "SCARB_MESSAGE_FORMAT",
+        hide_short_help = true
+    )]
+    pub message_format: MessageFormat,
+
     /// Run without accessing the network.
     #[arg(long, env = "SCARB_OFFLINE", hide_short_help = true)]
     pub offline: bool,
 
+    /// Require Scarb.lock to be up to date, fail instead of updating it.
+    #[arg(long, hide_short_help = true)]
+    pub locked: bool,
+
+    /// Require Scarb.lock to be up to date, and run without accessing the network.
+    ///
+    /// Equivalent to specifying both `--locked` and `--offline`.
+    #[arg(long, hide_short_help = true)]
+    pub frozen: bool,
+
     /// Directory for all cache data stored by Scarb.
     #[arg(
         long,
@@ -81,7 +104,8 @@ pub struct ScarbArgs {
 impl ScarbArgs {
     /// Construct [`OutputFormat`] value from these arguments.
     pub fn output_format(&self) -> OutputFormat {
-        if self.json {
+        // Structured diagnostics are only useful if all other messages are structured too.
+        if self.json || self.message_format == MessageFormat::Json {
             OutputFormat::Json
         } else {
             OutputFormat::default()
@@ -136,6 +160,8 @@ pub enum Command {
     /// Manipulate packages cache.
     #[clap(subcommand)]
     Cache(CacheSubcommand),
+    /// Analyze current project and report errors, without generating any artifacts.
+    Check(CheckArgs),
     /// Remove generated artifacts.
     Clean,
     /// List installed commands.
@@ -146,6 +172,17 @@ pub enum Command {
     Fmt(FmtArgs),
     /// Create a new Scarb package in existing directory.
     Init(InitArgs),
+    /// Save an authentication token for a registry.
+    #[command(after_help = "\
+        The token is stored in the `credentials.toml` file in Scarb config directory and is used \
+        by subsequent commands talking to this registry. If the `SCARB_REGISTRY_TOKEN` \
+        environment variable is set, it takes precedence over stored tokens in commands explicitly \
+        targeting a registry, like `scarb publish`, but it is never sent to registries of \
+        dependencies.
+    ")]
+    Login(LoginArgs),
+    /// Remove a saved authentication token for a registry.
+    Logout(LogoutArgs),
     /// Print path to current Scarb.toml file to standard output.
     ManifestPath,
     /// Output the resolved dependencies of a package, the concrete used versions including
@@ -168,6 +205,12 @@ pub enum Command {
     Publish(PublishArgs),
     /// Run arbitrary package scripts.
     Run(ScriptsRunnerArgs),
+    /// Search packages in the registry.
+    #[command(after_help = "\
+        Local registries have no search API, so only package names are matched against the query \
+        when searching them.
+    ")]
+    Search(SearchArgs),
     /// Execute all unit and integration tests of a local package.
     #[command(after_help = "\
         By default, this command delegates to `scarb cairo-test`. This behaviour can be changed by \
@@ -177,7 +220,21 @@ pub enum Command {
     ")]
     Test(TestArgs),
     /// Update dependencies.
-    Update,
+    Update(UpdateArgs),
+    /// Vendor all registry and Git dependencies into a local registry directory.
+    #[command(after_help = "\
+        Vendored packages can be used instead of their original sources by adding the printed \
+        `[source]` configuration to the `config.toml` file in Scarb config directory. This allows \
+        building the workspace fully offline.
+    ")]
+    Vendor(VendorArgs),
+    /// Remove a pushed package version from the registry index.
+    #[command(after_help = "\
+        Yanked versions are not selected by dependency resolution, unless they are already locked \
+        in Scarb.lock, so packages depending on them continue to build. Package archives of yanked \
+        versions are not deleted.
+    ")]
+    Yank(YankArgs),
     /// External command (`scarb-*` executable).
     #[command(external_subcommand)]
     External(Vec<OsString>),
@@ -192,6 +249,39 @@ pub struct BuildArgs {
     /// Build tests.
     #[arg(short, long, default_value_t = false)]
     pub test: bool,
+
+    #[command(flatten)]
+    pub features: FeaturesSpec,
+
+    /// Number of compilation units to compile in parallel, defaults to the number of CPUs up to `build.jobs`.
+    #[arg(short, long, value_name = "N", env = "SCARB_BUILD_JOBS")]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Continue compiling other compilation units after the first one fails.
+    #[arg(long, default_value_t = false)]
+    pub keep_going: bool,
+}
+
+/// Arguments accepted by the `check` command.
+#[derive(Parser, Clone, Debug)]
+pub struct CheckArgs {
+    #[command(flatten)]
+    pub packages_filter: PackagesFilter,
+
+    /// Check tests.
+    #[arg(short, long, default_value_t = false)]
+    pub test: bool,
+
+    #[command(flatten)]
+    pub features: FeaturesSpec,
+
+    /// Number of compilation units to check in parallel, defaults to the number of CPUs up to `build.jobs`.
+    #[arg(short, long, value_name = "N", env = "SCARB_BUILD_JOBS")]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Continue checking other compilation units after the first one fails.
+    #[arg(long, default_value_t = false)]
+    pub keep_going: bool,
 }
 
 /// Arguments accepted by the `run` command.
@@ -230,6 +320,9 @@ pub struct MetadataArgs {
     /// Output information only about the workspace members and don't fetch dependencies.
     #[arg(long)]
     pub no_deps: bool,
+
+    #[command(flatten)]
+    pub features: FeaturesSpec,
 }
 
 /// Arguments accepted by the `new` command.
@@ -353,12 +446,86 @@ impl SectionArgs for RemoveSectionArgs {
     }
 }
 
+/// Arguments accepted by the `login` command.
+#[derive(Parser, Clone, Debug)]
+pub struct LoginArgs {
+    /// Registry index URL to save the token for.
+    #[arg(long, value_name = "URL")]
+    pub index: Url,
+
+    /// Authentication token, read from the standard input if not specified.
+    #[arg(value_name = "TOKEN")]
+    pub token: Option<String>,
+}
+
+/// Arguments accepted by the `logout` command.
+#[derive(Parser, Clone, Debug)]
+pub struct LogoutArgs {
+    /// Registry index URL to remove the token for.
+    #[arg(long, value_name = "URL")]
+    pub index: Url,
+}
+
+/// Arguments accepted by the `yank` command.
+#[derive(Parser, Clone, Debug)]
+pub struct YankArgs {
+    /// Package version to yank.
+    #[arg(value_name = "NAME@VERSION")]
+    pub package: PackageVersionSpec,
+
+    /// Registry index URL to yank the package version in.
+    #[arg(long, value_name = "URL")]
+    pub index: Url,
+
+    /// Undo a yank, putting the version back into the index.
+    #[arg(long)]
+    pub undo: bool,
+}
+
+/// Arguments accepted by the `search` command.
+#[derive(Parser, Clone, Debug)]
+pub struct SearchArgs {
+    /// Text to look for in package names and descriptions.
+    pub query: String,
+
+    /// Maximum number of results to show.
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub limit: usize,
+
+    /// Registry index URL to search in, the default registry is used if not specified.
+    #[arg(long, value_name = "URL")]
+    pub index: Option<Url>,
+}
+
+/// Arguments accepted by the `update` command.
+#[derive(Parser, Clone, Debug)]
+pub struct UpdateArgs {
+    /// Packages to update, all dependencies are updated if none are specified.
+    #[arg(value_name = "PACKAGE")]
+    pub packages: Vec<PackageName>,
+
+    /// Update a single package to exactly this version or Git revision.
+    #[arg(long, value_name = "PRECISE", requires = "packages")]
+    pub precise: Option<String>,
+}
+
+/// Arguments accepted by the `vendor` command.
+#[derive(Parser, Clone, Debug)]
+pub struct VendorArgs {
+    /// Directory to store vendored packages in.
+    #[arg(value_name = "DIR", default_value = "vendor")]
+    pub path: Utf8PathBuf,
+}
+
 /// Arguments accepted by the `test` command.
 #[derive(Parser, Clone, Debug)]
 pub struct TestArgs {
     #[command(flatten)]
     pub packages_filter: PackagesFilter,
 
+    #[command(flatten)]
+    pub features: FeaturesSpec,
+
     /// Arguments for the test program.
     #[clap(allow_hyphen_values = true)]
     pub args: Vec<OsString>,
@@ -398,8 +565,12 @@ pub struct PackageArgs {
 #[derive(Parser, Clone, Debug)]
 pub struct PublishArgs {
     /// Registry index URL to upload the package to.
-    #[arg(long, value_name = "URL")]
-    pub index: Url,
+    #[arg(long, value_name = "URL", conflicts_with = "registry")]
+    pub index: Option<Url>,
+
+    /// Name of the registry from the `[registries]` table to upload the package to.
+    #[arg(long, value_name = "NAME")]
+    pub registry: Option<SmolStr>,
 
     #[clap(flatten)]
     pub shared_args: PackageSharedArgs,
@@ -456,6 +627,53 @@ impl ProfileSpec {
     }
 }
 
+/// Features specifier.
+#[derive(Parser, Clone, Debug)]
+pub struct FeaturesSpec {
+    /// Comma separated list of features to activate.
+    #[arg(short = 'F', long, value_delimiter = ',', env = "SCARB_FEATURES")]
+    pub features: Vec<FeatureName>,
+    /// Activate all available features.
+    #[arg(long, env = "SCARB_ALL_FEATURES")]
+    pub all_features: bool,
+    /// Do not activate the `default` feature.
+    #[arg(long, env = "SCARB_NO_DEFAULT_FEATURES")]
+    pub no_default_features: bool,
+}
+
+/// Exact version of a package, in the `<NAME>@<VERSION>` form.
+#[derive(Clone, Debug)]
+pub struct PackageVersionSpec {
+    /// Package name.
+    pub name: PackageName,
+    /// Package version.
+    pub version: Version,
+}
+
+impl FromStr for PackageVersionSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, version) = s
+            .split_once('@')
+            .ok_or_else(|| anyhow!("expected package version in `<NAME>@<VERSION>` form"))?;
+        Ok(Self {
+            name: name.parse()?,
+            version: version.parse()?,
+        })
+    }
+}
+
+impl From<FeaturesSpec> for FeaturesOpts {
+    fn from(spec: FeaturesSpec) -> Self {
+        Self {
+            features: spec.features,
+            all_features: spec.all_features,
+            no_default_features: spec.no_default_features,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use clap::CommandFactory;
@@ -22,6 +22,9 @@ pub fn run(args: BuildArgs, config: &Config) -> Result<()> {
     let opts = CompileOpts {
         include_targets,
         exclude_targets,
+        features: args.features.into(),
+        jobs: args.jobs,
+        keep_going: args.keep_going,
     };
     ops::compile(packages, opts, &ws)
 }
@@ -0,0 +1,27 @@
+use std::io;
+
+use anyhow::{Context, Result};
+
+use scarb::core::Config;
+use scarb::ops;
+
+use crate::args::LoginArgs;
+
+#[tracing::instrument(skip_all, level = "info")]
+pub fn run(args: LoginArgs, config: &Config) -> Result<()> {
+    let token = match args.token {
+        Some(token) => token,
+        None => {
+            config
+                .ui()
+                .print(format!("please paste the token for {} below", args.index));
+            let mut token = String::new();
+            io::stdin()
+                .read_line(&mut token)
+                .context("failed to read token from standard input")?;
+            token
+        }
+    };
+
+    ops::registry_login(&args.index, token, config)
+}
@@ -0,0 +1,11 @@
+use anyhow::Result;
+
+use scarb::core::Config;
+use scarb::ops;
+
+use crate::args::LogoutArgs;
+
+#[tracing::instrument(skip_all, level = "info")]
+pub fn run(args: LogoutArgs, config: &Config) -> Result<()> {
+    ops::registry_logout(&args.index, config)
+}
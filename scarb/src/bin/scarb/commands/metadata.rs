@@ -13,6 +13,7 @@ pub fn run(args: MetadataArgs, config: &Config) -> Result<()> {
     let opts = ops::MetadataOptions {
         version: args.format_version,
         no_deps: args.no_deps,
+        features: args.features.into(),
     };
 
     let metadata = ops::collect_metadata(&opts, &ws)?;
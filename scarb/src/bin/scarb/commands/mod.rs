@@ -10,12 +10,15 @@ pub mod add;
 pub mod build;
 pub mod cache_clean;
 pub mod cache_path;
+pub mod check;
 pub mod clean;
 pub mod commands;
 pub mod external;
 pub mod fetch;
 pub mod fmt;
 pub mod init;
+pub mod login;
+pub mod logout;
 pub mod manifest_path;
 pub mod metadata;
 pub mod new;
@@ -23,8 +26,11 @@ pub mod package;
 pub mod publish;
 pub mod remove;
 pub mod run;
+pub mod search;
 pub mod test;
 mod update;
+pub mod vendor;
+pub mod yank;
 
 pub fn run(command: Command, config: &mut Config) -> Result<()> {
     use Command::*;
@@ -35,12 +41,15 @@ pub fn run(command: Command, config: &mut Config) -> Result<()> {
         Build(args) => build::run(args, config),
         Cache(CacheSubcommand::Clean) => cache_clean::run(config),
         Cache(CacheSubcommand::Path) => cache_path::run(config),
+        Check(args) => check::run(args, config),
         Clean => clean::run(config),
         Commands => commands::run(config),
         External(args) => external::run(args, config),
         Fetch => fetch::run(config),
         Fmt(args) => fmt::run(args, config),
         Init(args) => init::run(args, config),
+        Login(args) => login::run(args, config),
+        Logout(args) => logout::run(args, config),
         ManifestPath => manifest_path::run(config),
         Metadata(args) => metadata::run(args, config),
         New(args) => new::run(args, config),
@@ -48,7 +57,10 @@ pub fn run(command: Command, config: &mut Config) -> Result<()> {
         Publish(args) => publish::run(args, config),
         Remove(args) => remove::run(args, config),
         Run(args) => run::run(args, config),
+        Search(args) => search::run(args, config),
         Test(args) => test::run(args, config),
-        Update => update::run(config),
+        Update(args) => update::run(args, config),
+        Vendor(args) => vendor::run(args, config),
+        Yank(args) => yank::run(args, config),
     }
 }
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 
 use scarb::core::Config;
 use scarb::ops::{self, PackageOpts, PublishOpts};
@@ -10,8 +10,22 @@ pub fn run(args: PublishArgs, config: &Config) -> Result<()> {
     let ws = ops::read_workspace(config.manifest_path(), config)?;
     let package = args.packages_filter.match_one(&ws)?;
 
+    let index_url = match (args.index, args.registry) {
+        (Some(index), _) => index,
+        (None, Some(registry)) => ws.registries().get(&registry).cloned().ok_or_else(|| {
+            anyhow!("registry `{registry}` is not defined in the `[registries]` table")
+        })?,
+        (None, None) => match ws.registries().default_registry() {
+            Some(index) => index.clone(),
+            None => bail!(
+                "no registry to publish to\n\
+                help: pass `--index` or `--registry`, or mark one of `[registries]` as default"
+            ),
+        },
+    };
+
     let ops = PublishOpts {
-        index_url: args.index,
+        index_url,
         package_opts: PackageOpts {
             allow_dirty: args.shared_args.allow_dirty,
             verify: !args.shared_args.no_verify,
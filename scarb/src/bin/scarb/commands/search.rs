@@ -0,0 +1,57 @@
+use anyhow::Result;
+use serde::{Serialize, Serializer};
+
+use scarb::core::{Config, SearchResult, SourceId};
+use scarb::ops::{self, SearchOpts};
+use scarb_ui::Message;
+
+use crate::args::SearchArgs;
+
+#[tracing::instrument(skip_all, level = "info")]
+pub fn run(args: SearchArgs, config: &Config) -> Result<()> {
+    let opts = SearchOpts {
+        index_url: args
+            .index
+            .unwrap_or_else(|| SourceId::default_registry().url.clone()),
+        limit: args.limit,
+    };
+
+    let results = ops::search(&args.query, &opts, config)?;
+
+    let width = results
+        .iter()
+        .map(|r| heading(r).len())
+        .max()
+        .unwrap_or_default();
+    for result in &results {
+        config.ui().print(SearchResultMessage { result, width });
+    }
+
+    Ok(())
+}
+
+fn heading(result: &SearchResult) -> String {
+    format!("{} = \"{}\"", result.name, result.version)
+}
+
+struct SearchResultMessage<'a> {
+    result: &'a SearchResult,
+    /// Width of the `name = "version"` column, used to align descriptions.
+    width: usize,
+}
+
+impl<'a> Message for SearchResultMessage<'a> {
+    fn text(self) -> String {
+        let heading = heading(self.result);
+        match &self.result.description {
+            Some(description) => {
+                format!("{heading:<width$}    # {description}", width = self.width)
+            }
+            None => heading,
+        }
+    }
+
+    fn structured<S: Serializer>(self, ser: S) -> Result<S::Ok, S::Error> {
+        self.result.serialize(ser)
+    }
+}
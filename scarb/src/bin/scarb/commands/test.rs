@@ -8,8 +8,11 @@ use crate::args::TestArgs;
 #[tracing::instrument(skip_all, level = "info")]
 pub fn run(args: TestArgs, config: &Config) -> Result<()> {
     let ws = ops::read_workspace(config.manifest_path(), config)?;
+    let features = args.features.into();
     args.packages_filter
         .match_many(&ws)?
         .iter()
-        .try_for_each(|package| ops::execute_test_subcommand(package, &args.args, &ws).map(|_| ()))
+        .try_for_each(|package| {
+            ops::execute_test_subcommand(package, &args.args, &ws, &features).map(|_| ())
+        })
 }
@@ -4,10 +4,17 @@ use scarb::core::Config;
 use scarb::ops;
 use scarb::ops::ResolveOpts;
 
+use crate::args::UpdateArgs;
+
 #[tracing::instrument(skip_all, level = "info")]
-pub fn run(config: &Config) -> Result<()> {
+pub fn run(args: UpdateArgs, config: &Config) -> Result<()> {
     let ws = ops::read_workspace(config.manifest_path(), config)?;
-    let opts = ResolveOpts { update: true };
+    let opts = ResolveOpts {
+        update: true,
+        update_packages: args.packages.into_iter().collect(),
+        precise: args.precise,
+        ..Default::default()
+    };
     ops::resolve_workspace_with_opts(&ws, &opts)?;
     Ok(())
 }
@@ -0,0 +1,25 @@
+use anyhow::Result;
+use indoc::formatdoc;
+
+use scarb::core::Config;
+use scarb::ops::{self, VendorOpts};
+
+use crate::args::VendorArgs;
+
+#[tracing::instrument(skip_all, level = "info")]
+pub fn run(args: VendorArgs, config: &Config) -> Result<()> {
+    let ws = ops::read_workspace(config.manifest_path(), config)?;
+    let opts = VendorOpts {
+        destination: args.path,
+    };
+
+    let source_config = ops::vendor(&opts, &ws)?;
+    let source_config = source_config.trim_end();
+
+    config.ui().print(formatdoc! {r#"
+        To use vendored sources, add this to the `config.toml` file in Scarb config directory:
+
+        {source_config}"#
+    });
+    Ok(())
+}
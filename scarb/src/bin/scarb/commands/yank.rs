@@ -0,0 +1,16 @@
+use anyhow::Result;
+
+use scarb::core::Config;
+use scarb::ops::{self, YankOpts};
+
+use crate::args::YankArgs;
+
+#[tracing::instrument(skip_all, level = "info")]
+pub fn run(args: YankArgs, config: &Config) -> Result<()> {
+    let opts = YankOpts {
+        index_url: args.index,
+        undo: args.undo,
+    };
+
+    ops::yank(args.package.name, args.package.version, &opts, config)
+}
@@ -69,7 +69,10 @@ fn cli_main(args: ScarbArgs) -> Result<()> {
         .target_dir_override(args.target_dir)
         .ui_verbosity(ui_verbosity)
         .ui_output_format(ui_output_format)
+        .message_format(args.message_format)
         .offline(args.offline)
+        .locked(args.locked)
+        .frozen(args.frozen)
         .log_filter_directive(env::var_os("SCARB_LOG"))
         .profile(args.profile_spec.determine()?)
         .build()?;
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use cairo_lang_compiler::db::RootDatabase;
 use cairo_lang_sierra_to_casm::metadata::calc_metadata;
+use camino::Utf8PathBuf;
 use serde::{Deserialize, Serialize};
 use tracing::trace_span;
 
@@ -40,7 +41,7 @@ impl Compiler for LibCompiler {
         unit: CompilationUnit,
         db: &mut RootDatabase,
         ws: &Workspace<'_>,
-    ) -> Result<()> {
+    ) -> Result<Vec<Utf8PathBuf>> {
         let props: Props = unit.target().props()?;
         if !props.sierra && !props.casm && !props.sierra_text {
             ws.config().ui().warn(
@@ -50,6 +51,7 @@ impl Compiler for LibCompiler {
         }
 
         let target_dir = unit.target_dir(ws);
+        let mut outputs = Vec::new();
 
         let compiler_config = build_compiler_config(&unit, ws);
 
@@ -62,7 +64,7 @@ impl Compiler for LibCompiler {
         };
 
         if props.sierra {
-            write_json(
+            let path = write_json(
                 format!("{}.sierra.json", unit.target().name).as_str(),
                 "output file",
                 &target_dir,
@@ -72,16 +74,17 @@ impl Compiler for LibCompiler {
             .with_context(|| {
                 format!("failed to serialize Sierra program {}", unit.target().name)
             })?;
+            outputs.push(path);
         }
 
         if props.sierra_text {
-            write_string(
+            outputs.push(write_string(
                 format!("{}.sierra", unit.target().name).as_str(),
                 "output file",
                 &target_dir,
                 ws,
                 &sierra_program,
-            )?;
+            )?);
         }
 
         if props.casm {
@@ -100,15 +103,15 @@ impl Compiler for LibCompiler {
                 cairo_lang_sierra_to_casm::compiler::compile(&program, &metadata, gas_usage_check)?
             };
 
-            write_string(
+            outputs.push(write_string(
                 format!("{}.casm", unit.target().name).as_str(),
                 "output file",
                 &target_dir,
                 ws,
                 cairo_program,
-            )?;
+            )?);
         }
 
-        Ok(())
+        Ok(outputs)
     }
 }
@@ -14,6 +14,7 @@ use cairo_lang_starknet::casm_contract_class::CasmContractClass;
 use cairo_lang_starknet::contract::{find_contracts, ContractDeclaration};
 use cairo_lang_starknet::contract_class::{compile_prepared_db, ContractClass};
 use cairo_lang_utils::{Upcast, UpcastMut};
+use camino::Utf8PathBuf;
 use indoc::{formatdoc, writedoc};
 use itertools::{izip, Itertools};
 use serde::{Deserialize, Serialize};
@@ -194,7 +195,7 @@ impl Compiler for StarknetContractCompiler {
         unit: CompilationUnit,
         db: &mut RootDatabase,
         ws: &Workspace<'_>,
-    ) -> Result<()> {
+    ) -> Result<Vec<Utf8PathBuf>> {
         let props: Props = unit.target().props()?;
         if !props.sierra && !props.casm {
             ws.config().ui().warn(
@@ -257,6 +258,7 @@ impl Compiler for StarknetContractCompiler {
             classes.iter().map(|_| None).collect()
         };
 
+        let mut outputs = Vec::new();
         let mut artifacts = StarknetArtifacts::default();
         let mut file_stem_calculator = ContractFileStemCalculator::new(contract_paths);
 
@@ -279,14 +281,26 @@ impl Compiler for StarknetContractCompiler {
 
             if props.sierra {
                 let file_name = format!("{file_stem}.contract_class.json");
-                write_json(&file_name, "output file", &target_dir, ws, &class)?;
+                outputs.push(write_json(
+                    &file_name,
+                    "output file",
+                    &target_dir,
+                    ws,
+                    &class,
+                )?);
                 artifact.artifacts.sierra = Some(file_name);
             }
 
             // if props.casm
             if let Some(casm_class) = casm_class {
                 let file_name = format!("{file_stem}.compiled_contract_class.json");
-                write_json(&file_name, "output file", &target_dir, ws, &casm_class)?;
+                outputs.push(write_json(
+                    &file_name,
+                    "output file",
+                    &target_dir,
+                    ws,
+                    &casm_class,
+                )?);
                 artifact.artifacts.casm = Some(file_name);
             }
 
@@ -295,15 +309,15 @@ impl Compiler for StarknetContractCompiler {
 
         artifacts.finish();
 
-        write_json(
+        outputs.push(write_json(
             &format!("{}.starknet_artifacts.json", target_name),
             "starknet artifacts file",
             &target_dir,
             ws,
             &artifacts,
-        )?;
+        )?);
 
-        Ok(())
+        Ok(outputs)
     }
 }
 
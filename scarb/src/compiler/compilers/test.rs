@@ -1,6 +1,7 @@
 use anyhow::Result;
 use cairo_lang_compiler::db::RootDatabase;
 use cairo_lang_test_plugin::compile_test_prepared_db;
+use camino::Utf8PathBuf;
 use tracing::trace_span;
 
 use crate::compiler::helpers::{
@@ -21,7 +22,7 @@ impl Compiler for TestCompiler {
         unit: CompilationUnit,
         db: &mut RootDatabase,
         ws: &Workspace<'_>,
-    ) -> Result<()> {
+    ) -> Result<Vec<Utf8PathBuf>> {
         let target_dir = unit.target_dir(ws);
 
         let test_crate_ids = collect_main_crate_ids(&unit, db);
@@ -42,7 +43,7 @@ impl Compiler for TestCompiler {
             compile_test_prepared_db(db, starknet, main_crate_ids, test_crate_ids)?
         };
 
-        {
+        let output = {
             let _ = trace_span!("serialize_test").enter();
             let file_name = format!("{}.test.json", unit.target().name);
             write_json(
@@ -51,9 +52,9 @@ impl Compiler for TestCompiler {
                 &target_dir,
                 ws,
                 &test_compilation,
-            )?;
-        }
+            )?
+        };
 
-        Ok(())
+        Ok(vec![output])
     }
 }
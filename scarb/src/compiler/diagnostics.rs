@@ -0,0 +1,231 @@
+use cairo_lang_diagnostics::Severity;
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::ValueEnum;
+
+use scarb_ui::components::{
+    DiagnosticMessage, DiagnosticPosition, DiagnosticSeverity, DiagnosticSpan,
+};
+
+use crate::compiler::CompilationUnit;
+use crate::core::PackageId;
+
+const LOCATION_PREFIX: &str = " --> ";
+const NOTE_PREFIX: &str = "note: ";
+
+/// Format in which compiler diagnostics are printed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, ValueEnum)]
+pub enum MessageFormat {
+    /// Print diagnostics as human-readable text, or as plain messages in JSON output mode.
+    #[default]
+    Human,
+    /// Print each diagnostic as a JSON object with all its details, regardless of output mode.
+    ///
+    /// The `scarb` CLI switches to JSON output mode when this format is selected.
+    Json,
+}
+
+/// Information about a compilation unit needed to describe diagnostics emitted while compiling it.
+#[derive(Clone, Debug)]
+pub(crate) struct DiagnosticsContext {
+    compilation_unit_id: String,
+    /// Root directories of component packages, sorted from the deepest one.
+    package_roots: Vec<(Utf8PathBuf, PackageId)>,
+}
+
+impl DiagnosticsContext {
+    pub fn new(unit: &CompilationUnit) -> Self {
+        let mut package_roots = unit
+            .components
+            .iter()
+            .map(|component| (component.package.root().to_path_buf(), component.package.id))
+            .collect::<Vec<_>>();
+        package_roots.sort_by_key(|(root, _)| std::cmp::Reverse(root.components().count()));
+        Self {
+            compilation_unit_id: unit.id(),
+            package_roots,
+        }
+    }
+
+    /// Build a structured diagnostic from the text rendered by the Cairo compiler.
+    pub fn diagnostic(&self, severity: Severity, rendered: &str) -> DiagnosticMessage {
+        let parsed = ParsedDiagnostic::parse(rendered);
+        let package_id = parsed.file.as_deref().and_then(|file| {
+            self.package_roots
+                .iter()
+                .find(|(root, _)| Utf8Path::new(file).starts_with(root))
+                .map(|(_, id)| id.to_serialized_string())
+        });
+        DiagnosticMessage {
+            severity: match severity {
+                Severity::Error => DiagnosticSeverity::Error,
+                Severity::Warning => DiagnosticSeverity::Warning,
+            },
+            message: parsed.message,
+            file: parsed.file,
+            spans: parsed.span.into_iter().collect(),
+            package_id,
+            compilation_unit_id: Some(self.compilation_unit_id.clone()),
+            notes: parsed.notes,
+            rendered: rendered.to_string(),
+        }
+    }
+}
+
+/// Parts of a diagnostic, recovered from its rendered text.
+///
+/// The Cairo diagnostics reporter only passes diagnostics rendered as text, in the following form:
+/// ```text
+/// Message, possibly spanning multiple lines.
+///  --> path/to/file.cairo:1:5
+/// fn f() -> felt252 { x }
+///     ^
+/// note: optional notes
+/// ```
+/// The span is reconstructed from the location line and the marker below the source line.
+/// Compiler only marks the first line of a span, so multiline spans end at the end of this line.
+#[derive(Debug, Default, Eq, PartialEq)]
+struct ParsedDiagnostic {
+    message: String,
+    file: Option<String>,
+    span: Option<DiagnosticSpan>,
+    notes: Vec<String>,
+}
+
+impl ParsedDiagnostic {
+    fn parse(rendered: &str) -> Self {
+        let lines = rendered.lines().collect::<Vec<_>>();
+        let location = lines.iter().enumerate().find_map(|(i, line)| {
+            let location = line.strip_prefix(LOCATION_PREFIX)?;
+            let mut parts = location.rsplitn(3, ':');
+            let column = parts.next()?.parse::<usize>().ok()?;
+            let line = parts.next()?.parse::<usize>().ok()?;
+            let file = parts.next()?;
+            Some((i, file, DiagnosticPosition { line, column }))
+        });
+
+        let Some((i, file, start)) = location else {
+            let (message, notes) = split_notes(&lines);
+            return Self {
+                message,
+                notes,
+                ..Default::default()
+            };
+        };
+
+        let marker = lines
+            .get(i + 2)
+            .map(|line| line.trim())
+            .filter(|line| line.starts_with('^'));
+        let end = DiagnosticPosition {
+            line: start.line,
+            column: start.column + marker.map_or(1, |marker| marker.chars().count()) - 1,
+        };
+        let rest = if marker.is_some() { i + 3 } else { i + 1 };
+        let (_, notes) = split_notes(lines.get(rest..).unwrap_or_default());
+
+        Self {
+            message: lines[..i].join("\n"),
+            file: Some(file.to_string()),
+            span: Some(DiagnosticSpan { start, end }),
+            notes,
+        }
+    }
+}
+
+/// Split lines into the message and notes, which start with `note: ` prefix.
+///
+/// Lines following a note which do not start a new note are continuation of this note.
+fn split_notes(lines: &[&str]) -> (String, Vec<String>) {
+    let mut message = Vec::new();
+    let mut notes: Vec<String> = Vec::new();
+    for line in lines {
+        if let Some(note) = line.strip_prefix(NOTE_PREFIX) {
+            notes.push(note.to_string());
+        } else if let Some(note) = notes.last_mut() {
+            note.push('\n');
+            note.push_str(line);
+        } else {
+            message.push(*line);
+        }
+    }
+    for note in &mut notes {
+        note.truncate(note.trim_end().len());
+    }
+    let message = message.join("\n").trim_end().to_string();
+    (message, notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use scarb_ui::components::{DiagnosticPosition, DiagnosticSpan};
+
+    use super::ParsedDiagnostic;
+
+    fn span(line: usize, start: usize, end: usize) -> Option<DiagnosticSpan> {
+        Some(DiagnosticSpan {
+            start: DiagnosticPosition {
+                line,
+                column: start,
+            },
+            end: DiagnosticPosition { line, column: end },
+        })
+    }
+
+    #[test]
+    fn parse_diagnostic_with_location() {
+        let parsed = ParsedDiagnostic::parse(indoc! {"
+            Identifier not found.
+             --> /path/to/src/lib.cairo:3:25
+            fn hello() -> felt252 { dep::forty_two() }
+                                    ^*^
+        "});
+        assert_eq!(
+            parsed,
+            ParsedDiagnostic {
+                message: "Identifier not found.".to_string(),
+                file: Some("/path/to/src/lib.cairo".to_string()),
+                span: span(3, 25, 27),
+                notes: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_diagnostic_with_single_character_span_and_notes() {
+        let parsed = ParsedDiagnostic::parse(indoc! {"
+            Unused variable.
+            Consider ignoring by prefixing with `_`.
+             --> C:\\src\\lib.cairo:1:5
+            let x = 1;
+                ^
+            note: first note
+            note: second note
+            spanning two lines
+        "});
+        assert_eq!(
+            parsed,
+            ParsedDiagnostic {
+                message: "Unused variable.\nConsider ignoring by prefixing with `_`.".to_string(),
+                file: Some("C:\\src\\lib.cairo".to_string()),
+                span: span(1, 5, 5),
+                notes: vec![
+                    "first note".to_string(),
+                    "second note\nspanning two lines".to_string()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_diagnostic_without_location() {
+        let parsed = ParsedDiagnostic::parse("Failed to get main module file.\n");
+        assert_eq!(
+            parsed,
+            ParsedDiagnostic {
+                message: "Failed to get main module file.".to_string(),
+                ..Default::default()
+            }
+        );
+    }
+}
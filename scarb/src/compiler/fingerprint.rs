@@ -0,0 +1,142 @@
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use scarb_ui::CapturedOutput;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::compiler::CompilationUnit;
+use crate::core::Workspace;
+use crate::flock::Filesystem;
+use crate::internal::stable_hash::StableHasher;
+
+const FINGERPRINT_DIR_NAME: &str = ".fingerprint";
+
+/// A hash of all inputs of a [`CompilationUnit`].
+///
+/// If the fingerprint of a unit is equal to the one stored after its last successful compilation,
+/// and all output files written by that compilation still exist, the unit is considered _fresh_
+/// and does not need to be compiled again.
+/// Fingerprints are stored in `target/<profile>/.fingerprint` directory, in files named after
+/// unit IDs, along with the list of output files and messages printed during compilation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Fingerprint(String);
+
+impl Fingerprint {
+    /// Compute the fingerprint of a compilation unit.
+    ///
+    /// The fingerprint covers [`CompilationUnit::digest`], compiler configuration, the cfg set,
+    /// loaded Cairo plugins, Scarb and Cairo versions, output and message formats, and
+    /// modification times of all source files of packages coming from the local filesystem.
+    /// Packages from other sources are immutable, so they are identified by their IDs only.
+    pub fn compute(unit: &CompilationUnit, ws: &Workspace<'_>) -> Result<Self> {
+        let mut hasher = StableHasher::new();
+
+        let version = crate::version::get();
+        version.version.hash(&mut hasher);
+        version.cairo.version.hash(&mut hasher);
+
+        unit.digest().hash(&mut hasher);
+        unit.compiler_config.hash(&mut hasher);
+        for cfg in unit.cfg_set.iter() {
+            cfg.key.hash(&mut hasher);
+            cfg.value.hash(&mut hasher);
+        }
+        for plugin in &unit.cairo_plugins {
+            plugin.package.id.hash(&mut hasher);
+        }
+        // Stored messages are replayed in the format they were printed in.
+        ws.config().ui().output_format().hash(&mut hasher);
+        ws.config().message_format().hash(&mut hasher);
+
+        let target_dir = ws.target_dir().path_unchecked();
+        for component in &unit.components {
+            if !component.package.id.source_id.is_path() {
+                continue;
+            }
+
+            hash_mtime(component.package.manifest_path(), &mut hasher)?;
+
+            let walker = WalkDir::new(component.target.source_root())
+                .sort_by_file_name()
+                .into_iter()
+                // Do not look into the target directory if sources are placed in package root.
+                .filter_entry(|entry| entry.path() != target_dir);
+            for entry in walker {
+                let entry = entry.context("failed to traverse source directory")?;
+                if entry.file_type().is_file() {
+                    let path = Utf8Path::from_path(entry.path())
+                        .context("source path is not valid UTF-8")?;
+                    hash_mtime(path, &mut hasher)?;
+                }
+            }
+        }
+
+        Ok(Self(hasher.finish_as_short_hash()))
+    }
+
+    /// Check whether the unit is fresh, returning messages printed during its last compilation.
+    ///
+    /// The unit is fresh if this fingerprint is equal to the one stored for the unit, and none of
+    /// the output files of its last compilation has been removed since.
+    pub fn check_fresh(
+        &self,
+        unit: &CompilationUnit,
+        ws: &Workspace<'_>,
+    ) -> Option<CapturedOutput> {
+        let path = fingerprint_dir(unit, ws).path_unchecked().join(unit.id());
+        let stored: StoredFingerprint = fs::read_to_string(path)
+            .ok()
+            .and_then(|stored| serde_json::from_str(&stored).ok())?;
+        let is_fresh =
+            stored.fingerprint == self.0 && stored.outputs.iter().all(|output| output.exists());
+        is_fresh.then(|| stored.messages.into())
+    }
+
+    /// Store this fingerprint for the unit, marking it as successfully compiled.
+    pub fn store(
+        &self,
+        unit: &CompilationUnit,
+        outputs: Vec<Utf8PathBuf>,
+        messages: &CapturedOutput,
+        ws: &Workspace<'_>,
+    ) -> Result<()> {
+        let stored = StoredFingerprint {
+            fingerprint: self.0.clone(),
+            outputs,
+            messages: messages.as_ref().to_string(),
+        };
+        let mut file =
+            fingerprint_dir(unit, ws).open_rw(unit.id(), "fingerprint file", ws.config())?;
+        serde_json::to_writer(&mut *file, &stored).context("failed to serialize fingerprint")?;
+        file.flush().context("failed to write fingerprint file")
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredFingerprint {
+    fingerprint: String,
+    outputs: Vec<Utf8PathBuf>,
+    messages: String,
+}
+
+fn fingerprint_dir(unit: &CompilationUnit, ws: &Workspace<'_>) -> Filesystem {
+    unit.target_dir(ws).child(FINGERPRINT_DIR_NAME)
+}
+
+fn hash_mtime(path: &Utf8Path, hasher: &mut impl Hasher) -> Result<()> {
+    let mtime = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .with_context(|| format!("failed to read modification time of: {path}"))?;
+    path.hash(hasher);
+    mtime
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .hash(hasher);
+    Ok(())
+}
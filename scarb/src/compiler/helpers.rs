@@ -7,23 +7,33 @@ use cairo_lang_compiler::CompilerConfig;
 use cairo_lang_diagnostics::Severity;
 use cairo_lang_filesystem::db::FilesGroup;
 use cairo_lang_filesystem::ids::{CrateId, CrateLongId};
+use camino::Utf8PathBuf;
 use serde::Serialize;
 use std::io::{BufWriter, Write};
 
-use crate::compiler::CompilationUnit;
+use scarb_ui::{OutputFormat, Ui};
+
+use crate::compiler::{CompilationUnit, DiagnosticsContext, MessageFormat};
 use crate::core::Workspace;
 use crate::flock::Filesystem;
 
 pub fn build_compiler_config<'c>(unit: &CompilationUnit, ws: &Workspace<'c>) -> CompilerConfig<'c> {
     let diagnostics_reporter = DiagnosticsReporter::callback({
         let config = ws.config();
+        let context = DiagnosticsContext::new(unit);
 
-        |severity: Severity, diagnostic: String| {
+        move |severity: Severity, diagnostic: String| {
             let msg = diagnostic.clone();
             let msg = msg.strip_suffix('\n').unwrap_or(diagnostic.as_str());
-            match severity {
-                Severity::Error => config.ui().error(msg),
-                Severity::Warning => config.ui().warn(msg),
+            match config.message_format() {
+                MessageFormat::Human => match severity {
+                    Severity::Error => config.ui().error(msg),
+                    Severity::Warning => config.ui().warn(msg),
+                },
+                MessageFormat::Json => {
+                    let ui = Ui::new(config.ui().verbosity(), OutputFormat::Json);
+                    ui.print(context.diagnostic(severity, msg));
+                }
             };
         }
     });
@@ -51,28 +61,31 @@ pub fn collect_all_crate_ids(unit: &CompilationUnit, db: &RootDatabase) -> Vec<C
         .collect()
 }
 
+/// Write the value as JSON to the file, returning the path of the written file.
 pub fn write_json(
     file_name: &str,
     description: &str,
     target_dir: &Filesystem,
     ws: &Workspace<'_>,
     value: impl Serialize,
-) -> Result<()> {
+) -> Result<Utf8PathBuf> {
     let file = target_dir.open_rw(file_name, description, ws.config())?;
+    let path = file.path().to_path_buf();
     let file = BufWriter::new(&*file);
     serde_json::to_writer(file, &value)
         .with_context(|| format!("failed to serialize {file_name}"))?;
-    Ok(())
+    Ok(path)
 }
 
+/// Write the value as a string to the file, returning the path of the written file.
 pub fn write_string(
     file_name: &str,
     description: &str,
     target_dir: &Filesystem,
     ws: &Workspace<'_>,
     value: impl ToString,
-) -> Result<()> {
+) -> Result<Utf8PathBuf> {
     let mut file = target_dir.open_rw(file_name, description, ws.config())?;
     file.write_all(value.to_string().as_bytes())?;
-    Ok(())
+    Ok(file.path().to_path_buf())
 }
@@ -1,7 +1,10 @@
 use anyhow::Result;
 use cairo_lang_compiler::db::RootDatabase;
+use camino::Utf8PathBuf;
 
 pub use compilation_unit::*;
+pub use diagnostics::*;
+pub use fingerprint::*;
 pub use profile::*;
 pub use repository::*;
 
@@ -10,6 +13,8 @@ use crate::core::{TargetKind, Workspace};
 mod compilation_unit;
 mod compilers;
 pub mod db;
+mod diagnostics;
+mod fingerprint;
 pub mod helpers;
 pub mod plugin;
 mod profile;
@@ -18,10 +23,11 @@ mod repository;
 pub trait Compiler: Sync {
     fn target_kind(&self) -> TargetKind;
 
+    /// Compile the unit, returning paths of all written output files.
     fn compile(
         &self,
         unit: CompilationUnit,
         db: &mut RootDatabase,
         ws: &Workspace<'_>,
-    ) -> Result<()>;
+    ) -> Result<Vec<Utf8PathBuf>>;
 }
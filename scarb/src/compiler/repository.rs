@@ -4,6 +4,7 @@ use std::fmt;
 
 use anyhow::{bail, Result};
 use cairo_lang_compiler::db::RootDatabase;
+use camino::Utf8PathBuf;
 use itertools::Itertools;
 use smol_str::SmolStr;
 
@@ -46,7 +47,7 @@ impl CompilerRepository {
         unit: CompilationUnit,
         db: &mut RootDatabase,
         ws: &Workspace<'_>,
-    ) -> Result<()> {
+    ) -> Result<Vec<Utf8PathBuf>> {
         let target_kind = &unit.target().kind;
         let Some(compiler) = self.compilers.get(target_kind.as_str()) else {
             bail!("unknown compiler for target `{target_kind}`");
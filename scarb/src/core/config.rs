@@ -14,10 +14,10 @@ use which::which_in;
 use scarb_ui::{OutputFormat, Ui, Verbosity};
 
 use crate::compiler::plugin::CairoPluginRepository;
-use crate::compiler::{CompilerRepository, Profile};
-use crate::core::AppDirs;
+use crate::compiler::{CompilerRepository, MessageFormat, Profile};
 #[cfg(doc)]
 use crate::core::Workspace;
+use crate::core::{AppDirs, GlobalConfig};
 use crate::flock::AdvisoryLock;
 use crate::internal::fsx;
 use crate::SCARB_ENV;
@@ -32,12 +32,15 @@ pub struct Config {
     target_dir_override: Option<Utf8PathBuf>,
     app_exe: OnceCell<PathBuf>,
     ui: Ui,
+    message_format: MessageFormat,
     creation_time: Instant,
     // HACK: This should be the lifetime of Config itself, but we cannot express that, so we
     //   put static lifetime here and transmute in getter function.
     package_cache_lock: OnceCell<AdvisoryLock<'static>>,
     log_filter_directive: OsString,
     offline: bool,
+    locked: bool,
+    frozen: bool,
     compilers: CompilerRepository,
     cairo_plugins: CairoPluginRepository,
     // This is a Dojo-specific feature that will be removed once Dojo is decoupled from Scarb as a library.
@@ -46,6 +49,7 @@ pub struct Config {
     tokio_handle: OnceCell<Handle>,
     profile: Profile,
     http_client: OnceCell<reqwest::Client>,
+    global_config: OnceCell<GlobalConfig>,
 }
 
 impl Config {
@@ -84,10 +88,13 @@ impl Config {
             target_dir_override: b.target_dir_override,
             app_exe: OnceCell::new(),
             ui,
+            message_format: b.message_format,
             creation_time,
             package_cache_lock: OnceCell::new(),
             log_filter_directive: b.log_filter_directive.unwrap_or_default(),
             offline: b.offline,
+            locked: b.locked,
+            frozen: b.frozen,
             compilers,
             cairo_plugins: compiler_plugins,
             custom_source_patches: b.custom_source_patches,
@@ -95,6 +102,7 @@ impl Config {
             tokio_handle,
             profile,
             http_client: OnceCell::new(),
+            global_config: OnceCell::new(),
         })
     }
 
@@ -116,6 +124,14 @@ impl Config {
         &self.dirs
     }
 
+    /// Returns settings read from the `config.toml` file in the global config directory.
+    ///
+    /// The file is read lazily on first access, and it is fine for it to not exist.
+    pub fn global_config(&self) -> Result<&GlobalConfig> {
+        self.global_config
+            .get_or_try_init(|| GlobalConfig::load(self.dirs().config_dir.path_unchecked()))
+    }
+
     pub fn target_dir_override(&self) -> Option<&Utf8PathBuf> {
         self.target_dir_override.as_ref()
     }
@@ -174,6 +190,11 @@ impl Config {
         self.ui.clone()
     }
 
+    /// Format in which compiler diagnostics should be printed.
+    pub fn message_format(&self) -> MessageFormat {
+        self.message_format
+    }
+
     pub fn elapsed_time(&self) -> Duration {
         self.creation_time.elapsed()
     }
@@ -208,12 +229,22 @@ impl Config {
         })
     }
 
-    /// States whether the _Offline Mode_ is turned on.
+    /// States whether the _Offline Mode_ is turned on, either directly or by [`Self::frozen`].
     ///
     /// For checking whether Scarb can communicate with the network, prefer to use
     /// [`Self::network_allowed`], as it might pull information from other sources in the future.
     pub const fn offline(&self) -> bool {
-        self.offline
+        self.offline || self.frozen
+    }
+
+    /// If `true`, Scarb must not modify the lockfile, and should fail if it needs to be updated.
+    pub const fn locked(&self) -> bool {
+        self.locked || self.frozen
+    }
+
+    /// States whether Scarb is running with both lockfile updates and network access forbidden.
+    pub const fn frozen(&self) -> bool {
+        self.frozen
     }
 
     /// If `false`, Scarb should never access the network, but otherwise it should continue
@@ -241,7 +272,9 @@ impl Config {
     /// Returns handle to the global HTTP client.
     ///
     /// The global client maintains an internal connection pool, and is preconfigured with known
-    /// user agent etc.
+    /// user agent, connection timeout from the `[net]` table of the global config etc.
+    /// The client does not limit the total time of requests, so that large files can be
+    /// downloaded over slow connections; callers apply the `net.timeout` setting themselves.
     ///
     /// It is fine to clone the returned instance, because it contains [`Arc`] inside.
     ///
@@ -254,8 +287,10 @@ impl Config {
     pub fn http(&self) -> Result<reqwest::Client> {
         self.http_client
             .get_or_try_init(|| {
+                let timeout = self.global_config()?.net.timeout();
                 reqwest::Client::builder()
                     .user_agent(USER_AGENT)
+                    .connect_timeout(timeout)
                     .build()
                     .context("failed to create HTTP client")
             })
@@ -296,7 +331,10 @@ pub struct ConfigBuilder {
     target_dir_override: Option<Utf8PathBuf>,
     ui_verbosity: Verbosity,
     ui_output_format: OutputFormat,
+    message_format: MessageFormat,
     offline: bool,
+    locked: bool,
+    frozen: bool,
     log_filter_directive: Option<OsString>,
     compilers: Option<CompilerRepository>,
     cairo_plugins: Option<CairoPluginRepository>,
@@ -315,7 +353,10 @@ impl ConfigBuilder {
             target_dir_override: None,
             ui_verbosity: Verbosity::Normal,
             ui_output_format: OutputFormat::Text,
+            message_format: MessageFormat::default(),
             offline: false,
+            locked: false,
+            frozen: false,
             log_filter_directive: None,
             compilers: None,
             cairo_plugins: None,
@@ -368,11 +409,26 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn message_format(mut self, message_format: MessageFormat) -> Self {
+        self.message_format = message_format;
+        self
+    }
+
     pub fn offline(mut self, offline: bool) -> Self {
         self.offline = offline;
         self
     }
 
+    pub fn locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    pub fn frozen(mut self, frozen: bool) -> Self {
+        self.frozen = frozen;
+        self
+    }
+
     pub fn log_filter_directive(
         mut self,
         log_filter_directive: Option<impl Into<OsString>>,
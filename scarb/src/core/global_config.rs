@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::{ensure, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+use smol_str::SmolStr;
+use url::Url;
+
+use crate::core::TomlRegistry;
+use crate::internal::fsx;
+
+pub const GLOBAL_CONFIG_FILE_NAME: &str = "config.toml";
+
+/// The `config.toml` file stored in Scarb config directory.
+///
+/// It holds user-wide settings, which apply to all workspaces, for example:
+///
+/// ```toml
+/// [registries.internal]
+/// index = "https://example.com/index/"
+///
+/// [source."scarb.xyz"]
+/// replace-with = "mirror"
+///
+/// [source.mirror]
+/// registry = "https://mirror.example.com/index/"
+///
+/// [net]
+/// jobs = 4
+///
+/// [build]
+/// jobs = 2
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GlobalConfig {
+    /// Registries which can be referred to by name, see [`TomlManifest::registries`].
+    ///
+    /// [`TomlManifest::registries`]: crate::core::TomlManifest::registries
+    #[serde(default)]
+    pub registries: BTreeMap<SmolStr, TomlRegistry>,
+
+    /// Sources definitions, used to replace sources of packages with other ones.
+    ///
+    /// The `scarb.xyz` key refers to the default registry.
+    #[serde(default)]
+    pub source: BTreeMap<SmolStr, TomlSource>,
+
+    /// Network settings.
+    #[serde(default)]
+    pub net: NetConfig,
+
+    /// Compilation settings.
+    #[serde(default)]
+    pub build: BuildConfig,
+}
+
+/// Source definition in the `[source]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TomlSource {
+    /// Name of the source to use instead of this one.
+    pub replace_with: Option<SmolStr>,
+    /// Registry index URL.
+    pub registry: Option<Url>,
+    /// Path to a local registry directory, relative to the config directory.
+    pub local_registry: Option<Utf8PathBuf>,
+    /// Git repository URL.
+    pub git: Option<Url>,
+}
+
+/// Network settings in the `[net]` table.
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct NetConfig {
+    /// Maximum number of concurrent requests made to a single registry.
+    pub jobs: usize,
+    /// Timeout of connecting to a server and of waiting for data from it, in seconds.
+    pub timeout: u64,
+    /// Number of times to retry requests failing with spurious network errors.
+    pub retry: u32,
+    /// How much of Git repositories history is fetched for Git dependencies.
+    pub git_fetch: GitFetchMode,
+}
+
+/// Strategy of fetching Git dependencies, set by the `net.git-fetch` key.
+///
+/// Reduced fetches are only possible if the dependency points to a tag or a full commit ID,
+/// otherwise all branches and tags of the repository have to be fetched anyway.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GitFetchMode {
+    /// Only fetch the requested commit, without its history (`git fetch --depth=1`).
+    Shallow,
+    /// Fetch the history, but not the contents of files, which are only fetched for checked out
+    /// commits (`git fetch --filter=blob:none`).
+    Partial,
+    /// Always fetch the complete history of requested references.
+    #[default]
+    Full,
+}
+
+impl Default for NetConfig {
+    fn default() -> Self {
+        Self {
+            jobs: 8,
+            timeout: 30,
+            retry: 3,
+            git_fetch: GitFetchMode::default(),
+        }
+    }
+}
+
+impl NetConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout)
+    }
+}
+
+/// Compilation settings in the `[build]` table.
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BuildConfig {
+    /// Maximum number of compilation units compiled in parallel, unless `--jobs` is passed.
+    ///
+    /// Every unit is compiled in its own compiler database, which can take hundreds of megabytes
+    /// of memory, so the number of available CPUs alone is not a safe default.
+    pub jobs: usize,
+}
+
+impl Default for BuildConfig {
+    fn default() -> Self {
+        Self { jobs: 4 }
+    }
+}
+
+impl GlobalConfig {
+    /// Read the global config file from the given config directory, if it exists.
+    pub(crate) fn load(config_dir: &Utf8Path) -> Result<Self> {
+        let path = config_dir.join(GLOBAL_CONFIG_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fsx::read_to_string(&path)?;
+        let mut global_config: Self =
+            toml::from_str(&contents).with_context(|| format!("failed to parse `{path}`"))?;
+
+        ensure!(
+            global_config.net.jobs > 0,
+            "failed to parse `{path}`: `net.jobs` must be greater than 0"
+        );
+        ensure!(
+            global_config.net.timeout > 0,
+            "failed to parse `{path}`: `net.timeout` must be greater than 0"
+        );
+        ensure!(
+            global_config.build.jobs > 0,
+            "failed to parse `{path}`: `build.jobs` must be greater than 0"
+        );
+
+        for source in global_config.source.values_mut() {
+            if let Some(local_registry) = &mut source.local_registry {
+                *local_registry = config_dir.join(&*local_registry);
+            }
+        }
+
+        Ok(global_config)
+    }
+}
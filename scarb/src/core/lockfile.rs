@@ -21,7 +21,7 @@ pub enum LockVersion {
     V1 = 1,
 }
 
-#[derive(Debug, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Lockfile {
     pub version: LockVersion,
@@ -29,6 +29,11 @@ pub struct Lockfile {
     #[serde(default = "BTreeSet::new")]
     #[serde(skip_serializing_if = "BTreeSet::is_empty")]
     pub packages: BTreeSet<PackageLock>,
+    /// Packages which dependencies have been patched with, using the `[patch]` manifest table.
+    #[serde(rename = "patch")]
+    #[serde(default = "BTreeSet::new")]
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub patches: BTreeSet<PackageLock>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, TypedBuilder)]
@@ -60,9 +65,18 @@ impl Lockfile {
         Self {
             version: Default::default(),
             packages: packages.into_iter().collect(),
+            patches: BTreeSet::new(),
         }
     }
 
+    pub fn with_patches(mut self, patches: impl IntoIterator<Item = PackageId>) -> Self {
+        self.patches = patches
+            .into_iter()
+            .map(|package_id| PackageLock::builder().use_package_id(package_id).build())
+            .collect();
+        self
+    }
+
     pub fn from_resolve(resolve: &Resolve) -> Self {
         let include_package = |package_id: &PackageId| !package_id.source_id.is_std();
         let packages = resolve
@@ -252,6 +266,36 @@ mod tests {
         assert_eq!(lock, deserialized);
     }
 
+    #[test]
+    fn patches() {
+        let pkg = PackageLock::builder()
+            .name(PackageName::new("first"))
+            .version(Version::parse("1.0.1").unwrap())
+            .source(Some(SourceId::mock_git()))
+            .build();
+
+        let lock = Lockfile::new(vec![pkg.clone()]).with_patches([pkg.try_into().unwrap()]);
+
+        let serialized = expect![[r#"
+            # Code generated by scarb DO NOT EDIT.
+            version = 1
+
+            [[package]]
+            name = "first"
+            version = "1.0.1"
+            source = "git+https://github.com/starkware-libs/cairo.git?tag=test"
+
+            [[patch]]
+            name = "first"
+            version = "1.0.1"
+            source = "git+https://github.com/starkware-libs/cairo.git?tag=test"
+        "#]];
+
+        serialized.assert_eq(&lock.render().unwrap());
+        let deserialized = Lockfile::from_str(serialized.data()).unwrap();
+        assert_eq!(lock, deserialized);
+    }
+
     #[test]
     fn empty() {
         let lock = Lockfile::new([]);
@@ -4,7 +4,9 @@ use std::ops::Deref;
 use std::sync::Arc;
 use typed_builder::TypedBuilder;
 
-use crate::core::{DependencyVersionReq, PackageId, PackageName, SourceId, Summary, TargetKind};
+use crate::core::{
+    DependencyVersionReq, FeatureName, PackageId, PackageName, SourceId, Summary, TargetKind,
+};
 
 /// See [`ManifestDependencyInner`] for public fields reference.
 #[derive(Clone, Eq, PartialEq, Hash)]
@@ -21,6 +23,15 @@ pub struct ManifestDependencyInner {
     pub source_id: SourceId,
     #[builder(default)]
     pub kind: DepKind,
+    /// Whether this dependency is only activated when a feature of the dependent enables it.
+    #[builder(default = false)]
+    pub optional: bool,
+    /// Features of the dependency package to enable.
+    #[builder(default)]
+    pub features: Vec<FeatureName>,
+    /// Whether to enable the `default` feature of the dependency package.
+    #[builder(default = true)]
+    pub default_features: bool,
 }
 
 #[derive(Clone, Default, Eq, PartialEq, Hash)]
@@ -58,6 +69,13 @@ impl ManifestDependency {
             .build()
     }
 
+    /// Returns a copy of this dependency, pointing at the given source.
+    pub fn with_source_id(&self, source_id: SourceId) -> Self {
+        let mut inner = self.0.as_ref().clone();
+        inner.source_id = source_id;
+        inner.into()
+    }
+
     pub fn matches_summary(&self, summary: &Summary) -> bool {
         self.matches_package_id(summary.package_id)
     }
@@ -0,0 +1,288 @@
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{bail, ensure, Result};
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
+
+use crate::core::{DepKind, ManifestDependency, PackageName};
+
+/// Features declared by a package in the `[features]` table, mapped to values they enable.
+pub type FeaturesDefinition = BTreeMap<FeatureName, Vec<FeatureValue>>;
+
+/// A [`String`]-like type representing a name of a package feature.
+///
+/// Feature names consist of ASCII letters, ASCII numbers, underscores and dashes,
+/// and cannot start with a dash.
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(into = "SmolStr", try_from = "SmolStr")]
+pub struct FeatureName(SmolStr);
+
+impl FeatureName {
+    /// The feature enabled by dependents unless they opt out with `default-features = false`,
+    /// or the `--no-default-features` flag is passed for workspace members.
+    pub const DEFAULT: Self = FeatureName(SmolStr::new_inline("default"));
+
+    /// Constructs and validates new [`FeatureName`].
+    ///
+    /// Panics if name does not conform to feature naming rules.
+    pub fn new(name: impl AsRef<str>) -> Self {
+        Self::try_new(name).unwrap()
+    }
+
+    /// Constructs and validates new [`FeatureName`].
+    pub fn try_new(name: impl AsRef<str>) -> Result<Self> {
+        Self::try_new_impl(name.as_ref().into())
+    }
+
+    fn try_new_impl(name: SmolStr) -> Result<Self> {
+        if name.is_empty() {
+            bail!("empty string cannot be used as feature name");
+        }
+
+        let mut chars = name.chars();
+
+        // Validate first letter.
+        if let Some(ch) = chars.next() {
+            if !(ch.is_ascii_alphanumeric() || ch == '_') {
+                bail!(
+                    "invalid character `{ch}` in feature name: `{name}`, \
+                    the first character must be an ASCII letter, ASCII number or underscore"
+                )
+            }
+        }
+
+        // Validate rest.
+        for ch in chars {
+            if !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '-') {
+                bail!(
+                    "invalid character `{ch}` in feature name: `{name}`, \
+                    characters must be ASCII letters, ASCII numbers, underscore or dash"
+                )
+            }
+        }
+
+        Ok(Self(name))
+    }
+
+    #[inline(always)]
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    #[inline(always)]
+    pub fn to_smol_str(&self) -> SmolStr {
+        self.0.clone()
+    }
+}
+
+impl AsRef<str> for FeatureName {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for FeatureName {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<FeatureName> for SmolStr {
+    fn from(value: FeatureName) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<SmolStr> for FeatureName {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SmolStr) -> Result<Self> {
+        FeatureName::try_new(value.as_str())
+    }
+}
+
+impl FromStr for FeatureName {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        FeatureName::try_new(name)
+    }
+}
+
+impl fmt::Display for FeatureName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Debug for FeatureName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FeatureName({self})")
+    }
+}
+
+/// A single item of a feature definition, describing what enabling the feature does.
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(into = "SmolStr", try_from = "SmolStr")]
+pub enum FeatureValue {
+    /// Enables another feature of the same package (`"feature"`), or activates an optional
+    /// dependency with this name.
+    Feature(FeatureName),
+    /// Enables a feature of a dependency (`"dependency/feature"`), activating the dependency
+    /// if it is optional.
+    DependencyFeature {
+        dependency: PackageName,
+        feature: FeatureName,
+    },
+}
+
+impl FeatureValue {
+    pub fn try_new(value: impl AsRef<str>) -> Result<Self> {
+        let value = value.as_ref();
+        match value.split_once('/') {
+            Some((dependency, feature)) => Ok(Self::DependencyFeature {
+                dependency: dependency.parse()?,
+                feature: feature.parse()?,
+            }),
+            None => Ok(Self::Feature(value.parse()?)),
+        }
+    }
+}
+
+impl From<FeatureValue> for SmolStr {
+    fn from(value: FeatureValue) -> Self {
+        value.to_string().into()
+    }
+}
+
+impl TryFrom<SmolStr> for FeatureValue {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SmolStr) -> Result<Self> {
+        FeatureValue::try_new(value)
+    }
+}
+
+impl FromStr for FeatureValue {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        FeatureValue::try_new(value)
+    }
+}
+
+impl fmt::Display for FeatureValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Feature(feature) => write!(f, "{feature}"),
+            Self::DependencyFeature {
+                dependency,
+                feature,
+            } => write!(f, "{dependency}/{feature}"),
+        }
+    }
+}
+
+impl fmt::Debug for FeatureValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FeatureValue({self})")
+    }
+}
+
+/// Check that all values of feature definitions refer to existing features and dependencies.
+pub fn validate_features(
+    features: &FeaturesDefinition,
+    dependencies: &[ManifestDependency],
+) -> Result<()> {
+    let find_dependency = |name: &PackageName| {
+        dependencies
+            .iter()
+            .find(|dep| dep.name == *name && dep.kind == DepKind::Normal)
+    };
+    let is_optional_dependency = |name: &FeatureName| {
+        PackageName::try_new(name)
+            .ok()
+            .and_then(|name| find_dependency(&name))
+            .map_or(false, |dep| dep.optional)
+    };
+
+    for (feature, values) in features {
+        ensure!(
+            !is_optional_dependency(feature),
+            "feature `{feature}` conflicts with the optional dependency of the same name"
+        );
+
+        for value in values {
+            match value {
+                FeatureValue::Feature(name) => {
+                    ensure!(
+                        features.contains_key(name) || is_optional_dependency(name),
+                        "feature `{feature}` includes `{name}`, which is neither a feature \
+                        nor an optional dependency"
+                    );
+                }
+                FeatureValue::DependencyFeature { dependency, .. } => {
+                    ensure!(
+                        find_dependency(dependency).is_some(),
+                        "feature `{feature}` includes `{value}`, but `{dependency}` \
+                        is not a dependency"
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{FeatureName, FeatureValue};
+    use crate::core::PackageName;
+
+    #[test_case("foo")]
+    #[test_case("Foo_bar")]
+    #[test_case("foo-bar")]
+    #[test_case("_foo")]
+    #[test_case("2d")]
+    fn validate_correct_feature_name(name: &str) {
+        assert!(FeatureName::try_new(name).is_ok())
+    }
+
+    #[test_case("" => "empty string cannot be used as feature name"; "empty string")]
+    #[test_case("-foo" => "invalid character `-` in feature name: `-foo`, the first character must be an ASCII letter, ASCII number or underscore")]
+    #[test_case("foo/bar" => "invalid character `/` in feature name: `foo/bar`, characters must be ASCII letters, ASCII numbers, underscore or dash")]
+    #[test_case("foo bar" => "invalid character ` ` in feature name: `foo bar`, characters must be ASCII letters, ASCII numbers, underscore or dash")]
+    fn validate_incorrect_feature_name(name: &str) -> String {
+        FeatureName::try_new(name).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parse_feature_value() {
+        assert_eq!(
+            FeatureValue::try_new("foo").unwrap(),
+            FeatureValue::Feature(FeatureName::new("foo"))
+        );
+        assert_eq!(
+            FeatureValue::try_new("dep/foo-bar").unwrap(),
+            FeatureValue::DependencyFeature {
+                dependency: PackageName::new("dep"),
+                feature: FeatureName::new("foo-bar"),
+            }
+        );
+        assert!(FeatureValue::try_new("dep/").is_err());
+        assert!(FeatureValue::try_new("Dep/foo").is_err());
+        assert!(FeatureValue::try_new("dep/foo/bar").is_err());
+    }
+
+    #[test]
+    fn feature_value_display_roundtrip() {
+        for value in ["foo", "dep/foo-bar"] {
+            assert_eq!(FeatureValue::try_new(value).unwrap().to_string(), value);
+        }
+    }
+}
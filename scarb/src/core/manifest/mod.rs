@@ -8,9 +8,11 @@ use semver::VersionReq;
 use serde::{Deserialize, Serialize};
 use smol_str::SmolStr;
 use toml::Value;
+use url::Url;
 
 pub use compiler_config::*;
 pub use dependency::*;
+pub use features::*;
 pub use maybe_workspace::*;
 pub use scripts::*;
 pub use summary::*;
@@ -24,6 +26,7 @@ use crate::compiler::Profile;
 
 mod compiler_config;
 mod dependency;
+mod features;
 mod maybe_workspace;
 mod scripts;
 mod summary;
@@ -49,6 +52,15 @@ pub struct Manifest {
     pub compiler_config: ManifestCompilerConfig,
     #[builder(default)]
     pub scripts: BTreeMap<SmolStr, ScriptDefinition>,
+    /// Globs of files to include in the package, see [`TomlPackage::include`].
+    #[builder(default)]
+    pub include: Vec<String>,
+    /// Globs of files to exclude from the package, see [`TomlPackage::exclude`].
+    #[builder(default)]
+    pub exclude: Vec<String>,
+    /// Index URLs of registries this package can be published to, or `None` if any is allowed.
+    #[builder(default)]
+    pub publish: Option<Vec<Url>>,
 }
 
 /// Subset of a [`Manifest`] that contains package metadata.
@@ -3,13 +3,14 @@ use std::ops::Deref;
 use std::sync::Arc;
 
 use once_cell::sync::Lazy;
+use semver::VersionReq;
 use typed_builder::TypedBuilder;
 
 #[cfg(doc)]
 use crate::core::Manifest;
 use crate::core::{
-    Checksum, DepKind, DependencyVersionReq, ManifestDependency, PackageId, PackageName, SourceId,
-    TargetKind,
+    Checksum, DepKind, DependencyVersionReq, FeaturesDefinition, ManifestDependency, PackageId,
+    PackageName, SourceId, TargetKind,
 };
 
 /// Subset of a [`Manifest`] that contains only the most important information about a package.
@@ -32,6 +33,13 @@ pub struct SummaryInner {
     pub no_core: bool,
     #[builder(default)]
     pub checksum: Option<Checksum>,
+    #[builder(default)]
+    pub features: FeaturesDefinition,
+    #[builder(default = false)]
+    pub yanked: bool,
+    /// Cairo versions this package is compatible with, as declared in `package.cairo-version`.
+    #[builder(default)]
+    pub cairo_version: Option<VersionReq>,
 }
 
 impl Deref for Summary {
@@ -58,6 +66,25 @@ impl Summary {
         Arc::make_mut(&mut self.0).checksum = Some(cksum);
     }
 
+    pub fn unset_checksum(&mut self) {
+        Arc::make_mut(&mut self.0).checksum = None;
+    }
+
+    /// Rewrite the package ID and dependencies pointing at the `to_replace` source,
+    /// so that they point at the `replace_with` source.
+    pub fn map_source(mut self, to_replace: SourceId, replace_with: SourceId) -> Self {
+        let inner = Arc::make_mut(&mut self.0);
+        if inner.package_id.source_id == to_replace {
+            inner.package_id = inner.package_id.with_source_id(replace_with);
+        }
+        for dependency in &mut inner.dependencies {
+            if dependency.source_id == to_replace {
+                *dependency = dependency.with_source_id(replace_with);
+            }
+        }
+        self
+    }
+
     pub fn full_dependencies(&self) -> impl Iterator<Item = &ManifestDependency> {
         self.dependencies.iter().chain(self.implicit_dependencies())
     }
@@ -7,7 +7,7 @@ use std::iter::{repeat, zip};
 use anyhow::{anyhow, bail, ensure, Context, Result};
 use cairo_lang_filesystem::db::Edition;
 use cairo_lang_filesystem::ids::CAIRO_FILE_EXTENSION;
-use camino::{Utf8Path, Utf8PathBuf};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 use itertools::Itertools;
 use pathdiff::diff_utf8_paths;
 use semver::{Version, VersionReq};
@@ -22,15 +22,19 @@ use crate::core::manifest::maybe_workspace::{MaybeWorkspace, WorkspaceInherit};
 use crate::core::manifest::scripts::ScriptDefinition;
 use crate::core::manifest::{ManifestDependency, ManifestMetadata, Summary, Target};
 use crate::core::package::PackageId;
+use crate::core::registry::named_registries::NamedRegistries;
+use crate::core::registry::patch_map::PatchMap;
 use crate::core::source::{GitReference, SourceId};
 use crate::core::{
-    DepKind, DependencyVersionReq, ManifestBuilder, ManifestCompilerConfig, PackageName,
-    TargetKind, TestTargetProps, TestTargetType,
+    validate_features, DepKind, DependencyVersionReq, FeatureName, FeaturesDefinition,
+    ManifestBuilder, ManifestCompilerConfig, PackageName, TargetKind, TestTargetProps,
+    TestTargetType,
 };
 use crate::internal::fsx;
 use crate::internal::fsx::PathBufUtf8Ext;
 use crate::internal::serdex::{toml_merge, RelativeUtf8PathBuf};
 use crate::internal::to_version::ToVersion;
+use crate::sources::canonical_url::CanonicalUrl;
 use crate::{
     DEFAULT_MODULE_MAIN_FILE, DEFAULT_SOURCE_PATH, DEFAULT_TESTS_PATH, MANIFEST_FILE_NAME,
 };
@@ -45,6 +49,7 @@ pub struct TomlManifest {
     pub workspace: Option<TomlWorkspace>,
     pub dependencies: Option<BTreeMap<PackageName, MaybeTomlWorkspaceDependency>>,
     pub dev_dependencies: Option<BTreeMap<PackageName, MaybeTomlWorkspaceDependency>>,
+    pub features: Option<FeaturesDefinition>,
     pub lib: Option<TomlTarget<TomlLibTargetParams>>,
     pub cairo_plugin: Option<TomlTarget<TomlExternalTargetParams>>,
     pub test: Option<Vec<TomlTarget<TomlExternalTargetParams>>>,
@@ -53,8 +58,13 @@ pub struct TomlManifest {
     pub profile: Option<TomlProfilesDefinition>,
     pub scripts: Option<BTreeMap<SmolStr, MaybeWorkspaceScriptDefinition>>,
     pub tool: Option<BTreeMap<SmolStr, MaybeWorkspaceTomlTool>>,
+    pub patch: Option<BTreeMap<SmolStr, BTreeMap<PackageName, TomlDependency>>>,
+    pub registries: Option<BTreeMap<SmolStr, TomlRegistry>>,
 }
 
+/// Key of the `[patch]` table which refers to the default registry.
+pub const DEFAULT_REGISTRY_PATCH_SOURCE: &str = "scarb.xyz";
+
 type MaybeWorkspaceScriptDefinition = MaybeWorkspace<ScriptDefinition, WorkspaceScriptDefinition>;
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
@@ -119,6 +129,7 @@ pub struct PackageInheritableFields {
     pub readme: Option<PathOrBool>,
     pub repository: Option<String>,
     pub cairo_version: Option<VersionReq>,
+    pub publish: Option<TomlPublish>,
 }
 
 macro_rules! get_field {
@@ -147,6 +158,7 @@ impl PackageInheritableFields {
     get_field!(license_file, Utf8PathBuf);
     get_field!(repository, String);
     get_field!(edition, Edition);
+    get_field!(publish, TomlPublish);
 
     pub fn readme(&self, workspace_root: &Utf8Path, package_root: &Utf8Path) -> Result<PathOrBool> {
         let Ok(Some(readme)) = readme_for_package(workspace_root, self.readme.as_ref()) else {
@@ -198,6 +210,13 @@ pub struct TomlPackage {
     /// **UNSTABLE** This package does not depend on Cairo's `core`.
     pub no_core: Option<bool>,
     pub cairo_version: Option<MaybeWorkspaceField<VersionReq>>,
+    /// Gitignore-style globs of files to include in the package.
+    /// When set, ignore files are not consulted.
+    pub include: Option<Vec<String>>,
+    /// Gitignore-style globs of files to exclude from the package.
+    pub exclude: Option<Vec<String>>,
+    /// Registries this package can be published to.
+    pub publish: Option<MaybeWorkspaceField<TomlPublish>>,
 }
 
 #[derive(Clone, Debug, Serialize, Eq, PartialEq)]
@@ -219,6 +238,28 @@ impl<'de> Deserialize<'de> for PathOrBool {
     }
 }
 
+/// Value of the `package.publish` field.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum TomlPublish {
+    /// Allow (`true`) or forbid (`false`) publishing to any registry.
+    Bool(bool),
+    /// Only allow publishing to the listed registries.
+    Registries(Vec<TomlDependencyRegistry>),
+}
+
+impl<'de> Deserialize<'de> for TomlPublish {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        UntaggedEnumVisitor::new()
+            .bool(|b| Ok(TomlPublish::Bool(b)))
+            .seq(|seq| seq.deserialize().map(TomlPublish::Registries))
+            .deserialize(deserializer)
+    }
+}
+
 impl From<Utf8PathBuf> for PathOrBool {
     fn from(p: Utf8PathBuf) -> Self {
         Self::Path(p)
@@ -269,8 +310,35 @@ pub struct DetailedTomlDependency {
     pub branch: Option<String>,
     pub tag: Option<String>,
     pub rev: Option<String>,
+    /// Relative to the Git repository root.
+    pub subdir: Option<String>,
+
+    pub registry: Option<TomlDependencyRegistry>,
+
+    pub optional: Option<bool>,
+    pub features: Option<Vec<FeatureName>>,
+    pub default_features: Option<bool>,
+}
+
+/// Registry of a dependency or a publishing target, specified either by index URL or by name.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum TomlDependencyRegistry {
+    /// Registry index URL, e.g. `registry = "https://example.com/index"`.
+    Url(Url),
+    /// Name of the registry defined in the `[registries]` table, e.g. `registry = "internal"`.
+    Name(SmolStr),
+}
 
-    pub registry: Option<Url>,
+/// Registry definition in the `[registries]` table.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TomlRegistry {
+    /// Registry index URL.
+    pub index: Url,
+    /// Whether this registry should be used by commands like `scarb publish`,
+    /// when no registry is specified.
+    pub default: Option<bool>,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
@@ -380,6 +448,7 @@ impl TomlManifest {
         source_id: SourceId,
         profile: Profile,
         workspace_manifest: Option<&TomlManifest>,
+        registries: &NamedRegistries,
     ) -> Result<Manifest> {
         let root = manifest_path
             .parent()
@@ -431,24 +500,42 @@ impl TomlManifest {
                     .and_then(|deps| deps.get(name.as_str()))
                     .cloned()
                     .ok_or_else(|| anyhow!("dependency `{}` not found in workspace", name.clone()))?
-                    .to_dependency(name.clone(), workspace_manifest_path, kind.clone())
+                    .to_dependency(
+                        name.clone(),
+                        workspace_manifest_path,
+                        kind.clone(),
+                        registries,
+                    )
             };
             let toml_dep = toml_dep
                 .clone()
-                .map(|dep| dep.to_dependency(name.clone(), manifest_path, kind.clone()))?
+                .map(|dep| {
+                    dep.to_dependency(name.clone(), manifest_path, kind.clone(), registries)
+                })?
                 .resolve(name.as_str(), inherit_ws)?;
             dependencies.push(toml_dep);
         }
 
+        let features = self.features.clone().unwrap_or_default();
+        validate_features(&features, &dependencies)?;
+
         let no_core = package.no_core.unwrap_or(false);
 
+        let cairo_version = package
+            .cairo_version
+            .clone()
+            .map(|mw| mw.resolve("cairo_version", || inheritable_package.cairo_version()))
+            .transpose()?;
+
         let targets = self.collect_targets(package.name.to_smol_str(), root)?;
 
         let summary = Summary::builder()
             .target_kinds(targets.iter().map(|t| t.kind.clone()).collect())
             .package_id(package_id)
             .dependencies(dependencies)
+            .features(features)
             .no_core(no_core)
+            .cairo_version(cairo_version.clone())
             .build();
 
         let scripts = self.scripts.clone().unwrap_or_default();
@@ -542,11 +629,7 @@ impl TomlManifest {
                 .clone()
                 .map(|mw| mw.resolve("repository", || inheritable_package.repository()))
                 .transpose()?,
-            cairo_version: package
-                .cairo_version
-                .clone()
-                .map(|mw| mw.resolve("cairo_version", || inheritable_package.cairo_version()))
-                .transpose()?,
+            cairo_version,
         };
 
         let edition = package
@@ -556,6 +639,15 @@ impl TomlManifest {
             .transpose()?
             .unwrap_or_default();
 
+        let publish = package
+            .publish
+            .clone()
+            .map(|mw| mw.resolve("publish", || inheritable_package.publish()))
+            .transpose()?
+            .map(|publish| Self::resolve_publish(publish, registries))
+            .transpose()?
+            .flatten();
+
         let manifest = ManifestBuilder::default()
             .summary(summary)
             .targets(targets)
@@ -563,11 +655,41 @@ impl TomlManifest {
             .metadata(metadata)
             .compiler_config(compiler_config)
             .scripts(scripts)
+            .include(package.include.clone().unwrap_or_default())
+            .exclude(package.exclude.clone().unwrap_or_default())
+            .publish(publish)
             .build()?;
 
         Ok(manifest)
     }
 
+    /// Resolve the `publish` field to the list of allowed registry index URLs,
+    /// or `None` if publishing to any registry is allowed.
+    fn resolve_publish(
+        publish: TomlPublish,
+        registries: &NamedRegistries,
+    ) -> Result<Option<Vec<Url>>> {
+        match publish {
+            TomlPublish::Bool(true) => Ok(None),
+            TomlPublish::Bool(false) => Ok(Some(Vec::new())),
+            TomlPublish::Registries(list) => list
+                .into_iter()
+                .map(|registry| match registry {
+                    TomlDependencyRegistry::Url(url) => Ok(url),
+                    TomlDependencyRegistry::Name(name) => {
+                        registries.get(&name).cloned().ok_or_else(|| {
+                            anyhow!(
+                                "`publish` refers to registry `{name}`, \
+                                which is not defined in the `[registries]` table"
+                            )
+                        })
+                    }
+                })
+                .collect::<Result<Vec<_>>>()
+                .map(Some),
+        }
+    }
+
     fn collect_targets(&self, package_name: SmolStr, root: &Utf8Path) -> Result<Vec<Target>> {
         let mut targets = Vec::new();
 
@@ -749,6 +871,48 @@ impl TomlManifest {
             .unwrap_or(Ok(vec![]))
     }
 
+    /// Read the `[patch]` table into a [`PatchMap`].
+    ///
+    /// Keys of this table are URLs of patched sources, or `scarb.xyz` for the default registry.
+    pub fn collect_patch(
+        &self,
+        manifest_path: &Utf8Path,
+        registries: &NamedRegistries,
+    ) -> Result<PatchMap> {
+        let mut patch_map = PatchMap::new();
+        for (source, patches) in self.patch.iter().flatten() {
+            let source_url = if source == DEFAULT_REGISTRY_PATCH_SOURCE {
+                SourceId::default().canonical_url.clone()
+            } else {
+                Url::parse(source)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|url| CanonicalUrl::new(&url))
+                    .with_context(|| format!("invalid patch source `{source}`"))?
+            };
+
+            let dependencies = patches
+                .iter()
+                .map(|(name, dep)| {
+                    let dependency = dep.to_dependency(
+                        name.clone(),
+                        manifest_path,
+                        DepKind::Normal,
+                        registries,
+                    )?;
+                    ensure!(
+                        dependency.source_id.canonical_url != source_url,
+                        "patch for `{name}` in `{source}` points to the same source, \
+                        but patches must point to different sources"
+                    );
+                    Ok(dependency)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            patch_map.insert(source_url, dependencies);
+        }
+        Ok(patch_map)
+    }
+
     fn collect_profile_definition(&self, profile: Profile) -> Result<TomlProfile> {
         let toml_cairo = self.cairo.clone().unwrap_or_default();
         let toml_profiles = self.profile.clone();
@@ -899,8 +1063,10 @@ impl TomlDependency {
         name: PackageName,
         manifest_path: &Utf8Path,
         dep_kind: DepKind,
+        registries: &NamedRegistries,
     ) -> Result<ManifestDependency> {
-        self.resolve().to_dependency(name, manifest_path, dep_kind)
+        self.resolve()
+            .to_dependency(name, manifest_path, dep_kind, registries)
     }
 }
 
@@ -910,6 +1076,7 @@ impl DetailedTomlDependency {
         name: PackageName,
         manifest_path: &Utf8Path,
         dep_kind: DepKind,
+        registries: &NamedRegistries,
     ) -> Result<ManifestDependency> {
         let version_req = self
             .version
@@ -933,6 +1100,13 @@ impl DetailedTomlDependency {
                 only one of `branch`, `tag` or `rev` is allowed"
             );
         }
+
+        if self.subdir.is_some() {
+            ensure!(
+                self.git.is_some(),
+                "dependency ({name}) is non-Git, but provides `subdir`"
+            );
+        }
         let source_id = match (
             self.version.as_ref(),
             self.git.as_ref(),
@@ -972,18 +1146,68 @@ impl DetailedTomlDependency {
                     GitReference::DefaultBranch
                 };
 
-                SourceId::for_git(git, &reference)?
+                let source_id = SourceId::for_git(git, &reference)?;
+                match &self.subdir {
+                    Some(subdir) => {
+                        let subdir = normalize_git_subdir(subdir).with_context(|| {
+                            format!("dependency ({name}) has invalid `subdir`: {subdir}")
+                        })?;
+                        source_id.with_subdir(subdir)?
+                    }
+                    None => source_id,
+                }
             }
 
-            (Some(_), None, None, Some(url)) => SourceId::for_registry(url)?,
+            (Some(_), None, None, Some(TomlDependencyRegistry::Url(url))) => {
+                SourceId::for_registry(url)?
+            }
+            (Some(_), None, None, Some(TomlDependencyRegistry::Name(registry))) => {
+                let Some(url) = registries.get(registry) else {
+                    bail!(
+                        "dependency ({name}) refers to registry `{registry}`, \
+                        which is not defined in the `[registries]` table"
+                    );
+                };
+                SourceId::for_registry(url)?
+            }
             (Some(_), None, None, None) => SourceId::default(),
         };
 
+        let optional = self.optional.unwrap_or(false);
+        ensure!(
+            !optional || dep_kind == DepKind::Normal,
+            "dev-dependency ({name}) cannot be optional"
+        );
+
         Ok(ManifestDependency::builder()
             .name(name)
             .source_id(source_id)
             .version_req(version_req)
             .kind(dep_kind)
+            .optional(optional)
+            .features(self.features.clone().unwrap_or_default())
+            .default_features(self.default_features.unwrap_or(true))
             .build())
     }
 }
+
+/// Normalize path to a directory within a Git repository, so that equivalent paths
+/// produce equal source IDs.
+fn normalize_git_subdir(subdir: &str) -> Result<SmolStr> {
+    let mut components = Vec::new();
+    for component in Utf8Path::new(subdir).components() {
+        match component {
+            Utf8Component::Normal(c) => components.push(c),
+            Utf8Component::CurDir => {}
+            Utf8Component::Prefix(_) | Utf8Component::RootDir => {
+                bail!("path must be relative to the repository root")
+            }
+            Utf8Component::ParentDir => bail!("path must not point outside the repository"),
+        }
+    }
+    ensure!(
+        !components.is_empty(),
+        "path must point to a directory within the repository"
+    );
+    Ok(components.join("/").into())
+}
@@ -5,8 +5,10 @@
 pub use checksum::*;
 pub use config::Config;
 pub use dirs::AppDirs;
+pub use global_config::GlobalConfig;
 pub use manifest::*;
 pub use package::{Package, PackageId, PackageIdInner, PackageInner, PackageName};
+pub use registry::client::SearchResult;
 pub use resolver::Resolve;
 pub use source::{GitReference, SourceId, SourceIdInner, SourceKind};
 pub use workspace::{Utf8PathWorkspaceExt, Workspace};
@@ -15,6 +17,7 @@ mod checksum;
 pub(crate) mod config;
 mod dirs;
 pub mod errors;
+pub(crate) mod global_config;
 pub(crate) mod lockfile;
 pub(crate) mod manifest;
 pub(crate) mod package;
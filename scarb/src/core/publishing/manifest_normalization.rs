@@ -7,8 +7,8 @@ use indoc::formatdoc;
 use crate::{
     core::{
         DepKind, DependencyVersionReq, DetailedTomlDependency, ManifestDependency, MaybeWorkspace,
-        Package, PackageName, TargetKind, TomlDependency, TomlManifest, TomlPackage,
-        TomlWorkspaceDependency,
+        Package, PackageName, TargetKind, TomlDependency, TomlDependencyRegistry, TomlManifest,
+        TomlPackage, TomlPublish, TomlWorkspaceDependency,
     },
     DEFAULT_LICENSE_FILE_NAME, DEFAULT_README_FILE_NAME,
 };
@@ -23,6 +23,8 @@ pub fn prepare_manifest_for_publish(pkg: &Package) -> Result<TomlManifest> {
         DepKind::Normal,
     )?);
 
+    let features = Some(pkg.manifest.summary.features.clone()).filter(|f| !f.is_empty());
+
     let tool = pkg.manifest.metadata.tool_metadata.clone().map(|m| {
         m.into_iter()
             .map(|(k, v)| (k, MaybeWorkspace::Defined(v)))
@@ -39,6 +41,7 @@ pub fn prepare_manifest_for_publish(pkg: &Package) -> Result<TomlManifest> {
         workspace: None,
         dependencies,
         dev_dependencies: None,
+        features,
         lib: None,
         cairo_plugin,
         test: None,
@@ -47,6 +50,8 @@ pub fn prepare_manifest_for_publish(pkg: &Package) -> Result<TomlManifest> {
         profile: None,
         scripts: None,
         tool,
+        patch: None,
+        registries: None,
     })
 }
 
@@ -75,6 +80,20 @@ fn generate_package(pkg: &Package) -> Box<TomlPackage> {
         repository: metadata.repository.clone().map(MaybeWorkspace::Defined),
         no_core: summary.no_core.then_some(true),
         cairo_version: metadata.cairo_version.clone().map(MaybeWorkspace::Defined),
+        include: Some(pkg.manifest.include.clone()).filter(|globs| !globs.is_empty()),
+        exclude: Some(pkg.manifest.exclude.clone()).filter(|globs| !globs.is_empty()),
+        publish: pkg.manifest.publish.clone().map(|registries| {
+            MaybeWorkspace::Defined(if registries.is_empty() {
+                TomlPublish::Bool(false)
+            } else {
+                TomlPublish::Registries(
+                    registries
+                        .into_iter()
+                        .map(TomlDependencyRegistry::Url)
+                        .collect(),
+                )
+            })
+        }),
     })
 }
 
@@ -132,14 +151,20 @@ fn generate_dependency(dep: &ManifestDependency) -> Result<TomlDependency> {
         branch: None,
         tag: None,
         rev: None,
+        subdir: None,
 
         // Unless it is default registry, expand registry specification to registry URL.
+        // Registry names are expanded too, because they are meaningless outside the workspace.
         //
         // NOTE: Default registry will reject packages with dependencies from other registries.
         registry: if dep.source_id.is_registry() && !dep.source_id.is_default_registry() {
-            Some(dep.source_id.url.clone())
+            Some(TomlDependencyRegistry::Url(dep.source_id.url.clone()))
         } else {
             None
         },
+
+        optional: dep.optional.then_some(true),
+        features: Some(dep.features.clone()).filter(|f| !f.is_empty()),
+        default_features: (!dep.default_features).then_some(false),
     })))
 }
@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
 use camino::Utf8PathBuf;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::{DirEntry, WalkBuilder};
 
 use crate::core::Package;
@@ -12,6 +13,9 @@ use crate::{DEFAULT_TARGET_DIR_NAME, LOCK_FILE_NAME, MANIFEST_FILE_NAME, SCARB_I
 /// package, provided that they potentially can be committed to the source directory. The following
 /// rules hold:
 /// * Look for any `.scarbignore`, `.gitignore` or `.ignore`-like files, using the [`ignore`] crate.
+/// * If `package.include` globs are specified, only include files matching them, ignoring
+///   all of the above files and the hidden files rule.
+/// * Skip files matching `package.exclude` globs.
 /// * Skip `.git` directory.
 /// * Skip any subdirectories containing `Scarb.toml`.
 /// * Skip `<root>/target` directory.
@@ -28,6 +32,10 @@ pub fn list_source_files(pkg: &Package) -> Result<Vec<Utf8PathBuf>> {
 }
 
 fn push_worktree_files(pkg: &Package, ret: &mut Vec<Utf8PathBuf>) -> Result<()> {
+    let has_include = !pkg.manifest.include.is_empty();
+    let include = build_globs(pkg, &pkg.manifest.include, "include")?;
+    let exclude = build_globs(pkg, &pkg.manifest.exclude, "exclude")?;
+
     let filter = {
         let pkg = pkg.clone();
         let readme = pkg.manifest.metadata.readme.clone().unwrap_or_default();
@@ -47,6 +55,11 @@ fn push_worktree_files(pkg: &Package, ret: &mut Vec<Utf8PathBuf>) -> Result<()>
                 return false;
             };
 
+            // Skip `.git` directory, which is not covered by standard filters if `include` is set.
+            if !is_root && entry.file_name() == ".git" {
+                return false;
+            }
+
             // Skip any subdirectories containing `Scarb.toml`.
             if !is_root && path.join(MANIFEST_FILE_NAME).exists() {
                 return false;
@@ -67,26 +80,54 @@ fn push_worktree_files(pkg: &Package, ret: &mut Vec<Utf8PathBuf>) -> Result<()>
                 return false;
             }
 
+            // Apply `package.exclude` and `package.include` globs.
+            if !is_root {
+                let dir = is_dir(entry);
+                if exclude.matched_path_or_any_parents(path, dir).is_ignore() {
+                    return false;
+                }
+                if has_include
+                    && !dir
+                    && !include.matched_path_or_any_parents(path, dir).is_ignore()
+                {
+                    return false;
+                }
+            }
+
             true
         }
     };
 
-    WalkBuilder::new(pkg.root())
+    let mut walker = WalkBuilder::new(pkg.root());
+    walker
         .follow_links(true)
-        .standard_filters(true)
+        .standard_filters(!has_include)
         .parents(false)
         .require_git(true)
         .same_file_system(true)
-        .add_custom_ignore_filename(SCARB_IGNORE_FILE_NAME)
-        .filter_entry(filter)
-        .build()
-        .try_for_each(|entry| {
-            let entry = entry?;
-            if !is_dir(&entry) {
-                ret.push(entry.into_path().try_into_utf8()?);
-            }
-            Ok(())
-        })
+        .filter_entry(filter);
+    if !has_include {
+        walker.add_custom_ignore_filename(SCARB_IGNORE_FILE_NAME);
+    }
+
+    walker.build().try_for_each(|entry| {
+        let entry = entry?;
+        if !is_dir(&entry) {
+            ret.push(entry.into_path().try_into_utf8()?);
+        }
+        Ok(())
+    })
+}
+
+/// Build a matcher for gitignore-style globs from the given manifest field.
+fn build_globs(pkg: &Package, globs: &[String], field: &str) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(pkg.root());
+    for glob in globs {
+        builder
+            .add_line(None, glob)
+            .with_context(|| format!("invalid `{field}` pattern: {glob}"))?;
+    }
+    Ok(builder.build()?)
 }
 
 fn is_dir(entry: &DirEntry) -> bool {
@@ -140,12 +140,8 @@ impl<'c> RegistryClientCache<'c> {
         package: PackageId,
     ) -> Result<(FileLockGuard, Checksum)> {
         // Skip downloading if the package already has been.
-        if self.is_package_downloaded(package).await {
+        if let Some(file) = self.cached_archive(package).await? {
             trace!("found cached archive which is not empty, skipping download");
-            let tarball_name = package.tarball_name();
-            let file = self
-                .dl_fs
-                .open_rw(&tarball_name, &tarball_name, self.config)?;
             let checksum = self.get_record_maybe_uncached(package).await?.checksum;
             return Ok((file, checksum));
         }
@@ -173,6 +169,18 @@ impl<'c> RegistryClientCache<'c> {
         }
     }
 
+    /// Open the package archive if it has already been downloaded, without querying the registry.
+    pub async fn cached_archive(&self, package: PackageId) -> Result<Option<FileLockGuard>> {
+        if !self.is_package_downloaded(package).await {
+            return Ok(None);
+        }
+        let tarball_name = package.tarball_name();
+        let file = self
+            .dl_fs
+            .open_rw(&tarball_name, &tarball_name, self.config)?;
+        Ok(Some(file))
+    }
+
     async fn db(&self) -> Result<&CacheDatabase> {
         self.db_cell
             .get_or_try_init(|| async {
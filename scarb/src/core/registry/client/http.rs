@@ -1,33 +1,49 @@
+use std::future::Future;
 use std::str::FromStr;
+use std::time::Duration;
 
 use anyhow::{bail, ensure, Context, Result};
 use async_trait::async_trait;
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
+use itertools::Itertools;
 use reqwest::header::{
     HeaderMap, HeaderName, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
 };
-use reqwest::{Response, StatusCode};
+use reqwest::multipart::{Form, Part};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
 use tokio::io;
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
-use tokio::sync::OnceCell;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufWriter};
+use tokio::sync::{OnceCell, Semaphore};
+use tokio::time::error::Elapsed;
 use tracing::{debug, trace, warn};
 
 use scarb_ui::components::Status;
 
 use crate::core::registry::client::{
-    CreateScratchFileCallback, RegistryClient, RegistryDownload, RegistryResource,
+    CreateScratchFileCallback, RegistryClient, RegistryDownload, RegistryResource, SearchResult,
 };
-use crate::core::registry::index::{IndexConfig, IndexRecords};
-use crate::core::{Config, Package, PackageId, PackageName, SourceId};
-use crate::flock::{FileLockGuard, Filesystem};
+use crate::core::registry::credentials::{
+    registry_token, target_registry_token, REGISTRY_TOKEN_ENV,
+};
+use crate::core::registry::download_progress::DownloadProgress;
+use crate::core::registry::index::{IndexConfig, IndexRecord, IndexRecords};
+use crate::core::{Config, Digest, ManifestMetadata, Package, PackageId, PackageName, SourceId};
+use crate::flock::{AsyncFileLockGuard, FileLockGuard, Filesystem};
 
-// TODO(mkaput): Progressbar.
-// TODO(mkaput): Request timeout.
+/// Delay before the first retry of a failed request, doubled with each subsequent retry.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
 
 /// Remote registry served by the HTTP-based registry API.
+///
+/// The number of concurrent requests made to the registry is limited by the `net.jobs` setting
+/// of the global config. Requests which can be safely repeated are retried on spurious network
+/// errors, as many times as the `net.retry` setting allows.
 pub struct HttpRegistryClient<'c> {
     config: &'c Config,
     index_config: IndexConfigManager<'c>,
+    requests: Semaphore,
 }
 
 enum HttpCacheKey {
@@ -46,9 +62,11 @@ struct IndexConfigManager<'c> {
 
 impl<'c> HttpRegistryClient<'c> {
     pub fn new(source_id: SourceId, config: &'c Config) -> Result<Self> {
+        let jobs = config.global_config()?.net.jobs;
         Ok(Self {
             config,
             index_config: IndexConfigManager::new(source_id, config),
+            requests: Semaphore::new(jobs),
         })
     }
 }
@@ -70,13 +88,13 @@ impl<'c> RegistryClient for HttpRegistryClient<'c> {
         let index_config = self.index_config.load().await?;
         let records_url = index_config.index.expand(package.into())?;
 
-        let response = self
+        let request = self
             .config
             .online_http()?
             .get(records_url)
-            .headers(cache_key.to_headers_for_request())
-            .send()
-            .await?;
+            .headers(cache_key.to_headers_for_request());
+        let _permit = self.requests.acquire().await?;
+        let response = send_with_retry(self.authorize(request, index_config)?, self.config).await?;
 
         let response = match response.status() {
             StatusCode::NOT_MODIFIED => {
@@ -117,27 +135,49 @@ impl<'c> RegistryClient for HttpRegistryClient<'c> {
             .ui()
             .print(Status::new("Downloading", &package.to_string()));
 
-        let response = self.config.online_http()?.get(dl_url).send().await?;
-
-        let response = match response.status() {
-            StatusCode::NOT_MODIFIED => {
-                bail!("packages archive server is not allowed to say not modified (HTTP 304)")
-            }
-            StatusCode::NOT_FOUND => {
-                return Ok(RegistryDownload::NotFound);
-            }
-            _ => response.error_for_status()?,
-        };
+        let request = self.config.online_http()?.get(dl_url);
+        let request = self.authorize(request, index_config)?;
+        // Keep the permit until the whole archive is downloaded.
+        let _permit = self.requests.acquire().await?;
 
         let mut output_file = create_scratch_file(self.config)?.into_async();
 
-        let mut stream = response.bytes_stream();
-        let mut writer = BufWriter::new(&mut *output_file);
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.context("failed to read response chunk")?;
-            io::copy_buf(&mut &*chunk, &mut writer)
+        // The download is retried as a whole, because the connection may break while streaming
+        // the response body as well.
+        let mut retry = Retry::new(self.config)?;
+        loop {
+            let request = request
+                .try_clone()
+                .expect("Download requests should not have streaming bodies.");
+            let error = match idle_timeout(request.send(), self.config).await {
+                Ok(response) => {
+                    match response.status() {
+                        StatusCode::NOT_MODIFIED => {
+                            bail!("packages archive server is not allowed to say not modified (HTTP 304)")
+                        }
+                        StatusCode::NOT_FOUND => {
+                            return Ok(RegistryDownload::NotFound);
+                        }
+                        _ => match save_body(response, &mut output_file, self.config).await {
+                            Ok(()) => break,
+                            Err(err) => err,
+                        },
+                    }
+                }
+                Err(err) => err,
+            };
+
+            if !is_spurious_error(&error) || !retry.wait(&error).await {
+                return Err(error);
+            }
+            output_file
+                .rewind()
+                .await
+                .context("failed to rewind downloaded file")?;
+            output_file
+                .set_len(0)
                 .await
-                .context("failed to save response chunk on disk")?;
+                .context("failed to truncate downloaded file")?;
         }
 
         let output_file = output_file.into_sync().await;
@@ -146,12 +186,345 @@ impl<'c> RegistryClient for HttpRegistryClient<'c> {
     }
 
     async fn supports_publish(&self) -> Result<bool> {
-        // TODO(mkaput): Publishing to HTTP registries is not implemented yet.
-        Ok(false)
+        let index_config = self.index_config.load().await?;
+        Ok(index_config.api.is_some())
+    }
+
+    async fn publish(&self, package: Package, tarball: FileLockGuard) -> Result<()> {
+        let index_config = self.index_config.load().await?;
+        let api = index_config
+            .api
+            .as_ref()
+            .expect("Publishing should only be attempted if registry has an API endpoint.");
+        let publish_url = api.join("publish")?;
+
+        let token = self.auth_token()?;
+
+        let tarball = tokio::fs::read(tarball.path())
+            .await
+            .with_context(|| format!("failed to read package archive: {}", tarball.path()))?;
+        let checksum = Digest::recommended().update(&tarball).finish();
+
+        let summary = &package.manifest.summary;
+        let metadata = PublishMetadata {
+            name: &summary.package_id.name,
+            record: IndexRecord::from_summary(summary, checksum),
+            metadata: &package.manifest.metadata,
+        };
+        let metadata =
+            serde_json::to_string(&metadata).context("failed to serialize package metadata")?;
+
+        let form = Form::new().text("metadata", metadata).part(
+            "package",
+            Part::bytes(tarball)
+                .file_name(format!("{}.tar.zst", summary.package_id.tarball_basename()))
+                .mime_str("application/zstd")?,
+        );
+
+        // Publishing is not idempotent, so the upload is never retried.
+        let request = self
+            .config
+            .online_http()?
+            .post(publish_url)
+            .bearer_auth(token)
+            .multipart(form);
+        let response = idle_timeout(request.send(), self.config).await?;
+
+        ensure_api_success(response).await?;
+        Ok(())
+    }
+
+    async fn supports_yank(&self) -> Result<bool> {
+        let index_config = self.index_config.load().await?;
+        Ok(index_config.api.is_some())
+    }
+
+    async fn yank(&self, package: PackageId, yanked: bool) -> Result<()> {
+        let index_config = self.index_config.load().await?;
+        let api = index_config
+            .api
+            .as_ref()
+            .expect("Yanking should only be attempted if registry has an API endpoint.");
+        let yank_url = api.join(&format!(
+            "packages/{}/{}/yank",
+            package.name, package.version
+        ))?;
+
+        let token = self.auth_token()?;
+
+        let http = self.config.online_http()?;
+        let request = if yanked {
+            http.put(yank_url)
+        } else {
+            http.delete(yank_url)
+        };
+        let response = idle_timeout(request.bearer_auth(token).send(), self.config).await?;
+
+        ensure_api_success(response).await?;
+        Ok(())
+    }
+
+    async fn supports_search(&self) -> Result<bool> {
+        let index_config = self.index_config.load().await?;
+        Ok(index_config.api.is_some())
     }
 
-    async fn publish(&self, _package: Package, _tarball: FileLockGuard) -> Result<()> {
-        todo!("Publishing to HTTP registries is not implemented yet.")
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        let index_config = self.index_config.load().await?;
+        let api = index_config
+            .api
+            .as_ref()
+            .expect("Searching should only be attempted if registry has an API endpoint.");
+        let search_url = api.join("search")?;
+
+        let request = self
+            .config
+            .online_http()?
+            .get(search_url)
+            .query(&[("q", query), ("limit", &limit.to_string())]);
+        // Searched registry is explicitly targeted by the user.
+        let request = if index_config.auth_required {
+            request.bearer_auth(self.auth_token()?)
+        } else {
+            request
+        };
+        let response = send_with_retry(request, self.config).await?;
+        let response = ensure_api_success(response).await?;
+
+        let results = response
+            .json::<SearchResponse>()
+            .await
+            .context("failed to deserialize search results")?;
+        Ok(results.packages)
+    }
+}
+
+/// Send the request, retrying it with exponential backoff if it fails with a spurious network
+/// error, like a timeout or a server error.
+///
+/// Only requests which can be safely repeated should be sent this way. These requests are expected
+/// to have small responses, so the whole request, including reading the response body, has to
+/// finish within the `net.timeout` setting.
+async fn send_with_retry(request: RequestBuilder, config: &Config) -> Result<Response> {
+    let timeout = config.global_config()?.net.timeout();
+    let mut retry = Retry::new(config)?;
+    loop {
+        let attempt = request
+            .try_clone()
+            .expect("Retried requests should not have streaming bodies.")
+            .timeout(timeout)
+            .send()
+            .await;
+
+        let error = match attempt {
+            Ok(response) if is_spurious_status(response.status()) => {
+                let error = response.error_for_status_ref().map(|_| ()).unwrap_err();
+                // Let the caller handle the error response if no tries are left.
+                if !retry.wait(&error.into()).await {
+                    return Ok(response);
+                }
+                continue;
+            }
+            Ok(response) => return Ok(response),
+            Err(err) => err.into(),
+        };
+
+        if !is_spurious_error(&error) || !retry.wait(&error).await {
+            return Err(error);
+        }
+    }
+}
+
+/// Wait for the future to finish, failing if it does not within the `net.timeout` setting.
+async fn idle_timeout<T>(
+    future: impl Future<Output = reqwest::Result<T>>,
+    config: &Config,
+) -> Result<T> {
+    let timeout = config.global_config()?.net.timeout();
+    let result = tokio::time::timeout(timeout, future)
+        .await
+        .context("timed out waiting for the server to respond")?;
+    Ok(result?)
+}
+
+/// Stream the response body to the file.
+///
+/// There is no limit on the total time of the download, so that large archives can be downloaded
+/// over slow connections, but waiting for each chunk of the body is limited by `net.timeout`.
+async fn save_body(
+    response: Response,
+    output_file: &mut AsyncFileLockGuard,
+    config: &Config,
+) -> Result<()> {
+    let response = response.error_for_status()?;
+    let mut stream = response.bytes_stream();
+    let mut writer = BufWriter::new(&mut **output_file);
+    while let Some(chunk) = idle_timeout(stream.next().map(Option::transpose), config).await? {
+        DownloadProgress::report_bytes(chunk.len() as u64);
+        io::copy_buf(&mut &*chunk, &mut writer)
+            .await
+            .context("failed to save response chunk on disk")?;
+    }
+    writer
+        .flush()
+        .await
+        .context("failed to save response chunk on disk")
+}
+
+/// Exponential backoff of retried requests, limited by the `net.retry` setting.
+struct Retry<'c> {
+    config: &'c Config,
+    tries_left: u32,
+    delay: Duration,
+}
+
+impl<'c> Retry<'c> {
+    fn new(config: &'c Config) -> Result<Self> {
+        Ok(Self {
+            config,
+            tries_left: config.global_config()?.net.retry,
+            delay: INITIAL_RETRY_DELAY,
+        })
+    }
+
+    /// Report the error and wait before the next try, or return `false` if no tries are left.
+    async fn wait(&mut self, error: &anyhow::Error) -> bool {
+        if self.tries_left == 0 {
+            return false;
+        }
+
+        let error = match error
+            .downcast_ref::<reqwest::Error>()
+            .and_then(|e| e.status())
+        {
+            Some(status) => format!("server responded with HTTP {status}"),
+            None => format!("{error:#}"),
+        };
+        self.config.ui().warn(format!(
+            "spurious network error ({} tries remaining): {error}",
+            self.tries_left
+        ));
+        tokio::time::sleep(self.delay).await;
+        self.delay = (self.delay * 2).min(MAX_RETRY_DELAY);
+        self.tries_left -= 1;
+        true
+    }
+}
+
+fn is_spurious_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Check whether the request failed with a timeout, a connection or response body stream error,
+/// or a server error status, so that it is worth retrying.
+fn is_spurious_error(err: &anyhow::Error) -> bool {
+    if err.chain().any(|cause| cause.is::<Elapsed>()) {
+        return true;
+    }
+    err.downcast_ref::<reqwest::Error>().map_or(false, |err| {
+        err.is_timeout()
+            || err.is_connect()
+            || err.is_body()
+            || err.status().map_or(false, is_spurious_status)
+    })
+}
+
+/// Check whether the registry API call succeeded, surfacing error messages sent by the registry.
+async fn ensure_api_success(response: Response) -> Result<Response> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        let message = ApiErrors::parse(&body).unwrap_or(body);
+        bail!("the registry responded with an error (HTTP {status}): {message}");
+    }
+    Ok(response)
+}
+
+impl<'c> HttpRegistryClient<'c> {
+    /// Find the token for API calls made to this registry, which is explicitly targeted by
+    /// the command being run.
+    fn auth_token(&self) -> Result<String> {
+        let source_id = self.index_config.source_id;
+        match target_registry_token(source_id, self.config)? {
+            Some(token) => Ok(token),
+            None => bail!(
+                "no authentication token found for registry: {source_id}\n\
+                help: run `scarb login --index {url}` or set the `{REGISTRY_TOKEN_ENV}` \
+                environment variable",
+                url = source_id.url,
+            ),
+        }
+    }
+
+    /// Attach the registry token to the request if the registry requires authentication
+    /// for all requests.
+    ///
+    /// Only stored tokens are used here, because packages are fetched from all registries
+    /// dependencies come from, not only the one targeted by the user.
+    fn authorize(
+        &self,
+        request: RequestBuilder,
+        index_config: &IndexConfig,
+    ) -> Result<RequestBuilder> {
+        if !index_config.auth_required {
+            return Ok(request);
+        }
+        let source_id = self.index_config.source_id;
+        match registry_token(source_id, self.config)? {
+            Some(token) => Ok(request.bearer_auth(token)),
+            None => bail!(
+                "no authentication token found for registry: {source_id}\n\
+                help: run `scarb login --index {url}`",
+                url = source_id.url,
+            ),
+        }
+    }
+}
+
+/// Package metadata sent along with the package archive to the `{api}/publish` endpoint.
+///
+/// Apart from the package name and fields of the [`IndexRecord`] the registry is expected
+/// to store in its index, it contains metadata of the normalized package manifest.
+#[derive(Serialize)]
+struct PublishMetadata<'a> {
+    name: &'a PackageName,
+    #[serde(flatten)]
+    record: IndexRecord,
+    metadata: &'a ManifestMetadata,
+}
+
+/// Response body of the `{api}/search` endpoint.
+///
+/// ```json
+/// {"packages": [{"name": "foo", "version": "1.0.0", "description": "An example package."}]}
+/// ```
+#[derive(Deserialize)]
+struct SearchResponse {
+    packages: Vec<SearchResult>,
+}
+
+/// Error response body returned by the registry API.
+///
+/// ```json
+/// {"errors": [{"detail": "package `foo` version `1.0.0` already exists"}]}
+/// ```
+#[derive(Deserialize)]
+struct ApiErrors {
+    errors: Vec<ApiError>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    detail: String,
+}
+
+impl ApiErrors {
+    fn parse(body: &str) -> Option<String> {
+        let errors = serde_json::from_str::<Self>(body).ok()?.errors;
+        if errors.is_empty() {
+            return None;
+        }
+        Some(errors.into_iter().map(|err| err.detail).join(", "))
     }
 }
 
@@ -319,11 +692,8 @@ impl<'c> IndexConfigManager<'c> {
             .expect("Registry config URL should always be valid.");
         debug!("fetching registry config: {index_config_url}");
 
-        let index_config = self
-            .config
-            .online_http()?
-            .get(index_config_url)
-            .send()
+        let request = self.config.online_http()?.get(index_config_url);
+        let index_config = send_with_retry(request, self.config)
             .await?
             .error_for_status()?
             .json::<IndexConfig>()
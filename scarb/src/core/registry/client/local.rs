@@ -1,24 +1,27 @@
-use std::fs::OpenOptions;
+use std::fs::{File, OpenOptions};
 use std::io;
-use std::io::{BufReader, BufWriter, Seek, SeekFrom};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 
 use anyhow::{ensure, Context, Error, Result};
 use async_trait::async_trait;
 use fs4::FileExt;
+use semver::Version;
 use tokio::task::spawn_blocking;
 use tracing::trace;
 use url::Url;
+use walkdir::WalkDir;
 
 use crate::core::registry::client::{
-    CreateScratchFileCallback, RegistryClient, RegistryDownload, RegistryResource,
+    CreateScratchFileCallback, RegistryClient, RegistryDownload, RegistryResource, SearchResult,
 };
-use crate::core::registry::index::{IndexDependency, IndexRecord, IndexRecords, TemplateUrl};
-use crate::core::{Checksum, Config, Digest, Package, PackageId, PackageName, Summary};
+use crate::core::registry::index::{IndexRecord, IndexRecords, TemplateUrl};
+use crate::core::{Config, Digest, Package, PackageId, PackageName, Summary};
 use crate::flock::{FileLockGuard, Filesystem};
 use crate::internal::fsx;
 use crate::internal::fsx::PathBufUtf8Ext;
+use crate::MANIFEST_FILE_NAME;
 
 /// Local registry that lives on the filesystem as a set of `.tar.zst` files with an `index`
 /// directory in the standard registry index format.
@@ -45,7 +48,19 @@ use crate::internal::fsx::PathBufUtf8Ext;
 /// ├── cairo_lib-0.2.0.tar.zst
 /// └── open_zeppelin-0.7.0.tar.zst
 /// ```
+///
+/// ## Publishing
+///
+/// Packages can be published to a local registry with `scarb publish --index file:///path/`.
+/// The index file of the package is locked for the duration of publishing, and versions which
+/// already exist in the registry are rejected.
+///
+/// ## Searching
+///
+/// Local registries have no API, so searching scans the index directory for packages with
+/// matching names. Descriptions are read from manifests in package tarballs.
 pub struct LocalRegistryClient<'c> {
+    root: PathBuf,
     index_template_url: TemplateUrl,
     dl_template_url: TemplateUrl,
     config: &'c Config,
@@ -63,7 +78,7 @@ impl<'c> LocalRegistryClient<'c> {
 
         let root = fsx::canonicalize(root)?;
 
-        let root_url = Url::from_directory_path(root)
+        let root_url = Url::from_directory_path(&root)
             .expect("Canonical path should always be convertible to URL.");
 
         let index_template_url =
@@ -73,12 +88,32 @@ impl<'c> LocalRegistryClient<'c> {
             TemplateUrl::new(&format!("{root_url}{{package}}-{{version}}.tar.zst"));
 
         Ok(Self {
+            root,
             index_template_url,
             dl_template_url,
             config,
         })
     }
 
+    /// Put the package tarball in this registry, described by the given index record.
+    ///
+    /// Unlike [`RegistryClient::publish`], this does not compute the record from the package
+    /// manifest, which allows storing packages exactly as they were found in other sources.
+    /// Adding a version which is already stored with different contents is rejected.
+    pub async fn add(
+        &self,
+        package: PackageId,
+        record: IndexRecord,
+        tarball: FileLockGuard,
+    ) -> Result<()> {
+        let records_path = self.records_path(&package.name);
+        let dl_path = self.dl_path(package);
+
+        spawn_blocking(move || add_impl(package, record, tarball, records_path, dl_path))
+            .await
+            .with_context(|| format!("failed to add package: {package}"))?
+    }
+
     fn records_path(&self, package: &PackageName) -> PathBuf {
         self.index_template_url
             .expand(package.into())
@@ -160,6 +195,31 @@ impl RegistryClient for LocalRegistryClient<'_> {
             .await
             .with_context(|| format!("failed to publish package: {package}"))?
     }
+
+    async fn supports_yank(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    async fn yank(&self, package: PackageId, yanked: bool) -> Result<()> {
+        let records_path = self.records_path(&package.name);
+
+        spawn_blocking(move || yank_impl(package, yanked, records_path))
+            .await
+            .with_context(|| format!("failed to yank package: {package}"))?
+    }
+
+    async fn supports_search(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        let root = self.root.clone();
+        let query = query.to_owned();
+
+        spawn_blocking(move || search_impl(&root, &query, limit))
+            .await
+            .context("failed to search local registry")?
+    }
 }
 
 fn publish_impl(
@@ -169,45 +229,169 @@ fn publish_impl(
     dl_path: PathBuf,
 ) -> Result<(), Error> {
     let checksum = Digest::recommended().update_read(tarball.deref())?.finish();
+    let record = IndexRecord::from_summary(&summary, checksum);
     let tarball_path = tarball.path().to_owned();
 
     // Drop the FileLockGuard to release the tarball file RW lock, otherwise the package cannot be copied to local registry on Windows.
     drop(tarball);
-    fsx::copy(tarball_path, dl_path)?;
 
-    let record = build_record(summary, checksum);
+    edit_records(&records_path, move |records| {
+        ensure!(
+            !records.iter().any(|r| r.version == record.version),
+            "package `{}` version `{}` already exists",
+            summary.package_id.name,
+            summary.package_id.version,
+        );
+
+        // Copy the tarball while holding the lock on records file, so that concurrent
+        // publishes of the same version cannot overwrite each other's archives.
+        fsx::copy(tarball_path, dl_path)?;
+
+        records.push(record);
+        records.sort_by_cached_key(|r| r.version.clone());
+        Ok(())
+    })
+    .with_context(|| format!("failed to edit records file: {}", records_path.display()))?
+}
+
+fn add_impl(
+    package: PackageId,
+    record: IndexRecord,
+    tarball: FileLockGuard,
+    records_path: PathBuf,
+    dl_path: PathBuf,
+) -> Result<(), Error> {
+    let tarball_path = tarball.path().to_owned();
+
+    // Drop the FileLockGuard to release the tarball file RW lock, otherwise the package cannot be copied to local registry on Windows.
+    drop(tarball);
 
     edit_records(&records_path, move |records| {
-        // Remove existing record if exists (note: version is the key).
+        // Adding the same package again is fine, but archives are keyed by name and version only,
+        // so a different revision of the same version would replace the existing one.
         if let Some(idx) = records.iter().position(|r| r.version == record.version) {
+            ensure!(
+                records[idx].checksum == record.checksum,
+                "package `{}` version `{}` is already stored in this registry with different \
+                contents",
+                package.name,
+                package.version,
+            );
             records.swap_remove(idx);
         }
 
-        records.push(record);
+        fsx::copy(tarball_path, dl_path)?;
 
+        records.push(record);
         records.sort_by_cached_key(|r| r.version.clone());
+        Ok(())
+    })
+    .with_context(|| format!("failed to edit records file: {}", records_path.display()))?
+}
+
+fn yank_impl(package: PackageId, yanked: bool, records_path: PathBuf) -> Result<()> {
+    ensure!(
+        records_path.exists(),
+        "package `{}` not found in registry",
+        package.name
+    );
+
+    let found = edit_records(&records_path, |records| {
+        records
+            .iter_mut()
+            .find(|r| r.version == package.version)
+            .map(|r| r.yanked = yanked)
+            .is_some()
     })
     .with_context(|| format!("failed to edit records file: {}", records_path.display()))?;
 
+    ensure!(
+        found,
+        "package `{}` version `{}` not found in registry",
+        package.name,
+        package.version
+    );
     Ok(())
 }
 
-fn build_record(summary: Summary, checksum: Checksum) -> IndexRecord {
-    IndexRecord {
-        version: summary.package_id.version.clone(),
-        dependencies: summary
-            .publish_dependencies()
-            .map(|dep| IndexDependency {
-                name: dep.name.clone(),
-                req: dep.version_req.clone().into(),
-            })
-            .collect(),
-        checksum,
-        no_core: summary.no_core,
+fn search_impl(root: &Path, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+    let index_dir = root.join("index");
+    if !index_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let query = query.to_lowercase();
+    let mut results = Vec::new();
+    for entry in WalkDir::new(&index_dir) {
+        let entry = entry?;
+        let path = entry.path();
+        if !entry.file_type().is_file() || path.extension().map_or(true, |ext| ext != "json") {
+            continue;
+        }
+
+        let Some(Ok(name)) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(PackageName::try_new)
+        else {
+            continue;
+        };
+        if !name.as_str().to_lowercase().contains(&query) {
+            continue;
+        }
+
+        let records: IndexRecords = serde_json::from_slice(&fsx::read(path)?)
+            .with_context(|| format!("failed to deserialize records file: {}", path.display()))?;
+        let Some(version) = records
+            .into_iter()
+            .filter(|r| !r.yanked)
+            .map(|r| r.version)
+            .max()
+        else {
+            continue;
+        };
+
+        results.push(SearchResult {
+            name,
+            version,
+            description: None,
+        });
     }
+
+    results.sort_by(|a, b| a.name.cmp(&b.name));
+    results.truncate(limit);
+
+    for result in &mut results {
+        result.description = read_description(root, &result.name, &result.version);
+    }
+
+    Ok(results)
 }
 
-fn edit_records(records_path: &Path, func: impl FnOnce(&mut IndexRecords)) -> Result<()> {
+/// Read package description from the manifest stored in the package tarball, if possible.
+fn read_description(root: &Path, name: &PackageName, version: &Version) -> Option<String> {
+    let basename = format!("{name}-{version}");
+    let file = File::open(root.join(format!("{basename}.tar.zst"))).ok()?;
+    let mut archive = tar::Archive::new(zstd::Decoder::new(file).ok()?);
+    let manifest_path = Path::new(&basename).join(MANIFEST_FILE_NAME);
+
+    let mut entry = archive
+        .entries()
+        .ok()?
+        .filter_map(Result::ok)
+        .find(|entry| entry.path().map_or(false, |path| path == manifest_path))?;
+    let mut manifest = String::new();
+    entry.read_to_string(&mut manifest).ok()?;
+
+    let manifest: toml::Table = toml::from_str(&manifest).ok()?;
+    manifest
+        .get("package")?
+        .get("description")?
+        .as_str()
+        .map(ToOwned::to_owned)
+}
+
+fn edit_records<T>(records_path: &Path, func: impl FnOnce(&mut IndexRecords) -> T) -> Result<T> {
     fsx::create_dir_all(records_path.parent().unwrap())?;
     let mut file = OpenOptions::new()
         .read(true)
@@ -228,7 +412,7 @@ fn edit_records(records_path: &Path, func: impl FnOnce(&mut IndexRecords)) -> Re
         IndexRecords::new()
     };
 
-    func(&mut records);
+    let result = func(&mut records);
 
     {
         file.seek(SeekFrom::Start(0))
@@ -240,5 +424,5 @@ fn edit_records(records_path: &Path, func: impl FnOnce(&mut IndexRecords)) -> Re
         serde_json::to_writer(file, &records).context("failed to serialize file")?;
     }
 
-    Ok(())
+    Ok(result)
 }
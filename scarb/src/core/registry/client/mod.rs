@@ -1,5 +1,7 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use semver::Version;
+use serde::{Deserialize, Serialize};
 
 use crate::core::registry::index::IndexRecords;
 use crate::core::{Config, Package, PackageId, PackageName};
@@ -33,6 +35,16 @@ pub enum RegistryDownload<T> {
     Download(T),
 }
 
+/// Package found by [`RegistryClient::search`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub name: PackageName,
+    /// The latest non-yanked version of the package.
+    pub version: Version,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
 pub type CreateScratchFileCallback = Box<dyn FnOnce(&Config) -> Result<FileLockGuard> + Send>;
 
 #[async_trait]
@@ -94,4 +106,40 @@ pub trait RegistryClient: Send + Sync {
         let _ = tarball;
         unreachable!("This registry does not support publishing.")
     }
+
+    /// State whether package versions can be yanked in this registry.
+    ///
+    /// This method is permitted to do network lookups, for example to fetch registry config.
+    async fn supports_yank(&self) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Mark a package version as yanked if `yanked` is `true`, or undo this otherwise.
+    ///
+    /// This function can only be called if [`RegistryClient::supports_yank`] returns `true`.
+    /// Default implementation panics with [`unreachable!`].
+    async fn yank(&self, package: PackageId, yanked: bool) -> Result<()> {
+        // Silence clippy warnings without using _ in argument names.
+        let _ = package;
+        let _ = yanked;
+        unreachable!("This registry does not support yanking.")
+    }
+
+    /// State whether packages in this registry can be searched.
+    ///
+    /// This method is permitted to do network lookups, for example to fetch registry config.
+    async fn supports_search(&self) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Search for packages matching the `query`, returning at most `limit` results.
+    ///
+    /// This function can only be called if [`RegistryClient::supports_search`] returns `true`.
+    /// Default implementation panics with [`unreachable!`].
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        // Silence clippy warnings without using _ in argument names.
+        let _ = query;
+        let _ = limit;
+        unreachable!("This registry does not support searching.")
+    }
 }
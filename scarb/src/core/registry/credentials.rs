@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Config, SourceId};
+use crate::internal::fsx;
+
+/// Environment variable holding the token used to authenticate in the registry explicitly
+/// targeted by a command, like `scarb publish --index`.
+///
+/// If set, it takes precedence over tokens stored in the credentials file. It is never sent to
+/// registries which are only used to fetch dependencies, because it is not scoped to any registry.
+pub const REGISTRY_TOKEN_ENV: &str = "SCARB_REGISTRY_TOKEN";
+
+const CREDENTIALS_FILE_NAME: &str = "credentials.toml";
+
+/// The `credentials.toml` file stored in Scarb config directory.
+///
+/// Tokens are keyed by canonical registry URLs, so that slightly different spellings of the same
+/// registry URL share the same token:
+///
+/// ```toml
+/// [registries."https://example.com/index"]
+/// token = "secret"
+/// ```
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CredentialsFile {
+    #[serde(default)]
+    registries: BTreeMap<String, RegistryCredentials>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RegistryCredentials {
+    token: String,
+}
+
+/// Find the token for authenticating in the registry explicitly targeted by the user,
+/// either in the environment or in the credentials file.
+pub fn target_registry_token(source_id: SourceId, config: &Config) -> Result<Option<String>> {
+    if let Ok(token) = env::var(REGISTRY_TOKEN_ENV) {
+        let token = token.trim();
+        if !token.is_empty() {
+            return Ok(Some(token.to_string()));
+        }
+    }
+
+    registry_token(source_id, config)
+}
+
+/// Find the token for authenticating in the registry in the credentials file.
+pub fn registry_token(source_id: SourceId, config: &Config) -> Result<Option<String>> {
+    let credentials = CredentialsFile::load(config)?;
+    Ok(credentials
+        .registries
+        .get(&registry_key(source_id))
+        .map(|credentials| credentials.token.clone()))
+}
+
+/// Save the token for authenticating in the registry in the credentials file.
+pub fn store_registry_token(source_id: SourceId, token: String, config: &Config) -> Result<()> {
+    let mut credentials = CredentialsFile::load(config)?;
+    credentials
+        .registries
+        .insert(registry_key(source_id), RegistryCredentials { token });
+    credentials.save(config)
+}
+
+/// Remove the token for authenticating in the registry from the credentials file.
+///
+/// Returns `false` if there was no token stored for this registry.
+pub fn remove_registry_token(source_id: SourceId, config: &Config) -> Result<bool> {
+    let mut credentials = CredentialsFile::load(config)?;
+    if credentials
+        .registries
+        .remove(&registry_key(source_id))
+        .is_none()
+    {
+        return Ok(false);
+    }
+    credentials.save(config)?;
+    Ok(true)
+}
+
+fn registry_key(source_id: SourceId) -> String {
+    source_id.canonical_url.to_string()
+}
+
+fn credentials_path(config: &Config) -> Utf8PathBuf {
+    config
+        .dirs()
+        .config_dir
+        .path_unchecked()
+        .join(CREDENTIALS_FILE_NAME)
+}
+
+impl CredentialsFile {
+    fn load(config: &Config) -> Result<Self> {
+        let path = credentials_path(config);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fsx::read_to_string(&path)?;
+        toml::from_str(&contents).with_context(|| format!("failed to parse `{path}`"))
+    }
+
+    fn save(&self, config: &Config) -> Result<()> {
+        let path = credentials_path(config);
+        fsx::create_dir_all(path.parent().unwrap())?;
+
+        let contents = toml::to_string(self).context("failed to serialize credentials")?;
+
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(&path)
+            .with_context(|| format!("failed to open `{path}`"))?;
+
+        // The mode passed to `open` is only applied when the file is created.
+        #[cfg(unix)]
+        {
+            use std::fs::Permissions;
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(Permissions::from_mode(0o600))
+                .with_context(|| format!("failed to set permissions of `{path}`"))?;
+        }
+
+        file.write_all(contents.as_bytes())
+            .with_context(|| format!("failed to write `{path}`"))
+    }
+}
@@ -0,0 +1,57 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use scarb_ui::components::{Progress, ProgressHandle};
+
+use crate::core::Config;
+
+tokio::task_local! {
+    static CURRENT: DownloadProgress;
+}
+
+/// Progress of downloading a batch of packages, displayed as a [`Progress`] widget.
+///
+/// Registry clients do not know which batch they are downloading packages for, so the progress
+/// is made available to them for the duration of [`DownloadProgress::scope`] call, and they
+/// report transferred bytes with [`DownloadProgress::report_bytes`].
+#[derive(Clone)]
+pub struct DownloadProgress {
+    handle: Option<Arc<ProgressHandle>>,
+}
+
+impl DownloadProgress {
+    /// Display progress of downloading the given number of packages.
+    pub fn new(packages: usize, config: &Config) -> Self {
+        let handle = if packages > 0 {
+            config
+                .ui()
+                .widget(Progress::new("Downloading", packages as u64))
+                .map(Arc::new)
+        } else {
+            None
+        };
+        Self { handle }
+    }
+
+    /// Run the future, attributing all bytes reported within it to this progress.
+    pub async fn scope<F: Future>(&self, f: F) -> F::Output {
+        CURRENT.scope(self.clone(), f).await
+    }
+
+    /// Mark a single package as downloaded.
+    pub fn package_done(&self) {
+        if let Some(handle) = &self.handle {
+            handle.inc(1);
+        }
+    }
+
+    /// Report bytes downloaded to the progress of the current [`DownloadProgress::scope`],
+    /// if there is one.
+    pub fn report_bytes(bytes: u64) {
+        let _ = CURRENT.try_with(|progress| {
+            if let Some(handle) = &progress.handle {
+                handle.inc_bytes(bytes);
+            }
+        });
+    }
+}
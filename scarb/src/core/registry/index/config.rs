@@ -12,7 +12,8 @@ use crate::core::registry::index::{BaseUrl, TemplateUrl};
 ///   "version": 1,
 ///   "api": "https://example.com/api/v1",
 ///   "dl": "https://example.com/api/v1/download/{package}/{version}",
-///   "index": "https://example.com/index/{prefix}/{package}.json"
+///   "index": "https://example.com/index/{prefix}/{package}.json",
+///   "auth-required": false
 /// }
 /// ```
 ///
@@ -41,6 +42,12 @@ pub struct IndexConfig {
     /// Usually, this is a location where `config.json` lies, as the rest of index files resides
     /// alongside config.
     pub index: TemplateUrl,
+
+    /// Whether fetching index files and downloading packages requires authentication.
+    ///
+    /// If `true`, the registry token is sent in all requests, not only in API calls.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auth_required: bool,
 }
 
 impl IndexConfig {
@@ -72,6 +79,21 @@ mod tests {
 
     use super::IndexConfig;
 
+    #[test]
+    fn deserialize_auth_required() {
+        let actual: IndexConfig = serde_json::from_str(
+            r#"{
+              "version": 1,
+              "dl": "https://example.com/{package}-{version}.tar.zst",
+              "index": "https://example.com/index/{prefix}/{package}.json",
+              "auth-required": true
+            }"#,
+        )
+        .unwrap();
+
+        assert!(actual.auth_required);
+    }
+
     #[test]
     fn deserialize() {
         let expected = IndexConfig {
@@ -79,6 +101,7 @@ mod tests {
             api: Some("https://example.com/api/v1/".parse().unwrap()),
             dl: TemplateUrl::new("https://example.com/api/v1/download/{package}/{version}"),
             index: TemplateUrl::new("https://example.com/index/{prefix}/{package}.json"),
+            auth_required: false,
         };
 
         let actual: IndexConfig = serde_json::from_str(
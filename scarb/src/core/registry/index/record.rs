@@ -1,7 +1,7 @@
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 
-use crate::core::{Checksum, PackageName};
+use crate::core::{Checksum, FeatureName, FeaturesDefinition, PackageName, SourceId, Summary};
 
 pub type IndexRecords = Vec<IndexRecord>;
 
@@ -15,6 +15,37 @@ pub struct IndexRecord {
     pub checksum: Checksum,
     #[serde(default = "default_false", skip_serializing_if = "is_false")]
     pub no_core: bool,
+    #[serde(default, skip_serializing_if = "FeaturesDefinition::is_empty")]
+    pub features: FeaturesDefinition,
+    #[serde(default = "default_false", skip_serializing_if = "is_false")]
+    pub yanked: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cairo_version: Option<VersionReq>,
+}
+
+impl IndexRecord {
+    /// Build an index record describing a package with the given summary and archive checksum.
+    pub fn from_summary(summary: &Summary, checksum: Checksum) -> Self {
+        Self {
+            version: summary.package_id.version.clone(),
+            dependencies: summary
+                .publish_dependencies()
+                .map(|dep| IndexDependency {
+                    name: dep.name.clone(),
+                    req: dep.version_req.clone().into(),
+                    optional: dep.optional,
+                    features: dep.features.clone(),
+                    default_features: dep.default_features,
+                    source: None,
+                })
+                .collect(),
+            checksum,
+            no_core: summary.no_core,
+            features: summary.features.clone(),
+            yanked: false,
+            cairo_version: summary.cairo_version.clone(),
+        }
+    }
 }
 
 pub type IndexDependencies = Vec<IndexDependency>;
@@ -23,6 +54,17 @@ pub type IndexDependencies = Vec<IndexDependency>;
 pub struct IndexDependency {
     pub name: PackageName,
     pub req: VersionReq,
+    #[serde(default = "default_false", skip_serializing_if = "is_false")]
+    pub optional: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<FeatureName>,
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub default_features: bool,
+    /// Source of the dependency, if it is not the registry this record comes from.
+    ///
+    /// Used by vendored registries, which store packages coming from various sources.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceId>,
 }
 
 fn default_false() -> bool {
@@ -32,3 +74,11 @@ fn default_false() -> bool {
 fn is_false(value: &bool) -> bool {
     !*value
 }
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
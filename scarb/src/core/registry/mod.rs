@@ -5,11 +5,15 @@ use crate::core::{ManifestDependency, Package, PackageId, Summary};
 
 pub mod cache;
 pub mod client;
+pub mod credentials;
+pub mod download_progress;
 pub mod index;
+pub mod named_registries;
 pub mod package_source_store;
 pub mod patch_map;
 pub mod patcher;
 pub mod source_map;
+pub mod source_replacement;
 
 pub const DEFAULT_REGISTRY_INDEX: &str = "https://there-is-no-default-registry-yet.com";
 
@@ -42,6 +46,7 @@ pub(crate) mod mock {
     pub struct MockRegistry {
         index: HashMap<(PackageName, SourceId), HashSet<PackageId>>,
         dependencies: HashMap<PackageId, Vec<ManifestDependency>>,
+        yanked: HashSet<PackageId>,
         packages: RwLock<HashMap<PackageId, Package>>,
     }
 
@@ -84,6 +89,14 @@ pub(crate) mod mock {
                 .append(&mut dependencies);
         }
 
+        pub fn yank(&mut self, package_id: PackageId) {
+            assert!(
+                self.has_package(package_id),
+                "Package {package_id} is not in registry"
+            );
+            self.yanked.insert(package_id);
+        }
+
         pub fn has_package(&self, package_id: PackageId) -> bool {
             self.dependencies.contains_key(&package_id)
         }
@@ -99,8 +112,11 @@ pub(crate) mod mock {
             } else {
                 drop(packages);
 
-                let package =
-                    Self::build_package(package_id, self.dependencies[&package_id].clone());
+                let package = Self::build_package(
+                    package_id,
+                    self.dependencies[&package_id].clone(),
+                    self.yanked.contains(&package_id),
+                );
 
                 let mut packages = self.packages.write().unwrap();
                 packages.insert(package_id, package.clone());
@@ -109,12 +125,17 @@ pub(crate) mod mock {
             }
         }
 
-        fn build_package(package_id: PackageId, dependencies: Vec<ManifestDependency>) -> Package {
+        fn build_package(
+            package_id: PackageId,
+            dependencies: Vec<ManifestDependency>,
+            yanked: bool,
+        ) -> Package {
             let summary = Summary::builder()
                 .target_kinds(HashSet::from_iter(vec![TargetKind::LIB]))
                 .package_id(package_id)
                 .dependencies(dependencies)
                 .no_core(package_id.is_core())
+                .yanked(yanked)
                 .build();
 
             let manifest = Box::new(
@@ -204,6 +225,15 @@ pub(crate) mod mock {
                 .build()
         };
 
+        (($n:literal, $v:literal, optional)) => {
+            $crate::core::ManifestDependency::builder()
+                .name($crate::core::PackageName::new($n))
+                .version_req(::semver::VersionReq::parse($v).unwrap().into())
+                .source_id($crate::core::SourceId::default_registry())
+                .optional(true)
+                .build()
+        };
+
         (($n:literal, $v:literal, (), $t:literal)) => {
             $crate::core::ManifestDependency::builder()
                 .name($crate::core::PackageName::new($n))
@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use smol_str::SmolStr;
+use url::Url;
+
+use crate::core::TomlRegistry;
+
+/// Registries defined in `[registries]` tables, which can be referred to by name.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NamedRegistries {
+    registries: BTreeMap<SmolStr, Url>,
+    default: Option<SmolStr>,
+}
+
+impl NamedRegistries {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add registries from a `[registries]` table.
+    ///
+    /// Registries with the same name as already added ones replace them, and so does the default
+    /// registry, if this table marks any.
+    pub fn extend(&mut self, registries: &BTreeMap<SmolStr, TomlRegistry>) -> Result<()> {
+        let mut defaults = registries
+            .iter()
+            .filter(|(_, registry)| registry.default.unwrap_or(false))
+            .map(|(name, _)| name);
+        if let Some(default) = defaults.next() {
+            if let Some(other) = defaults.next() {
+                bail!(
+                    "only one registry can be marked as default, \
+                    but both `{default}` and `{other}` are"
+                );
+            }
+            self.default = Some(default.clone());
+        }
+
+        self.registries.extend(
+            registries
+                .iter()
+                .map(|(name, registry)| (name.clone(), registry.index.clone())),
+        );
+        Ok(())
+    }
+
+    /// Lookup the index URL of the registry with the given name.
+    pub fn get(&self, name: &str) -> Option<&Url> {
+        self.registries.get(name)
+    }
+
+    /// Lookup the index URL of the registry marked as default, if any.
+    pub fn default_registry(&self) -> Option<&Url> {
+        self.default
+            .as_ref()
+            .and_then(|name| self.registries.get(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use indoc::indoc;
+    use smol_str::SmolStr;
+
+    use crate::core::TomlRegistry;
+
+    use super::NamedRegistries;
+
+    fn parse(toml: &str) -> BTreeMap<SmolStr, TomlRegistry> {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn workspace_overrides_global() {
+        let mut registries = NamedRegistries::new();
+        registries
+            .extend(&parse(indoc! {r#"
+                [internal]
+                index = "https://global.example.com/index/"
+                default = true
+
+                [other]
+                index = "https://other.example.com/index/"
+            "#}))
+            .unwrap();
+        registries
+            .extend(&parse(indoc! {r#"
+                [internal]
+                index = "https://workspace.example.com/index/"
+            "#}))
+            .unwrap();
+
+        assert_eq!(
+            registries.get("internal").unwrap().as_str(),
+            "https://workspace.example.com/index/"
+        );
+        assert_eq!(
+            registries.get("other").unwrap().as_str(),
+            "https://other.example.com/index/"
+        );
+        assert!(registries.get("missing").is_none());
+
+        assert_eq!(
+            registries.default_registry().unwrap().as_str(),
+            "https://workspace.example.com/index/"
+        );
+    }
+
+    #[test]
+    fn multiple_defaults() {
+        let mut registries = NamedRegistries::new();
+        let err = registries
+            .extend(&parse(indoc! {r#"
+                [a]
+                index = "https://a.example.com/"
+                default = true
+
+                [b]
+                index = "https://b.example.com/"
+                default = true
+            "#}))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "only one registry can be marked as default, but both `a` and `b` are"
+        );
+    }
+}
@@ -1,10 +1,10 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::core::{ManifestDependency, PackageName};
 use crate::sources::canonical_url::CanonicalUrl;
 
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct PatchMap(HashMap<CanonicalUrl, HashMap<PackageName, ManifestDependency>>);
+pub struct PatchMap(BTreeMap<CanonicalUrl, BTreeMap<PackageName, ManifestDependency>>);
 
 impl PatchMap {
     pub fn new() -> Self {
@@ -20,6 +20,15 @@ impl PatchMap {
             .unwrap_or(dependency)
     }
 
+    /// Iterate over all patches, along with URLs of sources they apply to.
+    pub fn iter(&self) -> impl Iterator<Item = (&CanonicalUrl, &ManifestDependency)> {
+        self.0.iter().flat_map(|(source_pattern, patches)| {
+            patches
+                .values()
+                .map(move |dependency| (source_pattern, dependency))
+        })
+    }
+
     pub fn insert(
         &mut self,
         source_pattern: CanonicalUrl,
@@ -7,12 +7,13 @@ use itertools::Itertools;
 use tokio::sync::RwLock;
 use tracing::trace;
 
+use crate::core::registry::source_replacement::SourceReplacementMap;
 use crate::core::registry::Registry;
 use crate::core::source::Source;
 #[cfg(doc)]
 use crate::core::Workspace;
 use crate::core::{Config, ManifestDependency, Package, PackageId, SourceId, Summary};
-use crate::sources::PathSource;
+use crate::sources::{GitSource, PathSource, RegistrySource, ReplacedSource};
 
 /// Source of information about a group of packages.
 pub struct SourceMap<'c> {
@@ -47,13 +48,33 @@ impl<'c> SourceMap<'c> {
             Ok(source)
         } else {
             trace!("loading source: {source_id}");
-            let source = source_id
-                .load(self.config)
+            let source = self
+                .load(source_id)
                 .with_context(|| format!("failed to load source: {source_id}"))?;
             self.sources.write().await.insert(source_id, source.clone());
             Ok(source)
         }
     }
+
+    /// Load the source, or its replacement if one is configured in the `[source]` table.
+    fn load(&self, source_id: SourceId) -> Result<Arc<dyn Source + 'c>> {
+        let replacements = SourceReplacementMap::new(&self.config.global_config()?.source)?;
+        let Some(replace_with) = replacements.lookup(source_id)? else {
+            return source_id.load(self.config);
+        };
+
+        trace!("replacing source: {source_id} with: {replace_with}");
+        if let Some(reference) = replace_with.git_reference() {
+            // Git mirrors are checked out as if they were the original repository.
+            let source =
+                GitSource::with_custom_repo(&replace_with.url, reference, source_id, self.config)?;
+            Ok(Arc::new(source))
+        } else {
+            let inner = RegistrySource::new(replace_with, self.config)?;
+            let source = ReplacedSource::new(source_id, replace_with, inner);
+            Ok(Arc::new(source))
+        }
+    }
 }
 
 #[async_trait(?Send)]
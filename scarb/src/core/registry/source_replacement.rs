@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use smol_str::SmolStr;
+use url::Url;
+
+use crate::core::global_config::TomlSource;
+use crate::core::{SourceId, SourceKind, DEFAULT_REGISTRY_PATCH_SOURCE};
+use crate::sources::canonical_url::CanonicalUrl;
+
+/// Replacements of sources, defined in the `[source]` table of the global config.
+///
+/// Registries can be replaced with other registries, for example mirrors or local directories.
+/// Git repositories can be replaced with other Git repositories, or with registries.
+#[derive(Clone, Debug, Default)]
+pub struct SourceReplacementMap {
+    registries: HashMap<CanonicalUrl, ReplaceWith>,
+    git: HashMap<CanonicalUrl, ReplaceWith>,
+}
+
+#[derive(Clone, Debug)]
+enum ReplaceWith {
+    Registry(Url),
+    Git(Url),
+}
+
+enum SourceLocation {
+    Registry(Url),
+    Git(Url),
+}
+
+impl SourceReplacementMap {
+    pub fn new(sources: &BTreeMap<SmolStr, TomlSource>) -> Result<Self> {
+        let mut map = Self::default();
+        for (name, source) in sources {
+            let Some(replace_with_name) = &source.replace_with else {
+                continue;
+            };
+            let result = (|| -> Result<()> {
+                let Some(replace_with) = sources.get(replace_with_name) else {
+                    bail!("source `{replace_with_name}` is not defined");
+                };
+                ensure!(
+                    replace_with.replace_with.is_none(),
+                    "source `{replace_with_name}` is replaced too, \
+                    but chained replacements are not supported"
+                );
+
+                let replace_with = match location(replace_with_name, replace_with)? {
+                    SourceLocation::Registry(url) => ReplaceWith::Registry(url),
+                    SourceLocation::Git(url) => ReplaceWith::Git(url),
+                };
+
+                match (location(name, source)?, replace_with) {
+                    (SourceLocation::Registry(_), ReplaceWith::Git(_)) => {
+                        bail!("registry cannot be replaced with Git source `{replace_with_name}`");
+                    }
+                    (SourceLocation::Registry(url), replace_with) => {
+                        map.registries
+                            .insert(CanonicalUrl::new(&url)?, replace_with);
+                    }
+                    (SourceLocation::Git(url), replace_with) => {
+                        map.git.insert(CanonicalUrl::new(&url)?, replace_with);
+                    }
+                }
+                Ok(())
+            })();
+            result.with_context(|| format!("failed to replace source `{name}`"))?;
+        }
+        Ok(map)
+    }
+
+    /// Find the source which should be used instead of the given one, if any.
+    ///
+    /// Git sources replaced with other Git repositories retain their reference,
+    /// subdirectory and locked revision.
+    pub fn lookup(&self, source_id: SourceId) -> Result<Option<SourceId>> {
+        let replace_with = match &source_id.kind {
+            SourceKind::Registry => self.registries.get(&source_id.canonical_url),
+            SourceKind::Git(_) => self.git.get(&source_id.canonical_url),
+            SourceKind::Path | SourceKind::Std => None,
+        };
+        let Some(replace_with) = replace_with else {
+            return Ok(None);
+        };
+        let replace_with = match replace_with {
+            ReplaceWith::Registry(url) => SourceId::for_registry(url)?,
+            ReplaceWith::Git(url) => {
+                let spec = source_id
+                    .kind
+                    .as_git_source_spec()
+                    .expect("only Git sources can be replaced with Git sources");
+                let replace_with = SourceId::for_git(url, &spec.reference)?;
+                let replace_with = match &spec.subdir {
+                    Some(subdir) => replace_with.with_subdir(subdir.clone())?,
+                    None => replace_with,
+                };
+                match &spec.precise {
+                    Some(precise) => replace_with.with_precise(precise.clone())?,
+                    None => replace_with,
+                }
+            }
+        };
+        Ok(Some(replace_with))
+    }
+}
+
+fn location(name: &str, source: &TomlSource) -> Result<SourceLocation> {
+    if name == DEFAULT_REGISTRY_PATCH_SOURCE {
+        ensure!(
+            source.registry.is_none() && source.local_registry.is_none() && source.git.is_none(),
+            "source `{name}` refers to the default registry, so it cannot specify its location"
+        );
+        return Ok(SourceLocation::Registry(
+            SourceId::default_registry().url.clone(),
+        ));
+    }
+
+    match (&source.registry, &source.local_registry, &source.git) {
+        (Some(url), None, None) => Ok(SourceLocation::Registry(url.clone())),
+        (None, Some(path), None) => Url::from_directory_path(path)
+            .map(SourceLocation::Registry)
+            .map_err(|_| anyhow!("local registry path is not absolute: {path}")),
+        (None, None, Some(url)) => Ok(SourceLocation::Git(url.clone())),
+        _ => bail!(
+            "source `{name}` must specify exactly one of `registry`, `local-registry` or `git`"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use indoc::indoc;
+    use smol_str::SmolStr;
+    use url::Url;
+
+    use crate::core::global_config::TomlSource;
+    use crate::core::{GitReference, SourceId};
+
+    use super::SourceReplacementMap;
+
+    fn parse(toml: &str) -> BTreeMap<SmolStr, TomlSource> {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn replace_default_registry() {
+        let map = SourceReplacementMap::new(&parse(indoc! {r#"
+            ["scarb.xyz"]
+            replace-with = "mirror"
+
+            [mirror]
+            registry = "https://mirror.example.com/index/"
+        "#}))
+        .unwrap();
+
+        let replaced = map.lookup(SourceId::default_registry()).unwrap().unwrap();
+        assert!(replaced.is_registry());
+        assert_eq!(replaced.url.as_str(), "https://mirror.example.com/index/");
+
+        let other = SourceId::for_registry(&Url::parse("https://example.com/").unwrap()).unwrap();
+        assert_eq!(map.lookup(other).unwrap(), None);
+    }
+
+    #[test]
+    fn replace_git_with_git() {
+        let map = SourceReplacementMap::new(&parse(indoc! {r#"
+            [upstream]
+            git = "https://github.com/example/repo.git"
+            replace-with = "mirror"
+
+            [mirror]
+            git = "https://git.example.com/repo.git"
+        "#}))
+        .unwrap();
+
+        let source_id = SourceId::for_git(
+            &Url::parse("https://github.com/example/repo").unwrap(),
+            &GitReference::Tag("v1.0.0".into()),
+        )
+        .unwrap()
+        .with_precise("1f06df93".into())
+        .unwrap();
+
+        let replaced = map.lookup(source_id).unwrap().unwrap();
+        assert_eq!(
+            replaced.to_pretty_url(),
+            "git+https://git.example.com/repo.git?tag=v1.0.0#1f06df93"
+        );
+    }
+
+    #[test]
+    fn registry_cannot_be_replaced_with_git() {
+        let err = SourceReplacementMap::new(&parse(indoc! {r#"
+            ["scarb.xyz"]
+            replace-with = "mirror"
+
+            [mirror]
+            git = "https://git.example.com/repo.git"
+        "#}))
+        .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "failed to replace source `scarb.xyz`: \
+            registry cannot be replaced with Git source `mirror`"
+        );
+    }
+
+    #[test]
+    fn undefined_replacement() {
+        let err = SourceReplacementMap::new(&parse(indoc! {r#"
+            ["scarb.xyz"]
+            replace-with = "mirror"
+        "#}))
+        .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "failed to replace source `scarb.xyz`: source `mirror` is not defined"
+        );
+    }
+}
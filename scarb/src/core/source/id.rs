@@ -120,10 +120,14 @@ const GIT_SOURCE_PROTOCOL: &str = "git";
 const REGISTRY_SOURCE_PROTOCOL: &str = "registry";
 const STD_SOURCE_PROTOCOL: &str = "std";
 
-#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct GitSourceSpec {
     pub reference: GitReference,
     pub precise: Option<String>,
+    /// Path to the directory within the repository, where the workspace or package is located.
+    ///
+    /// Uses forward slashes as separators, regardless of the platform.
+    pub subdir: Option<SmolStr>,
 }
 
 impl GitSourceSpec {
@@ -131,6 +135,7 @@ impl GitSourceSpec {
         Self {
             reference,
             precise: None,
+            subdir: None,
         }
     }
 
@@ -140,6 +145,25 @@ impl GitSourceSpec {
             ..self
         }
     }
+
+    pub fn with_subdir(self, subdir: SmolStr) -> Self {
+        Self {
+            subdir: Some(subdir),
+            ..self
+        }
+    }
+}
+
+impl Hash for GitSourceSpec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.reference.hash(state);
+        self.precise.hash(state);
+        // Only hash subdirectory if present, so that hashes of sources without one,
+        // and thus cache directory names derived from them, remain stable.
+        if let Some(subdir) = &self.subdir {
+            subdir.hash(state);
+        }
+    }
 }
 
 /// Information to find a specific commit in a Git repository.
@@ -180,6 +204,21 @@ impl SourceId {
         }))
     }
 
+    /// Creates a new `SourceId` from this source with the given repository `subdir`.
+    pub fn with_subdir(self, subdir: SmolStr) -> Result<SourceId> {
+        let kind = self
+            .kind
+            .as_git_source_spec()
+            .map(|spec| spec.clone().with_subdir(subdir))
+            .map(SourceKind::Git)
+            .ok_or_else(|| anyhow!("cannot set subdirectory for non-git source: {self}"))?;
+
+        Ok(Self::intern(SourceIdInner {
+            kind,
+            ..(*self).clone()
+        }))
+    }
+
     pub fn can_lock_source_id(self, other: Self) -> bool {
         if self == other {
             return true;
@@ -298,7 +337,11 @@ impl SourceId {
         match &self.kind {
             SourceKind::Path => format!("{PATH_SOURCE_PROTOCOL}+{}", self.url),
 
-            SourceKind::Git(GitSourceSpec { reference, precise }) => {
+            SourceKind::Git(GitSourceSpec {
+                reference,
+                precise,
+                subdir,
+            }) => {
                 let mut url = self.url.clone();
                 match reference {
                     GitReference::Tag(tag) => {
@@ -312,6 +355,9 @@ impl SourceId {
                     }
                     GitReference::DefaultBranch => {}
                 }
+                if let Some(subdir) = subdir {
+                    url.query_pairs_mut().append_pair("subdir", subdir);
+                }
                 let precise = precise
                     .as_ref()
                     .map(|p| format!("#{p}"))
@@ -356,11 +402,13 @@ impl SourceId {
                     .unwrap_or_else(|| Ok((url()?, None)))?;
 
                 let mut reference = GitReference::DefaultBranch;
+                let mut subdir = None;
                 for (k, v) in url.query_pairs() {
                     match &k[..] {
                         "branch" => reference = GitReference::Branch(v.into()),
                         "rev" => reference = GitReference::Rev(v.into()),
                         "tag" => reference = GitReference::Tag(v.into()),
+                        "subdir" => subdir = Some(v.into()),
                         _ => {}
                     }
                 }
@@ -368,6 +416,7 @@ impl SourceId {
                 url.set_query(None);
 
                 let sid = SourceId::for_git(&url, &reference)?;
+                let sid = subdir.map(|s| sid.with_subdir(s)).unwrap_or(Ok(sid))?;
                 precise.map(|p| sid.with_precise(p)).unwrap_or(Ok(sid))
             }
 
@@ -523,6 +572,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn includes_subdir() {
+        let sid = SourceId::mock_git()
+            .with_subdir("crates/foo".into())
+            .unwrap()
+            .with_precise("some_rev".into())
+            .unwrap();
+        assert_eq!(
+            sid.to_pretty_url(),
+            "git+https://github.com/starkware-libs/cairo.git?tag=test&subdir=crates%2Ffoo#some_rev"
+        );
+        assert_eq!(
+            SourceId::from_pretty_url(&sid.to_pretty_url()).unwrap(),
+            sid
+        );
+        assert_ne!(
+            sid,
+            SourceId::mock_git()
+                .with_precise("some_rev".into())
+                .unwrap()
+        );
+    }
+
     // NOTE: Path sources are deliberately not tested here, because paths have different form
     //   depending on running OS. We simply trust that this code works in that case.
     #[test_case(SourceId::mock_git() => "github.com-192sksn8g7p8c")]
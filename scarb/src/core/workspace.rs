@@ -9,6 +9,8 @@ use scarb_ui::args::PackagesSource;
 use crate::compiler::Profile;
 use crate::core::config::Config;
 use crate::core::package::Package;
+use crate::core::registry::named_registries::NamedRegistries;
+use crate::core::registry::patch_map::PatchMap;
 use crate::core::{PackageId, Target};
 use crate::flock::Filesystem;
 use crate::{DEFAULT_TARGET_DIR_NAME, LOCK_FILE_NAME, MANIFEST_FILE_NAME};
@@ -22,6 +24,8 @@ pub struct Workspace<'c> {
     members: BTreeMap<PackageId, Package>,
     manifest_path: Utf8PathBuf,
     profiles: Vec<Profile>,
+    patch: PatchMap,
+    registries: NamedRegistries,
     root_package: Option<PackageId>,
     target_dir: Filesystem,
 }
@@ -33,6 +37,8 @@ impl<'c> Workspace<'c> {
         root_package: Option<PackageId>,
         config: &'c Config,
         profiles: Vec<Profile>,
+        patch: PatchMap,
+        registries: NamedRegistries,
     ) -> Result<Self> {
         let targets = packages
             .iter()
@@ -55,6 +61,8 @@ impl<'c> Workspace<'c> {
             config,
             manifest_path,
             profiles,
+            patch,
+            registries,
             root_package,
             target_dir,
             members: packages,
@@ -65,6 +73,8 @@ impl<'c> Workspace<'c> {
         package: Package,
         config: &'c Config,
         profiles: Vec<Profile>,
+        patch: PatchMap,
+        registries: NamedRegistries,
     ) -> Result<Self> {
         let manifest_path = package.manifest_path().to_path_buf();
         let root_package = Some(package.id);
@@ -75,6 +85,8 @@ impl<'c> Workspace<'c> {
             root_package,
             config,
             profiles,
+            patch,
+            registries,
         )
     }
 
@@ -162,6 +174,16 @@ impl<'c> Workspace<'c> {
         Ok(profile)
     }
 
+    /// Returns patches defined in the `[patch]` table of the workspace root manifest.
+    pub fn patch(&self) -> &PatchMap {
+        &self.patch
+    }
+
+    /// Returns registries defined in the global config and the workspace root manifest.
+    pub fn registries(&self) -> &NamedRegistries {
+        &self.registries
+    }
+
     pub fn profile_names(&self) -> Result<Vec<String>> {
         let mut names = self
             .profiles
@@ -36,12 +36,17 @@ where
             Ok(future.await?)
         } else {
             let future = {
+                // Another task might have started loading this key, while we were waiting
+                // for the write lock.
                 let mut futures = self.futures.write().await;
-                let future = (self.load_fn)(key.clone(), self.context.clone())
-                    .boxed_local()
-                    .shared();
-                futures.insert(key.clone(), future.clone());
-                future
+                futures
+                    .entry(key.clone())
+                    .or_insert_with(|| {
+                        (self.load_fn)(key, self.context.clone())
+                            .boxed_local()
+                            .shared()
+                    })
+                    .clone()
             };
 
             Ok(future.await?)
@@ -1,13 +1,20 @@
+use std::iter;
 use std::mem;
 
 use anyhow::{anyhow, ensure, Context, Result};
 use camino::{Utf8Path, Utf8PathBuf};
 use indoc::formatdoc;
+use semver::Version;
 use toml_edit::{value, Document, Entry, InlineTable, Item};
 use url::Url;
 
-use crate::core::{GitReference, PackageName};
+use crate::core::registry::source_map::SourceMap;
+use crate::core::registry::Registry;
+use crate::core::{
+    Config, DependencyVersionReq, GitReference, ManifestDependency, PackageName, SourceId,
+};
 use crate::internal::fsx;
+use crate::internal::to_version::ToVersion;
 use crate::sources::canonical_url::CanonicalUrl;
 
 use super::tomlx::get_table_mut;
@@ -148,11 +155,15 @@ impl Dep {
                 reference,
             })
         } else {
-            Box::new(RegistrySource {
-                version: version.ok_or_else(|| {
-                    anyhow!("please specify package version requirement, for example: {name}@1.0.0")
-                })?,
-            })
+            let version = match version {
+                Some(version) => version,
+                None => {
+                    let version = latest_compatible_version(&name, ctx.opts.config)?;
+                    format!("^{version}")
+                }
+            };
+
+            Box::new(RegistrySource { version })
         };
 
         Ok(Dep { name, source })
@@ -211,6 +222,46 @@ impl Source for GitSource {
     }
 }
 
+/// Find the newest non-yanked version of a package in the default registry, which is compatible
+/// with the Cairo version of this Scarb. Pre-releases are only picked if there are no stable
+/// versions available.
+///
+/// In offline mode, only the index cached locally is consulted.
+fn latest_compatible_version(name: &PackageName, config: &Config) -> Result<Version> {
+    let source_id = SourceId::default_registry();
+    let dependency = ManifestDependency::builder()
+        .name(name.clone())
+        .version_req(DependencyVersionReq::Any)
+        .source_id(source_id)
+        .build();
+
+    let registry = SourceMap::preloaded(iter::empty(), config);
+    let summaries = config
+        .tokio_handle()
+        .block_on(registry.query(&dependency))?;
+
+    let cairo_version = crate::version::get().cairo.version.to_version()?;
+    summaries
+        .iter()
+        .filter(|summary| !summary.yanked)
+        .filter(|summary| {
+            summary
+                .cairo_version
+                .as_ref()
+                .map_or(true, |req| req.matches(&cairo_version))
+        })
+        .map(|summary| &summary.package_id.version)
+        .max_by_key(|version| (version.pre.is_empty(), *version))
+        .cloned()
+        .ok_or_else(|| {
+            anyhow!(
+                "cannot find a version of package `{name}` compatible with Cairo {cairo_version} \
+                in registry: {source_id}\n\
+                help: specify package version requirement, for example: {name}@1.0.0"
+            )
+        })
+}
+
 fn expand_version_shortcut(item: &mut Item) {
     if item.is_value() && !item.is_inline_table() {
         let version = mem::replace(item, value(InlineTable::new()));
@@ -236,6 +287,7 @@ fn purge_source(tab: &mut InlineTable) {
     tab.remove("branch");
     tab.remove("tag");
     tab.remove("rev");
+    tab.remove("subdir");
 }
 
 fn path_value(manifest_path: &Utf8Path, abs_path: &Utf8Path) -> String {
@@ -1,4 +1,11 @@
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
 use anyhow::{anyhow, Result};
+use cairo_lang_compiler::db::RootDatabase;
 use cairo_lang_compiler::diagnostics::DiagnosticsError;
 use indoc::formatdoc;
 
@@ -6,19 +13,53 @@ use scarb_ui::components::Status;
 use scarb_ui::HumanDuration;
 
 use crate::compiler::db::{build_scarb_root_database, has_starknet_plugin};
-use crate::compiler::CompilationUnit;
-use crate::core::{PackageId, TargetKind, Utf8PathWorkspaceExt, Workspace};
+use crate::compiler::helpers::build_compiler_config;
+use crate::compiler::{CompilationUnit, Fingerprint};
+use crate::core::{PackageId, PackageName, TargetKind, Utf8PathWorkspaceExt, Workspace};
 use crate::ops;
 
 #[derive(Debug)]
 pub struct CompileOpts {
     pub include_targets: Vec<TargetKind>,
     pub exclude_targets: Vec<TargetKind>,
+    pub features: ops::FeaturesOpts,
+    /// Maximum number of compilation units compiled in parallel.
+    ///
+    /// Defaults to the number of available CPUs, capped by `build.jobs` from the global config.
+    pub jobs: Option<NonZeroUsize>,
+    /// Continue compiling remaining units after one of them fails.
+    pub keep_going: bool,
 }
 
 #[tracing::instrument(skip_all, level = "debug")]
 pub fn compile(packages: Vec<PackageId>, opts: CompileOpts, ws: &Workspace<'_>) -> Result<()> {
-    let resolve = ops::resolve_workspace(ws)?;
+    process(packages, opts, ws, compile_unit, None)
+}
+
+/// Run semantic analysis of selected packages and report diagnostics, without generating
+/// any compilation artifacts.
+#[tracing::instrument(skip_all, level = "debug")]
+pub fn check(packages: Vec<PackageId>, opts: CompileOpts, ws: &Workspace<'_>) -> Result<()> {
+    process(packages, opts, ws, check_unit, Some("checking"))
+}
+
+fn process<F>(
+    packages: Vec<PackageId>,
+    opts: CompileOpts,
+    ws: &Workspace<'_>,
+    operation: F,
+    operation_type: Option<&str>,
+) -> Result<()>
+where
+    F: Fn(CompilationUnit, &Workspace<'_>) -> Result<()> + Sync,
+{
+    let resolve = ops::resolve_workspace_with_opts(
+        ws,
+        &ops::ResolveOpts {
+            features: opts.features.clone(),
+            ..Default::default()
+        },
+    )?;
 
     // Add test compilation units to build
     let packages = packages
@@ -45,33 +86,182 @@ pub fn compile(packages: Vec<PackageId>, opts: CompileOpts, ws: &Workspace<'_>)
         .filter(|cu| packages.contains(&cu.main_package_id))
         .collect::<Vec<_>>();
 
-    for unit in compilation_units {
-        compile_unit(unit, ws)?;
-    }
+    process_units(compilation_units, &opts, ws, operation)?;
 
     let elapsed_time = HumanDuration(ws.config().elapsed_time());
+    let operation_type = operation_type
+        .map(|operation_type| format!("{operation_type} "))
+        .unwrap_or_default();
     ws.config().ui().print(Status::new(
         "Finished",
-        &format!("release target(s) in {elapsed_time}"),
+        &format!("{operation_type}release target(s) in {elapsed_time}"),
     ));
 
     Ok(())
 }
 
+/// Process units using a pool of worker threads.
+///
+/// Each unit builds its own database, so units are independent of each other.
+/// Unless `--jobs` is passed, the number of workers is capped by `build.jobs` from the global
+/// config, because memory usage grows with the number of databases alive at once.
+/// Messages printed while compiling a unit are buffered and printed in the order of units,
+/// so that diagnostics of units compiled concurrently do not interleave.
+/// Unless [`CompileOpts::keep_going`] is set, no new units are started after the first failure.
+/// All failures are reported, and the error of the last failed unit is returned.
+fn process_units<F>(
+    units: Vec<CompilationUnit>,
+    opts: &CompileOpts,
+    ws: &Workspace<'_>,
+    operation: F,
+) -> Result<()>
+where
+    F: Fn(CompilationUnit, &Workspace<'_>) -> Result<()> + Sync,
+{
+    let jobs = match opts.jobs {
+        Some(jobs) => jobs.get(),
+        // Each unit holds its own compiler database in memory, so do not saturate all CPUs
+        // unless explicitly asked to.
+        None => thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(ws.config().global_config()?.build.jobs),
+    }
+    .min(units.len());
+
+    let mut failures = Vec::new();
+    if jobs <= 1 {
+        for unit in units {
+            if let Err(err) = operation(unit, ws) {
+                failures.push(err);
+                if !opts.keep_going {
+                    break;
+                }
+            }
+        }
+    } else {
+        let queue = Mutex::new(units.into_iter().enumerate());
+        let cancelled = AtomicBool::new(false);
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|s| {
+            for _ in 0..jobs {
+                let tx = tx.clone();
+                let queue = &queue;
+                let cancelled = &cancelled;
+                let operation = &operation;
+                s.spawn(move || {
+                    while !cancelled.load(Ordering::Relaxed) {
+                        // Units are taken in order, so all units preceding a failed one
+                        // are always compiled and reported.
+                        let Some((index, unit)) = queue.lock().unwrap().next() else {
+                            break;
+                        };
+                        let (result, output) = scarb_ui::capture(|| operation(unit, ws));
+                        if result.is_err() && !opts.keep_going {
+                            cancelled.store(true, Ordering::Relaxed);
+                        }
+                        if tx.send((index, result, output)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(tx);
+
+            // Print output of each unit as soon as all preceding units are done.
+            let mut pending = BTreeMap::new();
+            let mut next = 0;
+            for (index, result, output) in rx {
+                pending.insert(index, (result, output));
+                while let Some((result, output)) = pending.remove(&next) {
+                    output.print();
+                    if let Err(err) = result {
+                        failures.push(err);
+                    }
+                    next += 1;
+                }
+            }
+        });
+    }
+
+    let Some(last) = failures.pop() else {
+        return Ok(());
+    };
+    for err in &failures {
+        ws.config().ui().anyhow(err);
+    }
+    Err(last)
+}
+
 fn compile_unit(unit: CompilationUnit, ws: &Workspace<'_>) -> Result<()> {
     let package_name = unit.main_package_id.name.clone();
 
+    let fingerprint = Fingerprint::compute(&unit, ws)?;
+    if let Some(messages) = fingerprint.check_fresh(&unit, ws) {
+        ws.config().ui().print(Status::new("Fresh", &unit.name()));
+        messages.print();
+        return Ok(());
+    }
+
     ws.config()
         .ui()
         .print(Status::new("Compiling", &unit.name()));
 
     let mut db = build_scarb_root_database(&unit, ws)?;
 
+    // Capture warnings, so that they can be printed again while the unit stays fresh.
+    let (result, messages) = scarb_ui::capture(|| {
+        check_starknet_dependency(&unit, ws, &db, &package_name);
+
+        ws.config().compilers().compile(unit.clone(), &mut db, ws)
+    });
+    messages.clone().print();
+
+    let outputs = result.map_err(|err| {
+        if !suppress_error(&err) {
+            ws.config().ui().anyhow(&err);
+        }
+
+        anyhow!("could not compile `{package_name}` due to previous error")
+    })?;
+
+    fingerprint.store(&unit, outputs, &messages, ws)?;
+
+    Ok(())
+}
+
+fn check_unit(unit: CompilationUnit, ws: &Workspace<'_>) -> Result<()> {
+    let package_name = unit.main_package_id.name.clone();
+
+    ws.config()
+        .ui()
+        .print(Status::new("Checking", &unit.name()));
+
+    let mut db = build_scarb_root_database(&unit, ws)?;
+
+    check_starknet_dependency(&unit, ws, &db, &package_name);
+
+    let mut compiler_config = build_compiler_config(&unit, ws);
+
+    compiler_config
+        .diagnostics_reporter
+        .ensure(&mut db)
+        // Diagnostics have already been printed by the reporter.
+        .map_err(|_| anyhow!("could not check `{package_name}` due to previous error"))?;
+
+    Ok(())
+}
+
+fn check_starknet_dependency(
+    unit: &CompilationUnit,
+    ws: &Workspace<'_>,
+    db: &RootDatabase,
+    package_name: &PackageName,
+) {
     // NOTE: This is a special case that can be hit frequently by newcomers. Not specifying
     //   `starknet` dependency will error in 99% real-world Starknet contract projects.
     //   I think we can get away with emitting false positives for users who write raw contracts
     //   without using Starknet code generators. Such people shouldn't do what they do 😁
-    if unit.target().kind == TargetKind::STARKNET_CONTRACT && !has_starknet_plugin(&db) {
+    if unit.target().kind == TargetKind::STARKNET_CONTRACT && !has_starknet_plugin(db) {
         ws.config().ui().warn(formatdoc! {
             r#"
             package `{package_name}` declares `starknet-contract` target, but does not depend on `starknet` package
@@ -85,19 +275,6 @@ fn compile_unit(unit: CompilationUnit, ws: &Workspace<'_>) -> Result<()> {
             cairo_version = crate::version::get().cairo.version,
         })
     }
-
-    ws.config()
-        .compilers()
-        .compile(unit, &mut db, ws)
-        .map_err(|err| {
-            if !suppress_error(&err) {
-                ws.config().ui().anyhow(&err);
-            }
-
-            anyhow!("could not compile `{package_name}` due to previous error")
-        })?;
-
-    Ok(())
 }
 
 fn suppress_error(err: &anyhow::Error) -> bool {
@@ -1,19 +1,28 @@
-use crate::core::lockfile::Lockfile;
+use crate::core::lockfile::{Lockfile, PackageLock};
 use crate::core::Workspace;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use fs4::FileExt;
+use indoc::formatdoc;
+use itertools::{EitherOrBoth, Itertools};
+use std::fmt;
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Write};
+use std::io::{ErrorKind, Read, Write};
 use std::str::FromStr;
 
 #[tracing::instrument(skip_all, level = "debug")]
 pub fn read_lockfile(ws: &Workspace<'_>) -> Result<Lockfile> {
-    let mut file = OpenOptions::new()
+    // Do not create or even open the lockfile for writing if it is not allowed to be modified,
+    // so that it can be read from read-only checkouts.
+    let writable = !ws.config().locked();
+    let file = OpenOptions::new()
         .read(true)
-        .write(true)
-        .create(true)
-        .open(ws.lockfile_path())
-        .context("failed to open lockfile")?;
+        .write(writable)
+        .create(writable)
+        .open(ws.lockfile_path());
+    let mut file = match file {
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Lockfile::default()),
+        file => file.context("failed to open lockfile")?,
+    };
 
     file.lock_shared()
         .context("failed to acquire shared lockfile access")?;
@@ -26,6 +35,10 @@ pub fn read_lockfile(ws: &Workspace<'_>) -> Result<Lockfile> {
 
 #[tracing::instrument(skip_all, level = "debug")]
 pub fn write_lockfile(lockfile: Lockfile, ws: &Workspace<'_>) -> Result<()> {
+    if ws.config().locked() {
+        return check_lockfile_unchanged(lockfile, ws);
+    }
+
     let mut file = File::create(ws.lockfile_path()).context("failed to create lockfile")?;
 
     file.lock_exclusive()
@@ -36,3 +49,104 @@ pub fn write_lockfile(lockfile: Lockfile, ws: &Workspace<'_>) -> Result<()> {
 
     Ok(())
 }
+
+fn check_lockfile_unchanged(lockfile: Lockfile, ws: &Workspace<'_>) -> Result<()> {
+    let current = read_lockfile(ws)?;
+    // Compare the lockfile in the exact form it would be stored on disk.
+    let lockfile = Lockfile::from_str(&lockfile.render()?)?;
+    if lockfile == current {
+        return Ok(());
+    }
+
+    let flag = if ws.config().frozen() {
+        "--frozen"
+    } else {
+        "--locked"
+    };
+    let changes = lockfile_changes(&current, &lockfile)
+        .map(|change| format!("  {} {change}", change.verb()))
+        .join("\n");
+    bail!(formatdoc! {"
+        the lockfile {path} needs to be updated, but {flag} was passed to prevent this
+        following packages would change:
+        {changes}
+        help: run the command without {flag} to update the lockfile
+        ",
+        path = ws.lockfile_path(),
+    });
+}
+
+/// A change to a single [`PackageLock`] entry between two lockfiles.
+pub enum PackageLockChange<'a> {
+    Added(&'a PackageLock),
+    Removed(&'a PackageLock),
+    Updated(&'a PackageLock, &'a PackageLock),
+}
+
+impl PackageLockChange<'_> {
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Self::Added(_) => "Adding",
+            Self::Removed(_) => "Removing",
+            Self::Updated(..) => "Updating",
+        }
+    }
+
+    /// The package entry after the change, or before it if the package was removed.
+    pub fn package(&self) -> &PackageLock {
+        match self {
+            Self::Added(package) | Self::Removed(package) | Self::Updated(_, package) => package,
+        }
+    }
+}
+
+impl fmt::Display for PackageLockChange<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn describe(package: &PackageLock) -> String {
+            match package.source {
+                Some(source) if !source.is_path() && !source.is_default_registry() => {
+                    format!("{} v{} ({source})", package.name, package.version)
+                }
+                _ => format!("{} v{}", package.name, package.version),
+            }
+        }
+
+        match self {
+            Self::Added(package) | Self::Removed(package) => f.write_str(&describe(package)),
+            Self::Updated(old, new) => {
+                let new_rev = new
+                    .source
+                    .and_then(|s| s.kind.as_git_source_spec().cloned())
+                    .and_then(|spec| spec.precise);
+                if old.version != new.version {
+                    write!(f, "{} -> v{}", describe(old), new.version)
+                } else if old.source != new.source {
+                    match new_rev {
+                        Some(rev) => write!(f, "{} -> #{rev}", describe(old)),
+                        None => write!(f, "{} -> {}", describe(old), describe(new)),
+                    }
+                } else if old.checksum != new.checksum {
+                    write!(f, "{} (checksum changed)", describe(old))
+                } else {
+                    write!(f, "{} (dependencies changed)", describe(old))
+                }
+            }
+        }
+    }
+}
+
+/// Compare lockfile entries package by package.
+pub fn lockfile_changes<'a>(
+    previous: &'a Lockfile,
+    current: &'a Lockfile,
+) -> impl Iterator<Item = PackageLockChange<'a>> {
+    previous
+        .packages()
+        .merge_join_by(current.packages(), |a, b| a.name.cmp(&b.name))
+        .filter_map(|change| match change {
+            EitherOrBoth::Both(old, new) if old == new => None,
+            EitherOrBoth::Both(old, new) => Some(PackageLockChange::Updated(old, new)),
+            EitherOrBoth::Left(old) => Some(PackageLockChange::Removed(old)),
+            EitherOrBoth::Right(new) => Some(PackageLockChange::Added(new)),
+        })
+}
@@ -0,0 +1,34 @@
+use anyhow::{ensure, Result};
+use url::Url;
+
+use scarb_ui::components::Status;
+
+use crate::core::registry::credentials::{remove_registry_token, store_registry_token};
+use crate::core::{Config, SourceId};
+
+/// Save the token for authenticating in the registry with the given index URL.
+#[tracing::instrument(skip(token, config), level = "debug")]
+pub fn registry_login(index_url: &Url, token: String, config: &Config) -> Result<()> {
+    let token = token.trim().to_string();
+    ensure!(!token.is_empty(), "registry token must not be empty");
+
+    let source_id = SourceId::for_registry(index_url)?;
+    store_registry_token(source_id, token, config)?;
+
+    let message = format!("token for `{index_url}` saved");
+    config.ui().print(Status::new("Login", &message));
+    Ok(())
+}
+
+/// Remove the saved token for authenticating in the registry with the given index URL.
+#[tracing::instrument(skip(config), level = "debug")]
+pub fn registry_logout(index_url: &Url, config: &Config) -> Result<()> {
+    let source_id = SourceId::for_registry(index_url)?;
+    let message = if remove_registry_token(source_id, config)? {
+        format!("token for `{index_url}` has been removed from local storage")
+    } else {
+        format!("not currently logged in to `{index_url}`")
+    };
+    config.ui().print(Status::new("Logout", &message));
+    Ok(())
+}
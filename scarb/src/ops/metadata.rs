@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use anyhow::{bail, Result};
 use itertools::Itertools;
@@ -10,8 +10,8 @@ use scarb_ui::args::PackagesSource;
 
 use crate::compiler::CompilationUnit;
 use crate::core::{
-    edition_variant, DepKind, DependencyVersionReq, ManifestDependency, Package, PackageId,
-    SourceId, Target, Workspace,
+    edition_variant, DepKind, DependencyVersionReq, FeatureName, ManifestDependency, Package,
+    PackageId, SourceId, Target, Workspace,
 };
 use crate::ops;
 use crate::version::CommitInfo;
@@ -19,6 +19,7 @@ use crate::version::CommitInfo;
 pub struct MetadataOptions {
     pub version: u64,
     pub no_deps: bool,
+    pub features: ops::FeaturesOpts,
 }
 
 #[tracing::instrument(skip_all, level = "debug")]
@@ -32,11 +33,17 @@ pub fn collect_metadata(opts: &MetadataOptions, ws: &Workspace<'_>) -> Result<m:
     }
 
     let (mut packages, mut compilation_units) = if !opts.no_deps {
-        let resolve = ops::resolve_workspace(ws)?;
+        let resolve = ops::resolve_workspace_with_opts(
+            ws,
+            &ops::ResolveOpts {
+                features: opts.features.clone(),
+                ..Default::default()
+            },
+        )?;
         let packages: Vec<m::PackageMetadata> = resolve
             .packages
             .values()
-            .map(collect_package_metadata)
+            .map(|p| collect_package_metadata(p, resolve.features.get(&p.id)))
             .collect();
 
         let compilation_units: Vec<m::CompilationUnitMetadata> =
@@ -47,7 +54,10 @@ pub fn collect_metadata(opts: &MetadataOptions, ws: &Workspace<'_>) -> Result<m:
 
         (packages, compilation_units)
     } else {
-        let packages = ws.members().map(|p| collect_package_metadata(&p)).collect();
+        let packages = ws
+            .members()
+            .map(|p| collect_package_metadata(&p, None))
+            .collect();
         (packages, Vec::new())
     };
 
@@ -80,7 +90,10 @@ fn collect_workspace_metadata(ws: &Workspace<'_>) -> Result<m::WorkspaceMetadata
         .unwrap())
 }
 
-fn collect_package_metadata(package: &Package) -> m::PackageMetadata {
+fn collect_package_metadata(
+    package: &Package,
+    enabled_features: Option<&BTreeSet<FeatureName>>,
+) -> m::PackageMetadata {
     let mut dependencies: Vec<m::DependencyMetadata> = package
         .manifest
         .summary
@@ -133,8 +146,32 @@ fn collect_package_metadata(package: &Package) -> m::PackageMetadata {
         .build()
         .unwrap();
 
+    let features = package
+        .manifest
+        .summary
+        .features
+        .iter()
+        .map(|(name, values)| {
+            let values = values.iter().map(ToString::to_string).collect();
+            (name.to_string(), values)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let enabled_features = enabled_features
+        .into_iter()
+        .flatten()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
     let edition = edition_variant(package.manifest.edition);
 
+    let publish = package.manifest.publish.as_ref().map(|registries| {
+        registries
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    });
+
     m::PackageMetadataBuilder::default()
         .id(wrap_package_id(package.id))
         .name(package.id.name.clone())
@@ -145,6 +182,9 @@ fn collect_package_metadata(package: &Package) -> m::PackageMetadata {
         .root(package.root())
         .dependencies(dependencies)
         .targets(targets)
+        .features(features)
+        .enabled_features(enabled_features)
+        .publish(publish)
         .manifest_metadata(manifest_metadata)
         .build()
         .unwrap()
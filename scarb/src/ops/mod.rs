@@ -6,6 +6,7 @@ pub use cache::*;
 pub use clean::*;
 pub use compile::*;
 pub use fmt::*;
+pub use login::*;
 pub use manifest::*;
 pub use metadata::*;
 pub use new::*;
@@ -13,14 +14,18 @@ pub use package::*;
 pub use publish::*;
 pub use resolve::*;
 pub use scripts::*;
+pub use search::*;
 pub use subcommands::*;
+pub use vendor::*;
 pub use workspace::*;
+pub use yank::*;
 
 mod cache;
 mod clean;
 mod compile;
 mod fmt;
 mod lockfile;
+mod login;
 mod manifest;
 mod metadata;
 mod new;
@@ -28,5 +33,8 @@ mod package;
 mod publish;
 mod resolve;
 mod scripts;
+mod search;
 mod subcommands;
+mod vendor;
 mod workspace;
+mod yank;
@@ -144,6 +144,12 @@ fn package_one_impl(
 ) -> Result<FileLockGuard> {
     let pkg = ws.fetch_package(&pkg_id)?;
 
+    ensure!(
+        !matches!(&pkg.manifest.publish, Some(registries) if registries.is_empty()),
+        "package `{}` cannot be packaged, because `publish` is set to `false` in its manifest",
+        pkg_id.name
+    );
+
     ws.config()
         .ui()
         .print(Status::new("Packaging", &pkg_id.to_string()));
@@ -167,7 +173,7 @@ fn package_one_impl(
     dst.set_len(0)
         .with_context(|| format!("failed to truncate: {filename}"))?;
 
-    let uncompressed_size = tar(pkg_id, recipe, &mut dst, ws)?;
+    let uncompressed_size = tar(pkg_id, recipe, &mut dst, ws.config())?;
 
     let mut dst = if opts.verify {
         run_verify(pkg, dst, ws).context("failed to verify package tarball")?
@@ -296,6 +302,46 @@ fn prepare_archive_recipe(pkg: &Package, opts: &PackageOpts) -> Result<ArchiveRe
     Ok(recipe)
 }
 
+/// Archive sources of a package fetched from a non-registry source, keeping its manifest as is.
+///
+/// Unlike [`package`], this does not normalize the manifest and does not run any checks,
+/// because the package is not a workspace member and thus cannot be altered by the user.
+/// Used for storing Git dependencies in vendored registries.
+pub(crate) fn package_sources(pkg: &Package, dst: &mut File, config: &Config) -> Result<()> {
+    let mut recipe = source_files(pkg)?;
+
+    recipe.push(ArchiveFile {
+        path: MANIFEST_FILE_NAME.into(),
+        contents: ArchiveFileContents::OnDisk(pkg.manifest_path().to_owned()),
+    });
+
+    // README and LICENSE files are skipped when listing source files, so add them back
+    // at the paths the manifest points to.
+    let metadata = &pkg.manifest.metadata;
+    for file in [&metadata.readme, &metadata.license_file]
+        .into_iter()
+        .flatten()
+    {
+        if let Ok(path) = file.strip_prefix(pkg.root()) {
+            recipe.push(ArchiveFile {
+                path: path.to_owned(),
+                contents: ArchiveFileContents::OnDisk(file.clone()),
+            });
+        }
+    }
+
+    recipe.push(ArchiveFile {
+        path: VERSION_FILE_NAME.into(),
+        contents: ArchiveFileContents::Generated(Box::new(|| Ok(VERSION.to_string().into_bytes()))),
+    });
+
+    sort_recipe(&mut recipe);
+    recipe.dedup_by(|a, b| a.path == b.path);
+
+    tar(pkg.id, recipe, dst, config)?;
+    Ok(())
+}
+
 fn run_verify(pkg: &Package, tar: FileLockGuard, ws: &Workspace<'_>) -> Result<FileLockGuard> {
     ws.config()
         .ui()
@@ -318,6 +364,9 @@ fn run_verify(pkg: &Package, tar: FileLockGuard, ws: &Workspace<'_>) -> Result<F
         ops::CompileOpts {
             include_targets: Vec::new(),
             exclude_targets: vec![TargetKind::TEST.clone()],
+            features: ops::FeaturesOpts::default(),
+            jobs: None,
+            keep_going: false,
         },
         &ws,
     )?;
@@ -414,12 +463,7 @@ fn normalize_manifest(pkg: Package) -> Result<Vec<u8>> {
 ///
 /// Returns the uncompressed size of the contents of the archive.
 #[tracing::instrument(level = "trace", skip_all)]
-fn tar(
-    pkg_id: PackageId,
-    recipe: ArchiveRecipe,
-    dst: &mut File,
-    ws: &Workspace<'_>,
-) -> Result<u64> {
+fn tar(pkg_id: PackageId, recipe: ArchiveRecipe, dst: &mut File, config: &Config) -> Result<u64> {
     const COMPRESSION_LEVEL: i32 = 22;
     let encoder = zstd::stream::Encoder::new(dst, COMPRESSION_LEVEL)?;
     let mut ar = tar::Builder::new(encoder);
@@ -428,9 +472,7 @@ fn tar(
 
     let mut uncompressed_size = 0;
     for ArchiveFile { path, contents } in recipe {
-        ws.config()
-            .ui()
-            .verbose(Status::new("Archiving", path.as_str()));
+        config.ui().verbose(Status::new("Archiving", path.as_str()));
 
         let archive_path = base_path.join(&path);
         let mut header = tar::Header::new_gnu();
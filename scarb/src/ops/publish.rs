@@ -1,9 +1,10 @@
-use anyhow::{ensure, Context, Result};
+use anyhow::{bail, ensure, Context, Result};
+use itertools::Itertools;
 use url::Url;
 
 use scarb_ui::components::Status;
 
-use crate::core::{PackageId, SourceId, Workspace};
+use crate::core::{Package, PackageId, SourceId, Workspace};
 use crate::ops;
 use crate::sources::RegistrySource;
 
@@ -19,6 +20,8 @@ pub fn publish(package_id: PackageId, opts: &PublishOpts, ws: &Workspace<'_>) ->
     let package = ws.fetch_package(&package_id)?.clone();
 
     let source_id = SourceId::for_registry(&opts.index_url)?;
+    check_publish_allowed(&package, source_id)?;
+
     let registry_client = RegistrySource::create_client(source_id, ws.config())?;
 
     let supports_publish = ws
@@ -47,3 +50,28 @@ pub fn publish(package_id: PackageId, opts: &PublishOpts, ws: &Workspace<'_>) ->
 
     Ok(())
 }
+
+/// Check whether the `publish` field of the package manifest allows publishing to the registry.
+fn check_publish_allowed(package: &Package, source_id: SourceId) -> Result<()> {
+    let Some(allowed) = &package.manifest.publish else {
+        return Ok(());
+    };
+
+    let name = &package.id.name;
+    ensure!(
+        !allowed.is_empty(),
+        "package `{name}` cannot be published, because `publish` is set to `false` in its manifest"
+    );
+
+    for url in allowed {
+        if SourceId::for_registry(url)? == source_id {
+            return Ok(());
+        }
+    }
+
+    bail!(
+        "package `{name}` cannot be published to registry: {source_id}\n\
+        help: registries allowed by the `publish` field are: {}",
+        allowed.iter().join(", ")
+    )
+}
@@ -1,15 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-use anyhow::{bail, Result};
+use anyhow::{bail, ensure, Context, Result};
 use cairo_lang_filesystem::cfg::{Cfg, CfgSet};
-use futures::TryFutureExt;
+use futures::{stream, StreamExt, TryFutureExt, TryStreamExt};
 use indoc::formatdoc;
 use itertools::Itertools;
+use scarb_ui::components::Status;
 
 use crate::compiler::{CompilationUnit, CompilationUnitCairoPlugin, CompilationUnitComponent};
-use crate::core::lockfile::Lockfile;
+use crate::core::lockfile::{Lockfile, PackageLock};
 use crate::core::package::{Package, PackageClass, PackageId};
 use crate::core::registry::cache::RegistryCache;
+use crate::core::registry::download_progress::DownloadProgress;
 use crate::core::registry::patch_map::PatchMap;
 use crate::core::registry::patcher::RegistryPatcher;
 use crate::core::registry::source_map::SourceMap;
@@ -17,16 +19,20 @@ use crate::core::registry::Registry;
 use crate::core::resolver::Resolve;
 use crate::core::workspace::Workspace;
 use crate::core::{
-    DepKind, DependencyVersionReq, ManifestDependency, PackageName, SourceId, Target, TargetKind,
-    TestTargetProps, TestTargetType,
+    Config, DepKind, DependencyVersionReq, FeatureName, ManifestDependency, PackageName, SourceId,
+    Target, TargetKind, TestTargetProps, TestTargetType,
 };
 use crate::internal::to_version::ToVersion;
-use crate::ops::lockfile::{read_lockfile, write_lockfile};
+use crate::ops::lockfile::{lockfile_changes, read_lockfile, write_lockfile, PackageLockChange};
 use crate::{resolver, DEFAULT_SOURCE_PATH};
 
+pub use crate::resolver::FeaturesOpts;
+
 pub struct WorkspaceResolve {
     pub resolve: Resolve,
     pub packages: HashMap<PackageId, Package>,
+    /// Features enabled in each package, unified across the whole dependency graph.
+    pub features: HashMap<PackageId, BTreeSet<FeatureName>>,
 }
 
 impl WorkspaceResolve {
@@ -50,8 +56,20 @@ impl WorkspaceResolve {
 
 #[derive(Debug, Default)]
 pub struct ResolveOpts {
-    /// Do not use lockfile when resolving.
+    /// Update dependencies, instead of using versions pinned in the lockfile.
     pub update: bool,
+    /// Only update these packages, keeping all other lockfile entries pinned.
+    ///
+    /// All packages are updated if this set is empty. Ignored if `update` is not set.
+    pub update_packages: BTreeSet<PackageName>,
+    /// Update the single package from `update_packages` to exactly this version or Git revision.
+    pub precise: Option<String>,
+    /// Features to enable in workspace members.
+    pub features: FeaturesOpts,
+    /// Keep optional dependencies which are not activated by enabled features in the resolve.
+    ///
+    /// These dependencies are locked anyway, but are not needed for building the workspace.
+    pub keep_inactive_optional: bool,
 }
 
 pub fn resolve_workspace(ws: &Workspace<'_>) -> Result<WorkspaceResolve> {
@@ -101,49 +119,250 @@ pub fn resolve_workspace_with_opts(
 
             let source_map = SourceMap::preloaded(ws.members(), ws.config());
             let cached = RegistryCache::new(&source_map);
-            let patched = RegistryPatcher::new(&cached, &patch_map);
 
             let members_summaries = ws
                 .members()
                 .map(|pkg| pkg.manifest.summary.clone())
                 .collect::<Vec<_>>();
 
-            let lockfile: Lockfile = if opts.update {
-                Lockfile::new([])
+            let previous_lockfile = read_lockfile(ws)?;
+            let (lockfile, precise) = if opts.update {
+                let mut patch_map = patch_map.clone();
+                insert_workspace_patches(&mut patch_map, ws, &Lockfile::default())?;
+                let patched = RegistryPatcher::new(&cached, &patch_map);
+                lockfile_for_update(&previous_lockfile, opts, &patched).await?
+            } else {
+                (previous_lockfile.clone(), None)
+            };
+
+            // Packages which are not being updated must keep their locked versions.
+            let pinned = if opts.update {
+                lockfile
+                    .packages()
+                    .map(|p| p.name.clone())
+                    .filter(|name| !opts.update_packages.contains(name))
+                    .collect()
             } else {
-                read_lockfile(ws)?
+                BTreeSet::new()
             };
 
-            let resolve =
-                resolver::resolve(&members_summaries, &patched, lockfile, ws.config().ui()).await?;
+            insert_workspace_patches(&mut patch_map, ws, &lockfile)?;
+            let patched = RegistryPatcher::new(&cached, &patch_map);
+
+            let mut resolve = resolver::resolve(
+                &members_summaries,
+                &patched,
+                lockfile,
+                &pinned,
+                ws.config().ui(),
+            )
+            .await?;
+
+            if let Some(package_id) = precise {
+                ensure!(
+                    resolve.package_ids().contains(&package_id),
+                    "cannot update `{}` to `{}`, because it does not match version \
+                    requirements of packages depending on it",
+                    package_id.name,
+                    opts.precise.as_deref().unwrap_or_default(),
+                );
+            }
+
+            // Validate requested features before touching the lockfile.
+            let members = ws.members().map(|pkg| pkg.id).collect_vec();
+            let features = resolver::resolve_features(&resolve, &members, &opts.features)?;
+
+            let patches = used_patches(&resolve, ws);
+            let lockfile = Lockfile::from_resolve(&resolve).with_patches(patches);
+            if opts.update {
+                print_lockfile_changes(&previous_lockfile, &lockfile, ws.config());
+            }
+            write_lockfile(lockfile, ws)?;
 
-            write_lockfile(Lockfile::from_resolve(&resolve), ws)?;
+            // Optional dependencies are always locked, but only activated ones are built.
+            if !opts.keep_inactive_optional {
+                features.prune(&mut resolve);
+            }
 
-            let packages = collect_packages_from_resolve_graph(&resolve, &patched).await?;
+            let packages =
+                collect_packages_from_resolve_graph(&resolve, &patched, ws.config()).await?;
 
-            Ok(WorkspaceResolve { resolve, packages })
+            Ok(WorkspaceResolve {
+                resolve,
+                packages,
+                features: features.features,
+            })
         }
         .into_future(),
     )
 }
 
+/// Add patches from the workspace manifest to the patch map.
+///
+/// Patches follow the lockfile just like regular dependencies: they keep the locked source, like
+/// a Git revision, while the resolver only prefers the locked version.
+fn insert_workspace_patches(
+    patch_map: &mut PatchMap,
+    ws: &Workspace<'_>,
+    lockfile: &Lockfile,
+) -> Result<()> {
+    for (source_pattern, patch) in ws.patch().iter() {
+        let patch = match lockfile.packages_matching(patch.clone()) {
+            Some(locked_package_id) => patch.with_source_id(locked_package_id?.source_id),
+            None => patch.clone(),
+        };
+        patch_map.insert(source_pattern.clone(), [patch]);
+    }
+    Ok(())
+}
+
+/// Find packages which patches from the workspace manifest have been resolved to.
+///
+/// Emits a warning for each patch not used in the dependency graph.
+fn used_patches(resolve: &Resolve, ws: &Workspace<'_>) -> Vec<PackageId> {
+    let mut used = Vec::new();
+    for (_, patch) in ws.patch().iter() {
+        let package_id = resolve
+            .package_ids()
+            .find(|id| id.name == patch.name && id.source_id.can_lock_source_id(patch.source_id));
+        match package_id {
+            Some(package_id) => used.push(package_id),
+            None => ws.config().ui().warn(format!(
+                "patch `{}` ({}) was not used in the dependency graph",
+                patch.name, patch.source_id
+            )),
+        }
+    }
+    used
+}
+
+/// Build the lockfile guiding resolution when updating dependencies.
+///
+/// Entries of packages to update are removed, so that these packages are resolved from scratch.
+/// If a precise version was requested, the entry is replaced with one pinning that version,
+/// and the expected [`PackageId`] is returned.
+async fn lockfile_for_update(
+    previous: &Lockfile,
+    opts: &ResolveOpts,
+    registry: &dyn Registry,
+) -> Result<(Lockfile, Option<PackageId>)> {
+    if opts.update_packages.is_empty() {
+        ensure!(
+            opts.precise.is_none(),
+            "cannot update to a precise version without specifying a package"
+        );
+        return Ok((Lockfile::default(), None));
+    }
+
+    for package in &opts.update_packages {
+        ensure!(
+            previous.packages().any(|p| p.name == *package),
+            "package `{package}` not found in lockfile"
+        );
+    }
+
+    let mut packages = previous
+        .packages()
+        .filter(|p| !opts.update_packages.contains(&p.name))
+        .cloned()
+        .collect_vec();
+
+    let Some(precise) = &opts.precise else {
+        return Ok((Lockfile::new(packages), None));
+    };
+
+    let Ok(package) = opts.update_packages.iter().exactly_one() else {
+        bail!("cannot update more than one package to a precise version");
+    };
+    let locked = previous.packages().find(|p| p.name == *package).unwrap();
+    let Some(source_id) = locked.source.filter(|s| !s.is_path()) else {
+        bail!("cannot update path dependency `{package}` to a precise version");
+    };
+
+    let (source_id, version_req) = if source_id.is_git() {
+        (
+            source_id.with_precise(precise.clone())?,
+            DependencyVersionReq::Any,
+        )
+    } else {
+        let version = precise
+            .parse()
+            .with_context(|| format!("invalid precise version: {precise}"))?;
+        (source_id, DependencyVersionReq::exact(&version))
+    };
+    let dependency = ManifestDependency::builder()
+        .name(package.clone())
+        .source_id(source_id)
+        .version_req(version_req)
+        .build();
+
+    let Some(summary) = registry.query(&dependency).await?.into_iter().next() else {
+        bail!("cannot find `{package}` in version `{precise}`");
+    };
+    packages.push(
+        PackageLock::builder()
+            .use_package_id(summary.package_id)
+            .checksum(summary.checksum.clone())
+            .build(),
+    );
+
+    Ok((Lockfile::new(packages), Some(summary.package_id)))
+}
+
+/// Print the summary of changes to locked packages, like `Updating foo v0.1.0 -> v0.2.0`.
+fn print_lockfile_changes(previous: &Lockfile, current: &Lockfile, config: &Config) {
+    for change in lockfile_changes(previous, current) {
+        // Path packages are not really locked, their sources are not even stored in lockfiles.
+        if change.package().source.map_or(true, |s| s.is_path()) {
+            continue;
+        }
+        // Only report changes of versions and sources, not of package metadata.
+        if let PackageLockChange::Updated(old, new) = &change {
+            if old.version == new.version && old.source == new.source {
+                continue;
+            }
+        }
+        config
+            .ui()
+            .print(Status::new(change.verb(), &change.to_string()));
+    }
+}
+
 /// Gather [`Package`] instances from this resolver result, by asking the [`RegistryCache`]
 /// to download resolved packages.
 ///
-/// Currently, it is expected that all packages are already downloaded during resolution,
-/// so the `download` calls in this method should be cheap, but this may change the future.
+/// Packages are downloaded concurrently, up to the `net.jobs` limit from the global config.
+/// Packages from sources other than registries are usually already fetched during resolution,
+/// so only downloads of registry packages are reported in the progress bar.
 #[tracing::instrument(level = "trace", skip_all)]
 async fn collect_packages_from_resolve_graph(
     resolve: &Resolve,
     registry: &dyn Registry,
+    config: &Config,
 ) -> Result<HashMap<PackageId, Package>> {
-    let mut packages = HashMap::with_capacity(resolve.package_ids().size_hint().0);
-    // TODO(#6): Parallelize this loop.
-    for package_id in resolve.package_ids() {
-        let package = registry.download(package_id).await?;
-        packages.insert(package_id, package);
-    }
-    Ok(packages)
+    let jobs = config.global_config()?.net.jobs;
+    let progress = DownloadProgress::new(
+        resolve
+            .package_ids()
+            .filter(|id| id.source_id.is_registry())
+            .count(),
+        config,
+    );
+
+    let downloads = stream::iter(resolve.package_ids())
+        .map(|package_id| {
+            let progress = &progress;
+            async move {
+                let package = registry.download(package_id).await?;
+                if package_id.source_id.is_registry() {
+                    progress.package_done();
+                }
+                Ok::<_, anyhow::Error>((package_id, package))
+            }
+        })
+        .buffer_unordered(jobs)
+        .try_collect();
+    progress.scope(downloads).await
 }
 
 #[tracing::instrument(skip_all, level = "debug")]
@@ -185,7 +404,10 @@ fn generate_cairo_compilation_units(
             let packages = solution.packages.as_ref().unwrap();
             let cairo_plugins = solution.cairo_plugins.as_ref().unwrap();
 
-            let cfg_set = build_cfg_set(member_target);
+            let mut cfg_set = build_cfg_set(member_target);
+            for package in packages {
+                add_features(&mut cfg_set, package, resolve.features.get(&package.id));
+            }
 
             let props: TestTargetProps = member_target.props()?;
             let is_integration_test = props.test_type == TestTargetType::Integration;
@@ -358,6 +580,17 @@ fn build_cfg_set(target: &Target) -> CfgSet {
     cfg
 }
 
+/// Add `feature` items for features enabled in the package.
+///
+/// The Cairo compiler applies a single set of `cfg` items to all crates of the compilation unit,
+/// so feature names are qualified with the package name, like `hello/x`. Otherwise, a feature
+/// enabled in one package would also enable code of any other package declaring the same feature.
+fn add_features(cfg_set: &mut CfgSet, package: &Package, features: Option<&BTreeSet<FeatureName>>) {
+    for feature in features.into_iter().flatten() {
+        cfg_set.insert(Cfg::kv("feature", format!("{}/{feature}", package.id.name)));
+    }
+}
+
 fn check_cairo_version_compatibility(packages: &[Package], ws: &Workspace<'_>) -> Result<()> {
     let current_version = crate::version::get().cairo.version.to_version().unwrap();
     let matching_version = packages
@@ -0,0 +1,31 @@
+use anyhow::{ensure, Context, Result};
+use url::Url;
+
+use crate::core::{Config, SearchResult, SourceId};
+use crate::sources::RegistrySource;
+
+pub struct SearchOpts {
+    pub index_url: Url,
+    /// Maximum number of results to return.
+    pub limit: usize,
+}
+
+#[tracing::instrument(level = "debug", skip(opts, config))]
+pub fn search(query: &str, opts: &SearchOpts, config: &Config) -> Result<Vec<SearchResult>> {
+    let source_id = SourceId::for_registry(&opts.index_url)?;
+    let registry_client = RegistrySource::create_client(source_id, config)?;
+
+    let supports_search = config
+        .tokio_handle()
+        .block_on(registry_client.supports_search())
+        .with_context(|| format!("failed to check if registry supports searching: {source_id}"))?;
+    ensure!(
+        supports_search,
+        "searching packages is not supported by registry: {source_id}"
+    );
+
+    config
+        .tokio_handle()
+        .block_on(registry_client.search(query, opts.limit))
+        .with_context(|| format!("failed to search registry: {source_id}"))
+}
@@ -6,6 +6,7 @@ use std::process::Command;
 
 use anyhow::{bail, Result};
 use camino::Utf8PathBuf;
+use itertools::Itertools;
 use tracing::debug;
 
 use scarb_ui::components::Status;
@@ -13,6 +14,7 @@ use scarb_ui::components::Status;
 use crate::core::{Config, Package, ScriptDefinition, Workspace};
 use crate::internal::fsx::is_executable;
 use crate::ops;
+use crate::ops::FeaturesOpts;
 use crate::process::exec_replace;
 use crate::subcommands::{get_env_vars, EXTERNAL_CMD_PREFIX, SCARB_MANIFEST_PATH_ENV};
 
@@ -49,12 +51,24 @@ pub fn execute_test_subcommand(
     package: &Package,
     args: &[OsString],
     ws: &Workspace<'_>,
+    features: &FeaturesOpts,
 ) -> Result<()> {
     let package_name = &package.id.name;
-    let env = Some(HashMap::from_iter([(
+    let mut env = HashMap::from_iter([(
         SCARB_MANIFEST_PATH_ENV.into(),
         package.manifest_path().to_string(),
-    )]));
+    )]);
+    // Pass selected features down to Scarb invocations made by the test runner.
+    if !features.features.is_empty() {
+        env.insert("SCARB_FEATURES".into(), features.features.iter().join(","));
+    }
+    if features.all_features {
+        env.insert("SCARB_ALL_FEATURES".into(), "true".into());
+    }
+    if features.no_default_features {
+        env.insert("SCARB_NO_DEFAULT_FEATURES".into(), "true".into());
+    }
+    let env = Some(env);
     if let Some(script_definition) = package.manifest.scripts.get("test") {
         debug!("using `test` script: {script_definition}");
         ws.config().ui().print(Status::new(
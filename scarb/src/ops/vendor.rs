@@ -0,0 +1,204 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::{Seek, SeekFrom};
+
+use anyhow::{ensure, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use indoc::{indoc, writedoc};
+use itertools::Itertools;
+use scarb_ui::components::Status;
+
+use crate::core::registry::client::local::LocalRegistryClient;
+use crate::core::registry::index::IndexRecord;
+use crate::core::registry::source_replacement::SourceReplacementMap;
+use crate::core::{
+    Checksum, Config, Digest, Package, SourceId, Workspace, DEFAULT_REGISTRY_PATCH_SOURCE,
+};
+use crate::flock::{FileLockGuard, Filesystem};
+use crate::internal::fsx;
+use crate::ops;
+use crate::sources::RegistrySource;
+
+/// Name of the vendored registry in the generated `[source]` config.
+const VENDORED_SOURCE_NAME: &str = "vendored-sources";
+
+pub struct VendorOpts {
+    /// Directory to store the vendored registry in.
+    pub destination: Utf8PathBuf,
+}
+
+/// Copy all registry and Git dependencies of the workspace into a local registry directory.
+///
+/// Registry packages are stored as their original archives, so that their checksums match
+/// the ones stored in the lockfile. Git packages are archived from their checked out sources.
+///
+/// Returns the `[source]` config which replaces original sources with the vendored registry.
+#[tracing::instrument(level = "debug", skip(opts, ws))]
+pub fn vendor(opts: &VendorOpts, ws: &Workspace<'_>) -> Result<String> {
+    let config = ws.config();
+    // Vendor everything that is locked, so that building with any features works offline.
+    let resolve = ops::resolve_workspace_with_opts(
+        ws,
+        &ops::ResolveOpts {
+            keep_inactive_optional: true,
+            ..Default::default()
+        },
+    )?;
+
+    let packages = resolve
+        .packages
+        .values()
+        .filter(|pkg| pkg.id.source_id.is_registry() || pkg.id.source_id.is_git())
+        .sorted_by_key(|pkg| pkg.id)
+        .collect_vec();
+
+    // The vendored registry stores a single archive per package version, so for example two
+    // revisions of a Git repository with the same package version cannot be vendored together.
+    for (a, b) in packages.iter().tuple_windows() {
+        ensure!(
+            (&a.id.name, &a.id.version) != (&b.id.name, &b.id.version),
+            indoc! {"
+                cannot vendor package `{}` version `{}` coming from multiple sources:
+                source 1: {}
+                source 2: {}
+            "},
+            a.id.name,
+            a.id.version,
+            a.id.source_id,
+            b.id.source_id,
+        );
+    }
+
+    fsx::create_dir_all(&opts.destination)?;
+    let destination = fsx::canonicalize_utf8(&opts.destination)?;
+    let vendored = LocalRegistryClient::new(destination.as_std_path(), config)?;
+
+    let replacements = SourceReplacementMap::new(&config.global_config()?.source)?;
+    let mut registries: HashMap<SourceId, RegistrySource<'_>> = HashMap::new();
+    let scratch = ws.target_dir().child("vendor");
+
+    config.tokio_handle().block_on(async {
+        for pkg in &packages {
+            config
+                .ui()
+                .print(Status::new("Vendoring", &pkg.id.to_string()));
+
+            let (tarball, checksum) = if pkg.id.source_id.is_registry() {
+                // Respect replacements, in case the original registry is not reachable.
+                let source_id = replacements
+                    .lookup(pkg.id.source_id)?
+                    .unwrap_or(pkg.id.source_id);
+                let registry = match registries.entry(source_id) {
+                    Entry::Occupied(e) => e.into_mut(),
+                    Entry::Vacant(e) => e.insert(RegistrySource::new(source_id, config)?),
+                };
+                // Resolving the workspace has already downloaded the archive.
+                let id = pkg.id.with_source_id(source_id);
+                match (
+                    registry.cached_archive(id).await?,
+                    pkg.manifest.summary.checksum.clone(),
+                ) {
+                    (Some(archive), Some(checksum)) => (archive, checksum),
+                    _ => registry.download_archive(id).await?,
+                }
+            } else {
+                archive_git_package(pkg, &scratch, config)?
+            };
+
+            let record = index_record(pkg, checksum);
+            vendored
+                .add(pkg.id, record, tarball)
+                .await
+                .with_context(|| format!("failed to vendor package: {}", pkg.id))?;
+        }
+        Ok::<_, anyhow::Error>(())
+    })?;
+
+    // Git sources are replaced regardless of the reference, so list each repository once.
+    let sources = packages
+        .iter()
+        .map(|pkg| pkg.id.source_id)
+        .unique_by(|source_id| {
+            (
+                source_id.kind.primary_field().to_owned(),
+                source_id.canonical_url.clone(),
+            )
+        })
+        .collect_vec();
+    source_replacement_config(&sources, &destination)
+}
+
+fn archive_git_package(
+    pkg: &Package,
+    scratch: &Filesystem,
+    config: &Config,
+) -> Result<(FileLockGuard, Checksum)> {
+    let filename = pkg.id.tarball_name();
+    let mut dst = scratch.open_rw(format!(".{filename}"), "vendor scratch space", config)?;
+    dst.set_len(0)
+        .with_context(|| format!("failed to truncate: {filename}"))?;
+
+    ops::package_sources(pkg, &mut dst, config)
+        .with_context(|| format!("failed to archive package: {}", pkg.id))?;
+
+    dst.seek(SeekFrom::Start(0))?;
+    let checksum = Digest::recommended().update_read(&mut *dst)?.finish();
+    Ok((dst, checksum))
+}
+
+/// Build an index record which keeps the original sources of package dependencies.
+fn index_record(pkg: &Package, checksum: Checksum) -> IndexRecord {
+    let summary = &pkg.manifest.summary;
+    let mut record = IndexRecord::from_summary(summary, checksum);
+    for index_dep in &mut record.dependencies {
+        index_dep.source = summary
+            .publish_dependencies()
+            .find(|dep| dep.name == index_dep.name)
+            .map(|dep| dep.source_id);
+    }
+    record
+}
+
+fn source_replacement_config(sources: &[SourceId], destination: &Utf8Path) -> Result<String> {
+    let quote = |s: &str| toml::Value::String(s.to_owned()).to_string();
+    let replace_with = quote(VENDORED_SOURCE_NAME);
+
+    let mut config = String::new();
+    for source_id in sources {
+        if source_id.is_default_registry() {
+            writedoc!(
+                config,
+                r#"
+                [source.{name}]
+                replace-with = {replace_with}
+
+                "#,
+                name = quote(DEFAULT_REGISTRY_PATCH_SOURCE),
+            )?;
+        } else {
+            writedoc!(
+                config,
+                r#"
+                [source.{name}]
+                {kind} = {url}
+                replace-with = {replace_with}
+
+                "#,
+                name = quote(&source_id.ident()),
+                kind = source_id.kind.primary_field(),
+                url = quote(source_id.url.as_str()),
+            )?;
+        }
+    }
+    writedoc!(
+        config,
+        r#"
+        [source.{name}]
+        local-registry = {path}
+        "#,
+        name = quote(VENDORED_SOURCE_NAME),
+        path = quote(destination.as_str()),
+    )?;
+    Ok(config)
+}
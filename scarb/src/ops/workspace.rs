@@ -10,6 +10,7 @@ use tracing::trace;
 
 use crate::core::config::Config;
 use crate::core::package::Package;
+use crate::core::registry::named_registries::NamedRegistries;
 use crate::core::source::SourceId;
 use crate::core::workspace::Workspace;
 use crate::core::TomlManifest;
@@ -77,6 +78,11 @@ fn read_workspace_root<'c>(
     let toml_manifest = TomlManifest::read_from_path(manifest_path)?;
     let toml_workspace = toml_manifest.get_workspace();
     let profiles = toml_manifest.collect_profiles()?;
+    let registries = collect_registries(&toml_manifest, config)
+        .with_context(|| format!("failed to parse manifest at: {manifest_path}"))?;
+    let patch = toml_manifest
+        .collect_patch(manifest_path, &registries)
+        .with_context(|| format!("failed to parse manifest at: {manifest_path}"))?;
 
     let root_package = if toml_manifest.is_package() {
         let manifest = toml_manifest
@@ -86,6 +92,7 @@ fn read_workspace_root<'c>(
                 source_id,
                 config.profile(),
                 Some(&toml_manifest),
+                &registries,
             )
             .with_context(|| format!("failed to parse manifest at: {manifest_path}"))?;
         let manifest = Box::new(manifest);
@@ -110,6 +117,20 @@ fn read_workspace_root<'c>(
             .map(AsRef::as_ref)
             .map(|package_path| {
                 let package_manifest = TomlManifest::read_from_path(package_path)?;
+                if package_manifest.patch.is_some() {
+                    config.ui().warn(formatdoc! {"
+                        patch for the non root package will be ignored, specify patch at the workspace root:
+                        package:   {package_path}
+                        workspace: {manifest_path}"
+                    });
+                }
+                if package_manifest.registries.is_some() {
+                    config.ui().warn(formatdoc! {"
+                        registries for the non root package will be ignored, specify registries at the workspace root:
+                        package:   {package_path}
+                        workspace: {manifest_path}"
+                    });
+                }
                 // Read the member package.
                 let manifest = package_manifest
                     .to_manifest(
@@ -118,6 +139,7 @@ fn read_workspace_root<'c>(
                         source_id,
                         config.profile(),
                         Some(&toml_manifest),
+                        &registries,
                     )
                     .with_context(|| format!("failed to parse manifest at: {manifest_path}"))?;
                 let manifest = Box::new(manifest);
@@ -137,12 +159,24 @@ fn read_workspace_root<'c>(
             root_package,
             config,
             profiles,
+            patch,
+            registries,
         )
     } else {
         // Read single package workspace
         let package = root_package.ok_or_else(|| anyhow!("the [package] section is missing"))?;
-        Workspace::from_single_package(package, config, profiles)
+        Workspace::from_single_package(package, config, profiles, patch, registries)
+    }
+}
+
+/// Collect registries defined in the global config, overridden by ones from the workspace manifest.
+fn collect_registries(toml_manifest: &TomlManifest, config: &Config) -> Result<NamedRegistries> {
+    let mut registries = NamedRegistries::new();
+    registries.extend(&config.global_config()?.registries)?;
+    if let Some(workspace_registries) = &toml_manifest.registries {
+        registries.extend(workspace_registries)?;
     }
+    Ok(registries)
 }
 
 fn find_member_paths(
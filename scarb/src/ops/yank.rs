@@ -0,0 +1,45 @@
+use anyhow::{ensure, Context, Result};
+use semver::Version;
+use url::Url;
+
+use scarb_ui::components::Status;
+
+use crate::core::{Config, PackageId, PackageName, SourceId};
+use crate::sources::RegistrySource;
+
+pub struct YankOpts {
+    pub index_url: Url,
+    /// Undo a previous yank, instead of yanking.
+    pub undo: bool,
+}
+
+#[tracing::instrument(level = "debug", skip(opts, config))]
+pub fn yank(name: PackageName, version: Version, opts: &YankOpts, config: &Config) -> Result<()> {
+    let source_id = SourceId::for_registry(&opts.index_url)?;
+    let registry_client = RegistrySource::create_client(source_id, config)?;
+
+    let supports_yank = config
+        .tokio_handle()
+        .block_on(registry_client.supports_yank())
+        .with_context(|| format!("failed to check if registry supports yanking: {source_id}"))?;
+    ensure!(
+        supports_yank,
+        "yanking packages is not supported by registry: {source_id}"
+    );
+
+    let package_id = PackageId::new(name, version, source_id);
+
+    let (status, verb) = if opts.undo {
+        ("Unyanking", "unyank")
+    } else {
+        ("Yanking", "yank")
+    };
+    config
+        .ui()
+        .print(Status::new(status, &package_id.to_string()));
+
+    config
+        .tokio_handle()
+        .block_on(registry_client.yank(package_id, !opts.undo))
+        .with_context(|| format!("failed to {verb} package: {package_id}"))
+}
@@ -0,0 +1,240 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use anyhow::{bail, ensure, Result};
+use itertools::Itertools;
+
+use crate::core::resolver::Resolve;
+use crate::core::{DepKind, FeatureName, FeatureValue, ManifestDependency, PackageId, Summary};
+
+/// Features requested for workspace members, usually from the command line.
+#[derive(Clone, Debug, Default)]
+pub struct FeaturesOpts {
+    /// Features to enable in each workspace member which declares them.
+    pub features: Vec<FeatureName>,
+    /// Enable all features of all workspace members.
+    pub all_features: bool,
+    /// Do not enable the `default` feature of workspace members.
+    pub no_default_features: bool,
+}
+
+/// Result of feature resolution over a [`Resolve`] graph.
+#[derive(Debug, Default)]
+pub struct ResolvedFeatures {
+    /// Features enabled in each package.
+    ///
+    /// Features are unified across the whole graph, so each package has a single set of enabled
+    /// features, being the union of features requested by all its dependents.
+    /// Only packages which are activated have entries in this map.
+    pub features: HashMap<PackageId, BTreeSet<FeatureName>>,
+    /// Edges of the resolve graph which are activated, that is all edges, except ones
+    /// originating from optional dependencies not enabled by any feature.
+    pub dependencies: HashSet<(PackageId, PackageId)>,
+}
+
+impl ResolvedFeatures {
+    /// Remove packages and dependency edges which have not been activated from the graph.
+    pub fn prune(&self, resolve: &mut Resolve) {
+        let edges = resolve
+            .graph
+            .all_edges()
+            .map(|(a, b, _)| (a, b))
+            .filter(|edge| !self.dependencies.contains(edge))
+            .collect_vec();
+        for (a, b) in edges {
+            resolve.graph.remove_edge(a, b);
+        }
+
+        let nodes = resolve
+            .graph
+            .nodes()
+            .filter(|id| !self.features.contains_key(id))
+            .collect_vec();
+        for id in nodes {
+            resolve.graph.remove_node(id);
+            resolve.summaries.remove(&id);
+        }
+    }
+}
+
+/// Compute features enabled in each package of the resolve graph, starting from workspace members.
+///
+/// Dependencies of an activated package are activated as well, unless they are optional.
+/// Optional dependencies are activated when a feature of the same name, or a feature of this
+/// dependency (`dependency/feature`) is enabled in the dependent package.
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn resolve_features(
+    resolve: &Resolve,
+    members: &[PackageId],
+    opts: &FeaturesOpts,
+) -> Result<ResolvedFeatures> {
+    let mut resolver = FeatureResolver {
+        resolve,
+        resolved: ResolvedFeatures::default(),
+    };
+
+    for feature in &opts.features {
+        ensure!(
+            members
+                .iter()
+                .any(|id| has_feature(&resolve.summaries[id], feature)),
+            "no workspace member has feature `{feature}`"
+        );
+    }
+
+    for &member in members {
+        let summary = &resolve.summaries[&member];
+        let features = if opts.all_features {
+            all_features(summary).collect_vec()
+        } else {
+            opts.features
+                .iter()
+                .filter(|feature| has_feature(summary, feature))
+                .cloned()
+                .collect_vec()
+        };
+        resolver.activate_package(member, features, !opts.no_default_features)?;
+    }
+
+    Ok(resolver.resolved)
+}
+
+struct FeatureResolver<'a> {
+    resolve: &'a Resolve,
+    resolved: ResolvedFeatures,
+}
+
+impl<'a> FeatureResolver<'a> {
+    fn summary(&self, package_id: PackageId) -> &'a Summary {
+        &self.resolve.summaries[&package_id]
+    }
+
+    /// Find the package which the dependency has been resolved to.
+    ///
+    /// Returns `None` for dependencies which are not part of the graph, like development
+    /// dependencies of non-member packages.
+    fn resolved_dependency(
+        &self,
+        package_id: PackageId,
+        dependency: &ManifestDependency,
+    ) -> Option<PackageId> {
+        self.resolve
+            .package_dependencies(package_id)
+            .find(|id| id.name == dependency.name)
+    }
+
+    fn activate_package(
+        &mut self,
+        package_id: PackageId,
+        features: impl IntoIterator<Item = FeatureName>,
+        default_features: bool,
+    ) -> Result<()> {
+        let summary = self.summary(package_id);
+
+        if !self.resolved.features.contains_key(&package_id) {
+            self.resolved.features.insert(package_id, BTreeSet::new());
+            for dependency in summary.full_dependencies().filter(|dep| !dep.optional) {
+                self.activate_dependency(package_id, dependency)?;
+            }
+        }
+
+        if default_features && summary.features.contains_key(&FeatureName::DEFAULT) {
+            self.enable_feature(package_id, &FeatureName::DEFAULT)?;
+        }
+
+        for feature in features {
+            self.enable_feature(package_id, &feature)?;
+        }
+
+        Ok(())
+    }
+
+    fn activate_dependency(
+        &mut self,
+        package_id: PackageId,
+        dependency: &ManifestDependency,
+    ) -> Result<()> {
+        let Some(dependency_id) = self.resolved_dependency(package_id, dependency) else {
+            return Ok(());
+        };
+        self.resolved
+            .dependencies
+            .insert((package_id, dependency_id));
+        self.activate_package(
+            dependency_id,
+            dependency.features.iter().cloned(),
+            dependency.default_features,
+        )
+    }
+
+    fn enable_feature(&mut self, package_id: PackageId, feature: &FeatureName) -> Result<()> {
+        let enabled = self.resolved.features.entry(package_id).or_default();
+        if !enabled.insert(feature.clone()) {
+            return Ok(());
+        }
+
+        let summary = self.summary(package_id);
+        if let Some(values) = summary.features.get(feature) {
+            for value in values {
+                self.enable_value(package_id, value)?;
+            }
+        } else if let Some(dependency) = optional_dependency(summary, feature) {
+            self.activate_dependency(package_id, dependency)?;
+        } else {
+            bail!("package `{package_id}` does not have feature `{feature}`");
+        }
+
+        Ok(())
+    }
+
+    fn enable_value(&mut self, package_id: PackageId, value: &FeatureValue) -> Result<()> {
+        match value {
+            FeatureValue::Feature(feature) => self.enable_feature(package_id, feature),
+            FeatureValue::DependencyFeature {
+                dependency,
+                feature,
+            } => {
+                let summary = self.summary(package_id);
+                let dependencies = summary
+                    .dependencies
+                    .iter()
+                    .filter(|dep| dep.name == *dependency && dep.kind == DepKind::Normal);
+                for dependency in dependencies {
+                    self.activate_dependency(package_id, dependency)?;
+                    if let Some(dependency_id) = self.resolved_dependency(package_id, dependency) {
+                        self.enable_feature(dependency_id, feature)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Find an optional dependency which can be activated by enabling a feature of the same name.
+fn optional_dependency<'a>(
+    summary: &'a Summary,
+    feature: &FeatureName,
+) -> Option<&'a ManifestDependency> {
+    summary
+        .dependencies
+        .iter()
+        .find(|dep| dep.optional && dep.name.as_str() == feature.as_str())
+}
+
+fn has_feature(summary: &Summary, feature: &FeatureName) -> bool {
+    summary.features.contains_key(feature) || optional_dependency(summary, feature).is_some()
+}
+
+/// All features declared by a package, including ones implied by optional dependencies.
+fn all_features(summary: &Summary) -> impl Iterator<Item = FeatureName> + '_ {
+    let optional_dependencies = summary
+        .dependencies
+        .iter()
+        .filter(|dep| dep.optional)
+        .map(|dep| FeatureName::new(&dep.name));
+    summary
+        .features
+        .keys()
+        .cloned()
+        .chain(optional_dependencies)
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use crate::core::{ManifestDependency, PackageId, PackageName};
+use crate::resolver::term::{Term, TermRelation, VersionSet};
+
+/// Index of an [`Incompatibility`] in solver's incompatibility store.
+pub type IncompatibilityId = usize;
+
+/// A set of terms which must not all be true at the same time.
+///
+/// The solver operates by accumulating incompatibilities, until either a set of package versions
+/// which does not satisfy any of them is found, or an incompatibility stating that the root
+/// packages themselves cannot be selected is derived.
+#[derive(Clone, Debug)]
+pub struct Incompatibility {
+    terms: BTreeMap<PackageName, Term>,
+    pub kind: IncompatibilityKind,
+}
+
+/// The reason why an [`Incompatibility`] exists.
+#[derive(Clone, Debug)]
+pub enum IncompatibilityKind {
+    /// A root package (workspace member) must be selected in its exact version.
+    Root,
+    /// A package depends on another one.
+    ///
+    /// If the dependency does not match any versions of the depended upon package,
+    /// the incompatibility forbids selecting the dependent package at all.
+    Dependency(PackageId, ManifestDependency),
+    /// None of the remaining candidate versions of a package are available.
+    NoVersions(PackageName),
+    /// Derived during conflict resolution from two other incompatibilities.
+    Derived(IncompatibilityId, IncompatibilityId),
+}
+
+/// Relation of an [`Incompatibility`] to a partial solution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Relation {
+    /// All terms are satisfied, the partial solution contains a conflict.
+    Satisfied,
+    /// At least one term is contradicted, the incompatibility can be ignored.
+    Contradicted,
+    /// All terms but the one for the given package are satisfied, so the solution must
+    /// contain the negation of that term.
+    AlmostSatisfied(PackageName),
+    Inconclusive,
+}
+
+impl Incompatibility {
+    pub fn root(package_id: PackageId) -> Self {
+        let terms = BTreeMap::from([(
+            package_id.name.clone(),
+            Term::Negative(VersionSet::singleton(package_id.version.clone())),
+        )]);
+        Self {
+            terms,
+            kind: IncompatibilityKind::Root,
+        }
+    }
+
+    /// Create an incompatibility stating that `package_id` depends on `dependency`, which is
+    /// satisfied only by versions in the `candidates` set.
+    pub fn dependency(
+        package_id: PackageId,
+        dependency: ManifestDependency,
+        candidates: VersionSet,
+    ) -> Self {
+        assert_ne!(package_id.name, dependency.name);
+        let mut terms = BTreeMap::from([(
+            package_id.name.clone(),
+            Term::exact(package_id.version.clone()),
+        )]);
+        // A negative term of an empty set is always satisfied, so it can be omitted.
+        if !candidates.is_empty() {
+            terms.insert(dependency.name.clone(), Term::Negative(candidates));
+        }
+        Self {
+            terms,
+            kind: IncompatibilityKind::Dependency(package_id, dependency),
+        }
+    }
+
+    pub fn no_versions(package: PackageName, versions: VersionSet) -> Self {
+        let terms = BTreeMap::from([(package.clone(), Term::Positive(versions))]);
+        Self {
+            terms,
+            kind: IncompatibilityKind::NoVersions(package),
+        }
+    }
+
+    /// Derive a new incompatibility by resolving `incompat` with the `cause` of the assignment
+    /// that satisfied `incompat`'s term for `package`.
+    ///
+    /// The result is satisfied whenever both inputs are satisfied, and it does not depend on
+    /// the assignment in question.
+    pub fn prior_cause(
+        incompat_id: IncompatibilityId,
+        incompat: &Self,
+        cause_id: IncompatibilityId,
+        cause: &Self,
+        package: &PackageName,
+    ) -> Self {
+        let mut terms = incompat.terms.clone();
+        let incompat_term = terms
+            .remove(package)
+            .expect("package must be present in incompatibility");
+        let cause_term = cause
+            .get(package)
+            .expect("package must be present in satisfier cause");
+
+        for (other_package, other_term) in &cause.terms {
+            if other_package == package {
+                continue;
+            }
+            terms
+                .entry(other_package.clone())
+                .and_modify(|term| *term = term.intersection(other_term))
+                .or_insert_with(|| other_term.clone());
+        }
+
+        let term = incompat_term.union(cause_term);
+        if term != Term::any() {
+            terms.insert(package.clone(), term);
+        }
+
+        Self {
+            terms,
+            kind: IncompatibilityKind::Derived(incompat_id, cause_id),
+        }
+    }
+
+    pub fn get(&self, package: &PackageName) -> Option<&Term> {
+        self.terms.get(package)
+    }
+
+    pub fn terms(&self) -> impl Iterator<Item = (&PackageName, &Term)> {
+        self.terms.iter()
+    }
+
+    pub fn packages(&self) -> impl Iterator<Item = &PackageName> {
+        self.terms.keys()
+    }
+
+    /// Check whether this incompatibility proves that no solution exists.
+    ///
+    /// This is the case when it has no terms left, or when it only forbids selecting root
+    /// packages, which are always selected.
+    pub fn is_terminal(&self, is_root: impl Fn(&PackageName) -> bool) -> bool {
+        self.terms
+            .iter()
+            .all(|(package, term)| term.is_positive() && is_root(package))
+    }
+
+    /// Check how this incompatibility relates to a partial solution, which is described by
+    /// a function returning intersections of all terms known about a package.
+    pub fn relation<'a>(
+        &self,
+        intersection_of: impl Fn(&PackageName) -> Option<&'a Term>,
+    ) -> Relation {
+        let any = Term::any();
+        let mut relation = Relation::Satisfied;
+        for (package, term) in &self.terms {
+            match term.relation_with(intersection_of(package).unwrap_or(&any)) {
+                TermRelation::Satisfied => {}
+                TermRelation::Contradicted => return Relation::Contradicted,
+                TermRelation::Inconclusive if relation == Relation::Satisfied => {
+                    relation = Relation::AlmostSatisfied(package.clone());
+                }
+                TermRelation::Inconclusive => return Relation::Inconclusive,
+            }
+        }
+        relation
+    }
+}
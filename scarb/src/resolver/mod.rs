@@ -1,16 +1,34 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use anyhow::{bail, Result};
-use indoc::{formatdoc, indoc};
+use futures::future;
+use indoc::indoc;
+use itertools::Itertools;
 use petgraph::graphmap::DiGraphMap;
 use scarb_ui::Ui;
+use semver::Version;
 
 use crate::core::lockfile::Lockfile;
 use crate::core::registry::Registry;
 use crate::core::resolver::{DependencyEdge, Resolve};
 use crate::core::{
-    DepKind, DependencyVersionReq, ManifestDependency, PackageId, Summary, TargetKind,
+    DepKind, ManifestDependency, PackageId, PackageName, SourceId, Summary, TargetKind,
 };
+use crate::resolver::incompatibility::{
+    Incompatibility, IncompatibilityId, IncompatibilityKind, Relation,
+};
+use crate::resolver::report::FailureReport;
+use crate::resolver::state::State;
+use crate::resolver::term::{Term, VersionSet};
+
+pub use features::*;
+
+mod features;
+mod incompatibility;
+mod partial_solution;
+mod report;
+mod state;
+mod term;
 
 /// Builds the list of all packages required to build the first argument.
 ///
@@ -30,51 +48,313 @@ use crate::core::{
 ///     lockfile will result in no guidance. This function does not read or write lock files from
 ///     the filesystem.
 ///
+/// * `pinned` - names of packages which must stay in versions locked in `lockfile`, for example
+///     when only some packages are updated. Versions of other locked packages are only preferred,
+///     and can be changed if requirements cannot be satisfied otherwise.
+///
 /// * `ui` - an [`Ui`] instance used to show warnings to the user.
+///
+/// Optional dependencies are resolved just like regular ones, regardless of enabled features,
+/// so that the resolve and the lock file do not depend on features, like in Cargo.
+/// Thus, requirements of optional dependencies must be satisfiable even if they are never
+/// activated. Inactive optional dependencies are pruned later, see [`resolve_features`].
 #[tracing::instrument(level = "trace", skip_all)]
 pub async fn resolve(
     summaries: &[Summary],
     registry: &dyn Registry,
     lockfile: Lockfile,
+    pinned: &BTreeSet<PackageName>,
     ui: Ui,
 ) -> Result<Resolve> {
-    // TODO(#2): This is very bad, use PubGrub here.
-    let mut graph = DiGraphMap::<PackageId, DependencyEdge>::new();
+    let mut solver = Solver::new(summaries, registry, &lockfile, pinned)?;
 
-    let mut packages: HashMap<_, _> = HashMap::from_iter(
-        summaries
-            .iter()
-            .map(|s| (s.package_id.name.clone(), s.package_id)),
-    );
-
-    let mut summaries: HashMap<_, _> = summaries
+    let mut next = summaries
         .iter()
-        .map(|s| (s.package_id, s.clone()))
-        .collect();
+        .map(|s| s.package_id.name.clone())
+        .collect_vec();
+    loop {
+        for package in next.drain(..) {
+            if let Err(root_cause) = solver.state.unit_propagation(package) {
+                bail!(FailureReport::new(&solver, root_cause).to_string());
+            }
+        }
+
+        match solver.choose_package_version().await? {
+            Some(package) => next.push(package),
+            None => break,
+        }
+    }
+
+    let resolve = solver.into_resolve(ui);
+    resolve.check_checksums(&lockfile)?;
+    Ok(resolve)
+}
+
+/// Drives the version solver [`State`], by choosing package versions to try and feeding it
+/// with dependency information obtained from the [`Registry`].
+struct Solver<'a> {
+    registry: &'a dyn Registry,
+    lockfile: &'a Lockfile,
+    /// Packages which can only be used in their locked versions.
+    pinned: &'a BTreeSet<PackageName>,
+    /// Workspace members, which are the roots of the dependency graph.
+    members: Vec<PackageId>,
+    state: State,
+    /// All package versions returned by registry queries so far.
+    candidates: HashMap<PackageName, BTreeMap<Version, Summary>>,
+    /// The source each package is coming from, all dependencies must agree on it.
+    sources: HashMap<PackageName, SourceId>,
+    /// The order in which packages have been discovered, used to decide on packages in the order
+    /// their dependencies are declared.
+    discovered: HashMap<PackageName, usize>,
+    /// Incompatibilities created from dependencies of package versions tried so far.
+    dependencies: HashMap<PackageId, Vec<IncompatibilityId>>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(
+        summaries: &[Summary],
+        registry: &'a dyn Registry,
+        lockfile: &'a Lockfile,
+        pinned: &'a BTreeSet<PackageName>,
+    ) -> Result<Self> {
+        let mut solver = Self {
+            registry,
+            lockfile,
+            pinned,
+            members: summaries.iter().map(|s| s.package_id).collect(),
+            state: State::new(summaries.iter().map(|s| s.package_id)),
+            candidates: HashMap::new(),
+            sources: HashMap::new(),
+            discovered: HashMap::new(),
+            dependencies: HashMap::new(),
+        };
+        solver.add_candidates(summaries)?;
+        Ok(solver)
+    }
+
+    fn add_candidates(&mut self, summaries: &[Summary]) -> Result<()> {
+        for summary in summaries {
+            let package_id = summary.package_id;
+
+            let next = self.discovered.len();
+            self.discovered
+                .entry(package_id.name.clone())
+                .or_insert(next);
+
+            let source_id = *self
+                .sources
+                .entry(package_id.name.clone())
+                .or_insert(package_id.source_id);
+            if source_id != package_id.source_id {
+                bail!(
+                    indoc! {"
+                    found dependencies on the same package `{}` coming from incompatible \
+                    sources:
+                    source 1: {}
+                    source 2: {}
+                    "},
+                    package_id.name,
+                    source_id,
+                    package_id.source_id
+                );
+            }
 
-    let mut queue: Vec<PackageId> = summaries.keys().copied().collect();
-    while !queue.is_empty() {
-        let mut next_queue = Vec::new();
+            self.candidates
+                .entry(package_id.name.clone())
+                .or_default()
+                .entry(package_id.version.clone())
+                .or_insert_with(|| summary.clone());
+        }
+        Ok(())
+    }
 
-        for package_id in queue {
-            graph.add_node(package_id);
+    /// Candidates of the package, which belong to the `allowed` set.
+    fn allowed_candidates<'s>(
+        &'s self,
+        package: &PackageName,
+        allowed: &'s VersionSet,
+    ) -> impl Iterator<Item = &'s Summary> {
+        self.candidates
+            .get(package)
+            .into_iter()
+            .flat_map(|versions| versions.iter())
+            .filter(|(version, _)| allowed.contains(version))
+            .map(|(_, summary)| summary)
+    }
 
-            for dep in summaries[&package_id].clone().full_dependencies() {
-                let dep = rewrite_dependency_source_id(registry, &package_id, dep).await?;
+    /// Whether the package is locked in the lockfile guiding this resolution.
+    fn is_locked(&self, package_id: PackageId) -> bool {
+        self.lockfile.packages().any(|p| {
+            p.name == package_id.name
+                && p.version == package_id.version
+                && p.source
+                    .map_or(false, |sid| sid.can_lock_source_id(package_id.source_id))
+        })
+    }
 
-                let locked_package_id = lockfile.packages_matching(dep.clone());
-                let dep = if let Some(locked_package_id) = locked_package_id {
-                    rewrite_locked_dependency(dep.clone(), locked_package_id?)
+    /// Whether the package is pinned to another version locked in the lockfile.
+    fn is_pinned_to_other_version(&self, package_id: PackageId) -> bool {
+        self.pinned.contains(&package_id.name)
+            && !self.is_locked(package_id)
+            && self.lockfile.packages().any(|p| {
+                p.name == package_id.name
+                    && p.source
+                        .map_or(false, |sid| sid.can_lock_source_id(package_id.source_id))
+            })
+    }
+
+    /// Pick the next package to decide on, and try deciding on its best allowed version.
+    ///
+    /// Returns `None` if all required packages are decided, i.e. the solution is complete.
+    async fn choose_package_version(&mut self) -> Result<Option<PackageName>> {
+        // Decide on the most constrained packages first, as they are the most likely to
+        // cause conflicts, which is better to learn about early. Equally constrained packages
+        // are decided on in the order they have been discovered.
+        let Some((package, allowed)) = self
+            .state
+            .partial_solution
+            .undecided_packages()
+            .min_by_key(|(package, allowed)| {
+                let count = self.allowed_candidates(package, allowed).count();
+                let discovered = self.discovered.get(*package).copied();
+                (count, discovered.unwrap_or(usize::MAX), (*package).clone())
+            })
+            .map(|(package, allowed)| (package.clone(), allowed.clone()))
+        else {
+            return Ok(None);
+        };
+
+        // Prefer the locked version, so that resolution is stable across runs. Otherwise,
+        // prefer stable releases over pre-releases, and newer versions over older ones.
+        let Some(summary) = self
+            .allowed_candidates(&package, &allowed)
+            .max_by_key(|s| {
+                let version = s.package_id.version.clone();
+                (
+                    self.is_locked(s.package_id),
+                    version.pre.is_empty(),
+                    version,
+                )
+            })
+            .cloned()
+        else {
+            self.state
+                .add_incompatibility(Incompatibility::no_versions(package.clone(), allowed));
+            return Ok(Some(package));
+        };
+
+        let version = summary.package_id.version.clone();
+        let dependencies = self.add_dependencies(&summary).await?;
+
+        // Do not decide on this version, if any of its dependencies are already known
+        // to conflict with the partial solution. Unit propagation will take care of it.
+        let exact = Term::exact(version.clone());
+        let conflicts = dependencies.iter().any(|&id| {
+            let relation = self.state.incompatibility(id).relation(|p| {
+                if *p == package {
+                    Some(&exact)
                 } else {
-                    dep
-                };
+                    self.state.partial_solution.term_intersection(p)
+                }
+            });
+            relation == Relation::Satisfied
+        });
+        if !conflicts {
+            self.state
+                .partial_solution
+                .add_decision(package.clone(), version);
+        }
 
-                let results = registry.query(&dep).await?;
+        Ok(Some(package))
+    }
+
+    /// Query the registry for dependencies of the package, and add them to the solver state
+    /// as incompatibilities.
+    async fn add_dependencies(&mut self, summary: &Summary) -> Result<Vec<IncompatibilityId>> {
+        let package_id = summary.package_id;
+        if let Some(ids) = self.dependencies.get(&package_id) {
+            return Ok(ids.clone());
+        }
+
+        let mut deps = Vec::new();
+        for dep in summary.full_dependencies() {
+            let dep = rewrite_dependency_source_id(self.registry, &package_id, dep).await?;
+
+            // Locked versions are only preferred when choosing package versions, but the source
+            // must stay the same, e.g. to keep using the locked revision of a Git repository.
+            let dep = match self.lockfile.packages_matching(dep.clone()) {
+                Some(locked_package_id) => dep.with_source_id(locked_package_id?.source_id),
+                None => dep,
+            };
+            deps.push(dep);
+        }
 
-                let Some(dep_summary) = results.first() else {
-                    bail!("cannot find package {}", dep.name)
+        // Fetch index files of registry dependencies concurrently. Queries are cached by the
+        // registry, so the loop below gets the results (or errors) without waiting again.
+        // Other sources are queried one by one, because they may report progress to the user.
+        let registry = self.registry;
+        future::join_all(
+            deps.iter()
+                .filter(|dep| dep.source_id.is_registry())
+                .map(|dep| registry.query(dep)),
+        )
+        .await;
+
+        let mut ids = Vec::new();
+        for dep in deps {
+            let mut results = self.registry.query(&dep).await?;
+            // Yanked versions can only be used if they are already locked.
+            results.retain(|summary| !summary.yanked || self.is_locked(summary.package_id));
+            results.retain(|summary| !self.is_pinned_to_other_version(summary.package_id));
+            self.add_candidates(&results)?;
+
+            if dep.name == package_id.name {
+                continue;
+            }
+
+            let versions = results
+                .iter()
+                .map(|s| s.package_id.version.clone())
+                .collect();
+            let incompat = Incompatibility::dependency(package_id, dep, versions);
+            ids.push(self.state.add_incompatibility(incompat));
+        }
+
+        self.dependencies.insert(package_id, ids.clone());
+        Ok(ids)
+    }
+
+    /// Build the [`Resolve`] graph from decisions made by the solver.
+    fn into_resolve(self, ui: Ui) -> Resolve {
+        let selected: HashMap<PackageName, &Summary> = self
+            .state
+            .partial_solution
+            .decisions()
+            .map(|(package, version)| (package.clone(), &self.candidates[package][version]))
+            .collect();
+
+        let mut graph = DiGraphMap::<PackageId, DependencyEdge>::new();
+        for summary in selected.values() {
+            graph.add_node(summary.package_id);
+        }
+
+        let yanked = selected.values().filter(|s| s.yanked).map(|s| s.package_id);
+        for package_id in yanked.sorted() {
+            ui.warn(format!(
+                "package `{package_id}` is yanked, but it is used because it is locked in Scarb.lock"
+            ));
+        }
+
+        for summary in selected.values() {
+            let package_id = summary.package_id;
+            for &id in &self.dependencies[&package_id] {
+                let IncompatibilityKind::Dependency(_, dep) = &self.state.incompatibility(id).kind
+                else {
+                    unreachable!("expected dependency incompatibility");
                 };
 
+                let dep_summary = selected[&dep.name];
                 let dep_target_kind: Option<TargetKind> = match dep.kind.clone() {
                     DepKind::Normal => None,
                     DepKind::Target(target_kind) => Some(target_kind),
@@ -90,88 +370,22 @@ pub async fn resolve(
                     ));
                 }
 
-                if let Some(existing) = packages.get(dep.name.as_ref()) {
-                    if existing.source_id != dep.source_id {
-                        bail!(
-                            indoc! {"
-                            found dependencies on the same package `{}` coming from incompatible \
-                            sources:
-                            source 1: {}
-                            source 2: {}
-                            "},
-                            dep.name,
-                            existing.source_id,
-                            dep.source_id
-                        );
-                    }
-                }
-
                 let weight = graph
                     .edge_weight(package_id, dep)
                     .cloned()
                     .unwrap_or_default();
                 let weight = weight.extend(dep_target_kind);
                 graph.add_edge(package_id, dep, weight);
-                summaries.insert(dep, dep_summary.clone());
-
-                if packages.contains_key(dep.name.as_ref()) {
-                    continue;
-                }
-
-                packages.insert(dep.name.clone(), dep);
-                next_queue.push(dep);
-            }
-        }
-
-        queue = next_queue;
-    }
-
-    // Detect incompatibilities and bail in case ones are found.
-    let mut incompatibilities = Vec::new();
-    for from_package in graph.nodes() {
-        for manifest_dependency in summaries[&from_package].full_dependencies() {
-            let to_package = packages[&manifest_dependency.name];
-            if !manifest_dependency.matches_package_id(to_package) {
-                let message = format!(
-                    "- {from_package} cannot use {to_package}, because {} requires {} {}",
-                    from_package.name, to_package.name, manifest_dependency.version_req
-                );
-                incompatibilities.push(message);
             }
         }
-    }
 
-    if !incompatibilities.is_empty() {
-        incompatibilities.sort();
-        let incompatibilities = incompatibilities.join("\n");
-        bail!(formatdoc! {"
-            Version solving failed:
-            {incompatibilities}
+        let summaries = selected
+            .into_values()
+            .map(|s| (s.package_id, s.clone()))
+            .collect();
 
-            Scarb does not have real version solving algorithm yet.
-            Perhaps in the future this conflict could be resolved, but currently,
-            please upgrade your dependencies to use latest versions of their dependencies.
-        "});
+        Resolve { graph, summaries }
     }
-
-    let resolve = Resolve { graph, summaries };
-    resolve.check_checksums(&lockfile)?;
-    Ok(resolve)
-}
-
-fn rewrite_locked_dependency(
-    dependency: ManifestDependency,
-    locked_package_id: PackageId,
-) -> ManifestDependency {
-    ManifestDependency::builder()
-        .kind(dependency.kind.clone())
-        .name(dependency.name.clone())
-        .source_id(locked_package_id.source_id)
-        .version_req(DependencyVersionReq::Locked {
-            exact: locked_package_id.version.clone(),
-            req: dependency.version_req.clone().into(),
-        })
-        .build()
 }
 
 async fn rewrite_dependency_source_id(
@@ -202,6 +416,8 @@ async fn rewrite_dependency_source_id(
 mod tests {
     //! These tests largely come from Elixir's `hex_solver` test suite.
 
+    use std::collections::BTreeSet;
+
     use anyhow::Result;
     use indoc::indoc;
     use itertools::Itertools;
@@ -291,7 +507,13 @@ mod tests {
 
         let lockfile = Lockfile::new(locks.iter().cloned());
         let ui = Ui::new(Verbose, OutputFormat::Text);
-        runtime.block_on(super::resolve(&summaries, &registry, lockfile, ui))
+        runtime.block_on(super::resolve(
+            &summaries,
+            &registry,
+            lockfile,
+            &BTreeSet::new(),
+            ui,
+        ))
     }
 
     fn package_id<S: AsRef<str>>(name: S) -> PackageId {
@@ -418,20 +640,7 @@ mod tests {
                 ("baz v1.0.0", []),
             ],
             &[deps![("foo", "*")]],
-            // TODO(#2): Expected result is commented out.
-            // Ok(pkgs![
-            //     "bar v1.0.0",
-            //     "baz v1.0.0",
-            //     "foo v1.0.0"
-            // ]),
-            Err(indoc! {"
-            Version solving failed:
-            - bar v2.0.0 cannot use baz v1.0.0, because bar requires baz ^2.0.0
-
-            Scarb does not have real version solving algorithm yet.
-            Perhaps in the future this conflict could be resolved, but currently,
-            please upgrade your dependencies to use latest versions of their dependencies.
-            "}),
+            Ok(pkgs!["bar v1.0.0", "baz v1.0.0", "foo v1.0.0"]),
         )
     }
 
@@ -450,25 +659,11 @@ mod tests {
                 ("baz v2.1.0", []),
             ],
             &[deps![("bar", "~1.1.0"), ("foo", "~2.7")]],
-            // TODO(#2): Expected result is commented out.
-            // Ok(pkgs![
-            //     "bar v1.1.1",
-            //     "baz v1.8.0",
-            //     "foo v2.9.0"
-            // ]),
-            Err(indoc! {"
-            Version solving failed:
-            - foo v2.7.0 cannot use baz v2.1.0, because foo requires baz ~1.7.1
-
-            Scarb does not have real version solving algorithm yet.
-            Perhaps in the future this conflict could be resolved, but currently,
-            please upgrade your dependencies to use latest versions of their dependencies.
-            "}),
+            Ok(pkgs!["bar v1.1.1", "baz v1.7.1", "foo v2.7.0"]),
         )
     }
 
     #[test]
-    #[ignore = "does not work as expected"]
     fn overlapping_ranges() {
         check(
             registry![
@@ -505,11 +700,120 @@ mod tests {
             &[deps![("top1", "1"), ("top2", "1")]],
             Err(indoc! {"
             Version solving failed:
-            - top2 v1.0.0 cannot use foo v1.0.0, because top2 requires foo ^2.0.0
+            - foo ^1.0.0 is required by top1 v1.0.0
+              ... which satisfies dependency top1 ^1 of root_1 v1.0.0 (workspace member)
+            - foo ^2.0.0 is required by top2 v1.0.0
+              ... which satisfies dependency top2 ^1 of root_1 v1.0.0 (workspace member)
+
+            help: change the `top1` and `top2` dependency requirements in the Scarb.toml of `root_1`, so that all requirements on `foo` can be satisfied by a single version
+            "}),
+        )
+    }
+
+    #[test]
+    fn conflict_through_intermediate_packages() {
+        check(
+            registry![
+                ("foo v1.0.0", []),
+                ("foo v2.0.0", []),
+                ("mid v1.0.0", [("foo", "1.0.0")]),
+                ("top v1.0.0", [("mid", "1")]),
+                ("other v1.0.0", [("foo", "2.0.0")]),
+            ],
+            &[deps![("top", "1"), ("other", "1")]],
+            Err(indoc! {"
+            Version solving failed:
+            - foo ^1.0.0 is required by mid v1.0.0
+              ... which satisfies dependency mid ^1 of top v1.0.0
+              ... which satisfies dependency top ^1 of root_1 v1.0.0 (workspace member)
+            - foo ^2.0.0 is required by other v1.0.0
+              ... which satisfies dependency other ^1 of root_1 v1.0.0 (workspace member)
+
+            help: change the `other` and `top` dependency requirements in the Scarb.toml of `root_1`, so that all requirements on `foo` can be satisfied by a single version
+            "}),
+        )
+    }
+
+    #[test]
+    fn conflict_in_diamond_names_chain_from_derivation() {
+        // Both `a` and `b` depend on `shared`, but only the requirement of `b` causes
+        // the conflict, so the chain must not go through `a`.
+        check(
+            registry![
+                ("a v1.0.0", [("shared", ">=1.0.0")]),
+                ("b v1.0.0", [("shared", "2.0.0"), ("d", "2.0.0")]),
+                ("shared v1.0.0", []),
+                ("shared v2.0.0", [("d", "1.0.0")]),
+                ("d v1.0.0", []),
+                ("d v2.0.0", []),
+            ],
+            &[deps![("a", "1"), ("b", "1")]],
+            Err(indoc! {"
+            Version solving failed:
+            - d ^2.0.0 is required by b v1.0.0
+              ... which satisfies dependency b ^1 of root_1 v1.0.0 (workspace member)
+            - d ^1.0.0 is required by shared v2.0.0
+              ... which satisfies dependency shared ^2.0.0 of b v1.0.0
+              ... which satisfies dependency b ^1 of root_1 v1.0.0 (workspace member)
+
+            help: change the `b` dependency requirement in the Scarb.toml of `root_1`, so that all requirements on `d` can be satisfied by a single version
+            "}),
+        )
+    }
+
+    #[test]
+    fn conflict_with_direct_dependency() {
+        check(
+            registry![
+                ("foo v1.0.0", []),
+                ("foo v2.0.0", []),
+                ("top v1.0.0", [("foo", "2.0.0")]),
+            ],
+            &[deps![("top", "1"), ("foo", "1")]],
+            Err(indoc! {"
+            Version solving failed:
+            - foo ^1 is required by root_1 v1.0.0 (workspace member)
+            - foo ^2.0.0 is required by top v1.0.0
+              ... which satisfies dependency top ^1 of root_1 v1.0.0 (workspace member)
+
+            help: change the `foo` dependency requirement in the Scarb.toml of `root_1`, so that it is compatible with `foo ^2.0.0` required by `top v1.0.0`
+            "}),
+        )
+    }
+
+    #[test]
+    fn conflict_between_workspace_members() {
+        check(
+            registry![
+                ("foo v1.0.0", []),
+                ("foo v2.0.0", []),
+                ("top1 v1.0.0", [("foo", "1.0.0")]),
+                ("top2 v1.0.0", [("foo", "2.0.0")]),
+            ],
+            &[deps![("top1", "1")], deps![("top2", "1")]],
+            Err(indoc! {"
+            Version solving failed:
+            - foo ^1.0.0 is required by top1 v1.0.0
+              ... which satisfies dependency top1 ^1 of root_1 v1.0.0 (workspace member)
+            - foo ^2.0.0 is required by top2 v1.0.0
+              ... which satisfies dependency top2 ^1 of root_2 v1.0.0 (workspace member)
+
+            help: change the `top1` dependency requirement in the Scarb.toml of `root_1`, or the `top2` dependency requirement in the Scarb.toml of `root_2`, so that all requirements on `foo` can be satisfied by a single version
+            "}),
+        )
+    }
+
+    #[test]
+    fn unsatisfied_transient_version_constraint() {
+        check(
+            registry![("foo v1.0.0", []), ("top v1.0.0", [("foo", "3.0.0")]),],
+            &[deps![("top", "1")]],
+            Err(indoc! {"
+            Version solving failed:
+            - foo ^3.0.0 is required by top v1.0.0, but no versions of foo match
+              ... which satisfies dependency top ^1 of root_1 v1.0.0 (workspace member)
 
-            Scarb does not have real version solving algorithm yet.
-            Perhaps in the future this conflict could be resolved, but currently,
-            please upgrade your dependencies to use latest versions of their dependencies.
+            help: change the `top` dependency requirement in the Scarb.toml of `root_1`, so that it selects a version which does not depend on `foo ^3.0.0`
             "}),
         )
     }
@@ -528,7 +832,12 @@ mod tests {
         check(
             registry![("foo v2.0.0", []),],
             &[deps![("foo", "1.0.0")]],
-            Err(r#"cannot find package foo"#),
+            Err(indoc! {"
+            Version solving failed:
+            - foo ^1.0.0 is required by root_1 v1.0.0 (workspace member), but no versions of foo match
+
+            help: change the `foo` dependency requirement in the Scarb.toml of `root_1`, so that it matches an available version
+            "}),
         )
     }
 
@@ -553,7 +862,12 @@ mod tests {
                 ("b v3.8.14", []),
             ],
             &[deps![("a", "~3.6"), ("b", "~3.6")]],
-            Err(r#"cannot find package a"#),
+            Err(indoc! {"
+            Version solving failed:
+            - b ~3.6 is required by root_1 v1.0.0 (workspace member), but no versions of b match
+
+            help: change the `b` dependency requirement in the Scarb.toml of `root_1`, so that it matches an available version
+            "}),
         )
     }
 
@@ -573,7 +887,12 @@ mod tests {
                 ("b v3.8.5", [("d", "2.9.0")]),
             ],
             &[deps![("a", "~3.6"), ("c", "~1.1"), ("b", "~3.6")]],
-            Err(r#"cannot find package a"#),
+            Err(indoc! {"
+            Version solving failed:
+            - b ~3.6 is required by root_1 v1.0.0 (workspace member), but no versions of b match
+
+            help: change the `b` dependency requirement in the Scarb.toml of `root_1`, so that it matches an available version
+            "}),
         )
     }
 
@@ -596,7 +915,12 @@ mod tests {
                 ),
             ],
             &[deps![("e", "~1.0"), ("a", "~3.7"), ("b", "~3.7")]],
-            Err(r#"cannot find package e"#),
+            Err(indoc! {"
+            Version solving failed:
+            - b ~3.7 is required by root_1 v1.0.0 (workspace member), but no versions of b match
+
+            help: change the `b` dependency requirement in the Scarb.toml of `root_1`, so that it matches an available version
+            "}),
         )
     }
 
@@ -691,13 +1015,53 @@ mod tests {
         )
     }
 
+    #[test]
+    fn skip_yanked() {
+        let mut registry = registry![("foo v1.0.0", []), ("foo v1.1.0", [])];
+        registry.yank(pkgs!["foo v1.1.0"][0]);
+        check(registry, &[deps![("foo", "1")]], Ok(pkgs!["foo v1.0.0"]))
+    }
+
+    #[test]
+    fn use_locked_yanked() {
+        let mut registry = registry![("foo v1.0.0", []), ("foo v1.1.0", [])];
+        registry.yank(pkgs!["foo v1.1.0"][0]);
+        check_with_lock(
+            registry,
+            &[deps![("foo", "1")]],
+            locks![("foo v1.1.0", [])],
+            Ok(pkgs!["foo v1.1.0"]),
+        )
+    }
+
+    #[test]
+    fn all_versions_yanked() {
+        let mut registry = registry![("foo v1.0.0", [])];
+        registry.yank(pkgs!["foo v1.0.0"][0]);
+        check(
+            registry,
+            &[deps![("foo", "1")]],
+            Err(indoc! {"
+            Version solving failed:
+            - foo ^1 is required by root_1 v1.0.0 (workspace member), but no versions of foo match
+
+            help: change the `foo` dependency requirement in the Scarb.toml of `root_1`, so that it matches an available version
+            "}),
+        )
+    }
+
     #[test]
     fn lock_conflict_1() {
         check_with_lock(
             registry![("foo v1.0.0", []),],
             &[deps![("foo", "2.0.0"),]],
             locks![("foo v1.0.0", [])],
-            Err("cannot find package foo"),
+            Err(indoc! {"
+            Version solving failed:
+            - foo ^2.0.0 is required by root_1 v1.0.0 (workspace member), but no versions of foo match
+
+            help: change the `foo` dependency requirement in the Scarb.toml of `root_1`, so that it matches an available version
+            "}),
         );
     }
 
@@ -727,145 +1091,159 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "optional deps are not implemented yet"]
-    fn skip_single_optional() {
-        //     Registry.put("foo", "1.0.0", [])
-        //
-        //     assert run([{"foo", "1.0.0", optional: true}]) == %{}
+    fn stale_lock_conflicting_with_changed_requirement() {
+        check_with_lock(
+            registry![
+                ("foo v1.0.0", []),
+                ("foo v1.1.0", []),
+                ("bar v1.0.0", []),
+                ("bar v1.1.0", [("foo", ">=1.1.0")]),
+            ],
+            &[deps![("foo", "1"), ("bar", "1.1")]],
+            locks![("foo v1.0.0", []), ("bar v1.0.0", [])],
+            Ok(pkgs!["bar v1.1.0", "foo v1.1.0"]),
+        );
     }
 
-    #[test]
-    #[ignore = "optional deps are not implemented yet"]
-    fn skip_locked_optional() {
-        //     Registry.put("foo", "1.0.0", [])
-        //
-        //     assert run([{"foo", "1.0.0", optional: true}], [{"foo", "1.0.0"}]) == %{}
-    }
+    // Unlike in Hex, which these cases are ported from, optional dependencies are not skipped
+    // during resolution, but are always resolved to keep the lock file independent of features.
 
     #[test]
-    #[ignore = "optional deps are not implemented yet"]
-    fn skip_conflicting_optionals() {
-        //     Registry.put("foo", "1.0.0", [{"bar", "1.0.0"}, {"car", "~1.0", optional: true}])
-        //     Registry.put("bar", "1.0.0", [{"car", "~2.0", optional: true}])
-        //     Registry.put("car", "1.0.0", [])
-        //     Registry.put("car", "2.0.0", [])
-        //
-        //     assert run([{"foo", "1.0.0"}], []) == %{
-        //              "foo" => "1.0.0",
-        //              "bar" => "1.0.0"
-        //            }
+    fn optional_dependency_is_resolved() {
+        check(
+            registry![("foo v1.0.0", [])],
+            &[deps![("foo", "1.0.0", optional)]],
+            Ok(pkgs!["foo v1.0.0"]),
+        )
     }
 
     #[test]
-    #[ignore = "optional deps are not implemented yet"]
-    fn skip_transitive_optionals() {
-        //     # car's fuse dependency needs to be a subset of bar's fuse dependency
-        //     # fuse 1.0.0 ⊃ fuse ~1.0
-        //
-        //     Registry.put("foo", "1.0.0", [{"bar", "1.0.0"}, {"car", "1.0.0"}])
-        //     Registry.put("bar", "1.0.0", [{"fuse", "~1.0", optional: true}])
-        //     Registry.put("car", "1.0.0", [{"fuse", "1.0.0", optional: true}])
-        //     Registry.put("fuse", "1.0.0", [])
-        //
-        //     assert run([{"foo", "1.0.0"}], []) == %{
-        //              "foo" => "1.0.0",
-        //              "bar" => "1.0.0",
-        //              "car" => "1.0.0"
-        //            }
+    fn locked_optional_dependency_is_resolved() {
+        check_with_lock(
+            registry![("foo v1.0.0", []), ("foo v1.1.0", [])],
+            &[deps![("foo", "1.0.0", optional)]],
+            locks![("foo v1.0.0", [])],
+            Ok(pkgs!["foo v1.0.0"]),
+        )
     }
 
     #[test]
-    #[ignore = "optional deps are not implemented yet"]
-    fn skip_conflicting_transitive_optionals() {
-        //     Registry.put("foo", "1.0.0", [{"bar", "1.0.0"}, {"car", "1.0.0"}])
-        //     Registry.put("bar", "1.0.0", [{"fuse", "~1.0", optional: true}])
-        //     Registry.put("car", "1.0.0", [{"fuse", "~2.0", optional: true}])
-        //     Registry.put("fuse", "1.0.0", [])
-        //     Registry.put("fuse", "2.0.0", [])
-        //
-        //     assert run([{"foo", "1.0.0"}], []) == %{
-        //              "foo" => "1.0.0",
-        //              "bar" => "1.0.0",
-        //              "car" => "1.0.0"
-        //            }
+    fn stale_lock_of_optional_dependency_does_not_conflict() {
+        check_with_lock(
+            registry![("foo v1.0.0", [])],
+            &[deps![("foo", "1.0.0", optional)]],
+            locks![("foo v1.1.0", [])],
+            Ok(pkgs!["foo v1.0.0"]),
+        )
     }
 
     #[test]
-    #[ignore = "optional deps are not implemented yet"]
-    fn locked_optional_does_not_conflict() {
-        //     Registry.put("foo", "1.0.0", [])
-        //
-        //     assert run([{"foo", "1.0.0", optional: true}], [{"foo", "1.1.0"}]) == %{}
+    fn transitive_optional_dependencies_are_unified() {
+        check(
+            registry![
+                ("foo v1.0.0", [("bar", "1.0.0"), ("car", "1.0.0")]),
+                ("bar v1.0.0", [("fuse", "~1.0", optional)]),
+                ("car v1.0.0", [("fuse", "=1.0.0", optional)]),
+                ("fuse v1.0.0", []),
+                ("fuse v1.1.0", []),
+            ],
+            &[deps![("foo", "1.0.0")]],
+            Ok(pkgs![
+                "bar v1.0.0",
+                "car v1.0.0",
+                "foo v1.0.0",
+                "fuse v1.0.0"
+            ]),
+        )
     }
 
     #[test]
-    #[ignore = "optional deps are not implemented yet"]
-    fn skip_optional_with_backtrack() {
-        //     Registry.put("foo", "1.1.0", [{"bar", "1.1.0"}, {"baz", "1.0.0"}, {"opt", "1.0.0"}])
-        //     Registry.put("foo", "1.0.0", [{"bar", "1.0.0"}, {"opt", "1.0.0", optional: true}])
-        //     Registry.put("bar", "1.1.0", [{"baz", "1.1.0"}, {"opt", "1.0.0"}])
-        //     Registry.put("bar", "1.0.0", [{"baz", "1.0.0"}, {"opt", "1.0.0", optional: true}])
-        //     Registry.put("baz", "1.1.0", [{"opt", "1.0.0"}])
-        //     Registry.put("baz", "1.0.0", [{"opt", "1.0.0", optional: true}])
-        //     Registry.put("opt", "1.0.0", [])
-        //
-        //     assert run([{"foo", "~1.0"}]) == %{"foo" => "1.0.0", "bar" => "1.0.0", "baz" => "1.0.0"}
+    fn optional_and_required_dependency_are_unified() {
+        check(
+            registry![
+                ("foo v1.0.0", []),
+                ("foo v1.1.0", []),
+                ("bar v1.0.0", [("foo", "~1.0")]),
+            ],
+            &[deps![("foo", "~1.0.0", optional), ("bar", "1.0.0")]],
+            Ok(pkgs!["bar v1.0.0", "foo v1.0.0"]),
+        )
     }
 
     #[test]
-    #[ignore = "optional deps are not implemented yet"]
-    fn select_optional() {
-        //     Registry.put("foo", "1.0.0", [])
-        //     Registry.put("bar", "1.0.0", [{"foo", "1.0.0"}])
-        //
-        //     assert run([{"foo", "1.0.0", optional: true}, {"bar", "1.0.0"}]) == %{
-        //              "foo" => "1.0.0",
-        //              "bar" => "1.0.0"
-        //            }
+    fn optional_dependency_with_backtrack() {
+        check(
+            registry![
+                (
+                    "foo v1.1.0",
+                    [("bar", "=1.1.0"), ("baz", "=1.0.0"), ("opt", "1.0.0")]
+                ),
+                (
+                    "foo v1.0.0",
+                    [("bar", "=1.0.0"), ("opt", "1.0.0", optional)]
+                ),
+                ("bar v1.1.0", [("baz", "=1.1.0"), ("opt", "1.0.0")]),
+                (
+                    "bar v1.0.0",
+                    [("baz", "=1.0.0"), ("opt", "1.0.0", optional)]
+                ),
+                ("baz v1.1.0", [("opt", "1.0.0")]),
+                ("baz v1.0.0", [("opt", "1.0.0", optional)]),
+                ("opt v1.0.0", []),
+            ],
+            &[deps![("foo", "~1.0")]],
+            Ok(pkgs![
+                "bar v1.0.0",
+                "baz v1.0.0",
+                "foo v1.0.0",
+                "opt v1.0.0"
+            ]),
+        )
     }
 
     #[test]
-    #[ignore = "optional deps are not implemented yet"]
-    fn select_older_optional() {
-        //     Registry.put("foo", "1.0.0", [])
-        //     Registry.put("foo", "1.1.0", [])
-        //     Registry.put("bar", "1.0.0", [{"foo", "~1.0"}])
-        //
-        //     assert run([{"foo", "~1.0.0", optional: true}, {"bar", "1.0.0"}]) == %{
-        //              "foo" => "1.0.0",
-        //              "bar" => "1.0.0"
-        //            }
-    }
+    fn conflicting_optional_dependencies() {
+        // Optional dependencies take part in resolution even if no feature enables them,
+        // so conflicting requirements are reported just like for regular dependencies.
+        check(
+            registry![
+                ("foo v1.0.0", []),
+                ("foo v2.0.0", []),
+                ("top1 v1.0.0", [("foo", "1.0.0", optional)]),
+                ("top2 v1.0.0", [("foo", "2.0.0", optional)]),
+            ],
+            &[deps![("top1", "1"), ("top2", "1")]],
+            Err(indoc! {"
+            Version solving failed:
+            - foo ^1.0.0 is required by top1 v1.0.0
+              ... which satisfies dependency top1 ^1 of root_1 v1.0.0 (workspace member)
+            - foo ^2.0.0 is required by top2 v1.0.0
+              ... which satisfies dependency top2 ^1 of root_1 v1.0.0 (workspace member)
 
-    #[test]
-    #[ignore = "optional deps are not implemented yet"]
-    fn select_optional_with_backtrack() {
-        //     Registry.put("foo", "1.1.0", [{"bar", "1.1.0"}, {"baz", "1.0.0"}, {"opt", "1.0.0"}])
-        //     Registry.put("foo", "1.0.0", [{"bar", "1.0.0"}, {"opt", "1.0.0", optional: true}])
-        //     Registry.put("bar", "1.1.0", [{"baz", "1.1.0"}, {"opt", "1.0.0"}])
-        //     Registry.put("bar", "1.0.0", [{"baz", "1.0.0"}, {"opt", "1.0.0", optional: true}])
-        //     Registry.put("baz", "1.1.0", [{"opt", "1.0.0", optional: true}])
-        //     Registry.put("baz", "1.0.0", [{"opt", "1.0.0"}])
-        //     Registry.put("opt", "1.0.0", [])
-        //
-        //     assert run([{"foo", "~1.0"}]) == %{
-        //              "foo" => "1.0.0",
-        //              "bar" => "1.0.0",
-        //              "baz" => "1.0.0",
-        //              "opt" => "1.0.0"
-        //            }
+            help: change the `top1` and `top2` dependency requirements in the Scarb.toml of `root_1`, so that all requirements on `foo` can be satisfied by a single version
+            "}),
+        )
     }
 
     #[test]
-    #[ignore = "optional deps are not implemented yet"]
-    fn optional_with_conflict() {
-        //     Registry.put("foo", "1.0.0", [{"bar", "~2.0", optional: true}])
-        //     Registry.put("baz", "1.0.0", [{"bar", "~1.0"}])
-        //     Registry.put("car", "1.0.0", [{"foo", ">= 1.0.0"}])
-        //     Registry.put("bar", "1.0.0", [])
-        //     Registry.put("bar", "2.0.0", [])
-        //
-        //     assert {:conflict, _, _} = run([{"car", ">= 0.0.0"}, {"baz", ">= 0.0.0"}])
+    fn optional_dependency_conflicting_with_required_dependency() {
+        let resolve = resolve(
+            registry![
+                ("foo v1.0.0", [("bar", "~2.0", optional)]),
+                ("baz v1.0.0", [("bar", "~1.0")]),
+                ("car v1.0.0", [("foo", ">=1.0.0")]),
+                ("bar v1.0.0", []),
+                ("bar v2.0.0", []),
+            ],
+            vec![(
+                deps![("car", ">=0.0.0"), ("baz", ">=0.0.0")],
+                package_id("root_1"),
+            )],
+        );
+        assert!(resolve
+            .unwrap_err()
+            .to_string()
+            .starts_with("Version solving failed:"));
     }
 
     #[test]
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use semver::Version;
+
+use crate::core::PackageName;
+use crate::resolver::incompatibility::{Incompatibility, IncompatibilityId, Relation};
+use crate::resolver::term::{Term, VersionSet};
+
+/// A single step in building the [`PartialSolution`].
+#[derive(Clone, Debug)]
+struct Assignment {
+    package: PackageName,
+    term: Term,
+    decision_level: usize,
+    /// The incompatibility which caused this assignment to be derived,
+    /// or `None` if this is a decision.
+    cause: Option<IncompatibilityId>,
+}
+
+/// The outcome of searching for a satisfier of a conflicting incompatibility.
+pub enum SatisfierSearch {
+    /// The conflict can be fixed by backtracking to the given decision level.
+    DifferentDecisionLevels { previous_satisfier_level: usize },
+    /// The satisfier was derived at the same decision level as the rest of the incompatibility,
+    /// so conflict resolution has to continue with its cause.
+    SameDecisionLevels { satisfier_cause: IncompatibilityId },
+}
+
+/// An ordered list of assignments (decisions and derivations) made by the solver so far.
+#[derive(Debug, Default)]
+pub struct PartialSolution {
+    assignments: Vec<Assignment>,
+    decision_level: usize,
+    intersections: HashMap<PackageName, Term>,
+    decisions: HashMap<PackageName, Version>,
+}
+
+impl PartialSolution {
+    pub fn add_decision(&mut self, package: PackageName, version: Version) {
+        self.decision_level += 1;
+        self.decisions.insert(package.clone(), version.clone());
+        self.push(Assignment {
+            package,
+            term: Term::exact(version),
+            decision_level: self.decision_level,
+            cause: None,
+        });
+    }
+
+    pub fn add_derivation(&mut self, package: PackageName, term: Term, cause: IncompatibilityId) {
+        self.push(Assignment {
+            package,
+            term,
+            decision_level: self.decision_level,
+            cause: Some(cause),
+        });
+    }
+
+    fn push(&mut self, assignment: Assignment) {
+        self.intersections
+            .entry(assignment.package.clone())
+            .and_modify(|term| *term = term.intersection(&assignment.term))
+            .or_insert_with(|| assignment.term.clone());
+        self.assignments.push(assignment);
+    }
+
+    /// Remove all assignments made after the given decision level.
+    pub fn backtrack(&mut self, decision_level: usize) {
+        let assignments = std::mem::take(&mut self.assignments);
+        self.decision_level = decision_level;
+        self.intersections.clear();
+        self.decisions.clear();
+        for assignment in assignments
+            .into_iter()
+            .take_while(|a| a.decision_level <= decision_level)
+        {
+            if assignment.cause.is_none() {
+                let Term::Positive(VersionSet::Only(versions)) = &assignment.term else {
+                    unreachable!("decisions are always exact terms");
+                };
+                let version = versions.first().unwrap().clone();
+                self.decisions.insert(assignment.package.clone(), version);
+            }
+            self.push(assignment);
+        }
+    }
+
+    /// Intersection of all terms assigned to the package, or `None` if nothing is known about it.
+    pub fn term_intersection(&self, package: &PackageName) -> Option<&Term> {
+        self.intersections.get(package)
+    }
+
+    pub fn relation(&self, incompat: &Incompatibility) -> Relation {
+        incompat.relation(|package| self.term_intersection(package))
+    }
+
+    /// Iterate over packages which are required by the solution, but do not have a version
+    /// decided yet, together with the sets of versions they are allowed to take.
+    pub fn undecided_packages(&self) -> impl Iterator<Item = (&PackageName, &VersionSet)> {
+        self.intersections
+            .iter()
+            .filter(|(package, _)| !self.decisions.contains_key(*package))
+            .filter_map(|(package, term)| match term {
+                Term::Positive(set) => Some((package, set)),
+                Term::Negative(_) => None,
+            })
+    }
+
+    pub fn decisions(&self) -> impl Iterator<Item = (&PackageName, &Version)> {
+        self.decisions.iter()
+    }
+
+    /// Find the assignment which satisfied the `incompat`, and the decision level at which
+    /// the incompatibility was satisfied before that assignment.
+    ///
+    /// Returns the name of the package of the satisfier and the search result.
+    ///
+    /// # Safety
+    /// * Asserts that `incompat` is satisfied by this partial solution.
+    pub fn satisfier_search(&self, incompat: &Incompatibility) -> (PackageName, SatisfierSearch) {
+        let satisfiers = incompat
+            .terms()
+            .map(|(package, term)| (package, self.find_satisfier(package, term, Term::any())))
+            .collect::<Vec<_>>();
+
+        let &(satisfier_package, satisfier_index) = satisfiers
+            .iter()
+            .max_by_key(|(_, index)| *index)
+            .expect("incompatibility must not be empty");
+        let satisfier = &self.assignments[satisfier_index];
+
+        // Find the earliest assignment for the satisfier's package, which together with
+        // the satisfier itself, satisfies the incompatibility.
+        let previous_index = self.find_satisfier(
+            satisfier_package,
+            incompat.get(satisfier_package).unwrap(),
+            satisfier.term.clone(),
+        );
+
+        let previous_satisfier_level = satisfiers
+            .iter()
+            .map(|&(package, index)| {
+                if package == satisfier_package {
+                    previous_index
+                } else {
+                    index
+                }
+            })
+            .map(|index| self.assignments[index].decision_level)
+            .max()
+            .unwrap_or_default()
+            .max(1);
+
+        let search = match satisfier.cause {
+            Some(cause) if previous_satisfier_level >= satisfier.decision_level => {
+                SatisfierSearch::SameDecisionLevels {
+                    satisfier_cause: cause,
+                }
+            }
+            _ => SatisfierSearch::DifferentDecisionLevels {
+                previous_satisfier_level,
+            },
+        };
+
+        (satisfier_package.clone(), search)
+    }
+
+    /// Find the index of the earliest assignment for `package` such that the intersection of
+    /// all assignments up to it, together with `start`, satisfies `term`.
+    fn find_satisfier(&self, package: &PackageName, term: &Term, start: Term) -> usize {
+        let mut accumulated = start;
+        for (index, assignment) in self.assignments.iter().enumerate() {
+            if assignment.package != *package {
+                continue;
+            }
+            accumulated = accumulated.intersection(&assignment.term);
+            if accumulated.is_subset_of(term) {
+                return index;
+            }
+        }
+        panic!("term for package `{package}` is not satisfied by partial solution")
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use itertools::Itertools;
+
+use crate::core::{ManifestDependency, PackageId, PackageName};
+use crate::resolver::incompatibility::{IncompatibilityId, IncompatibilityKind};
+use crate::resolver::term::Term;
+use crate::resolver::Solver;
+
+/// A dependency of a package version, which took part in deriving a version solving failure.
+struct Requirement<'a> {
+    dependent: PackageId,
+    dependency: &'a ManifestDependency,
+    /// Whether none of the known versions of the dependency match its version requirement.
+    unsatisfiable: bool,
+    /// Known versions of the dependency, which match its version requirement.
+    candidates: Vec<PackageId>,
+}
+
+/// Human-readable explanation of a version solving failure.
+///
+/// Instead of reciting the derivation of the root cause incompatibility step by step,
+/// the report lists requirements on packages which are in conflict, each followed by the chain
+/// of dependencies through which it has been pulled in by a workspace member.
+/// It ends with a hint on which dependency in which `Scarb.toml` is the most likely one to fix.
+pub struct FailureReport<'a> {
+    members: &'a [PackageId],
+    requirements: Vec<Requirement<'a>>,
+    unavailable: BTreeSet<&'a PackageName>,
+    /// The package and dependency through which each package version taking part in
+    /// the failure has been reached the quickest from workspace members.
+    required_by: HashMap<PackageId, (PackageId, &'a ManifestDependency)>,
+}
+
+impl<'a> FailureReport<'a> {
+    pub fn new(solver: &'a Solver<'_>, root_cause: IncompatibilityId) -> Self {
+        let mut requirements = Vec::new();
+        let mut unavailable = BTreeSet::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![root_cause];
+        while let Some(id) = stack.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            let incompat = solver.state.incompatibility(id);
+            match &incompat.kind {
+                IncompatibilityKind::Derived(left, right) => {
+                    stack.push(*left);
+                    stack.push(*right);
+                }
+                IncompatibilityKind::Root => {}
+                IncompatibilityKind::Dependency(dependent, dependency) => {
+                    let candidates = match incompat.get(&dependency.name) {
+                        Some(Term::Negative(versions)) => solver
+                            .allowed_candidates(&dependency.name, versions)
+                            .map(|summary| summary.package_id)
+                            .collect(),
+                        _ => Vec::new(),
+                    };
+                    requirements.push(Requirement {
+                        dependent: *dependent,
+                        dependency,
+                        unsatisfiable: incompat.get(&dependency.name).is_none(),
+                        candidates,
+                    });
+                }
+                IncompatibilityKind::NoVersions(package) => {
+                    unavailable.insert(package);
+                }
+            }
+        }
+        requirements.sort_by(|a, b| {
+            (&a.dependency.name, a.dependent).cmp(&(&b.dependency.name, b.dependent))
+        });
+
+        let required_by = required_by(&solver.members, &requirements);
+        Self {
+            members: &solver.members,
+            requirements,
+            unavailable,
+            required_by,
+        }
+    }
+
+    fn is_member(&self, package_id: &PackageId) -> bool {
+        self.members.contains(package_id)
+    }
+
+    /// Packages which are depended upon, but whose own dependencies do not take part in
+    /// the failure, are the ones in conflict.
+    /// All other packages are just links in requirement chains.
+    fn conflicting_packages(&self) -> BTreeSet<&'a PackageName> {
+        let dependents = self
+            .requirements
+            .iter()
+            .map(|r| &r.dependent.name)
+            .collect::<HashSet<_>>();
+        let conflicting = self
+            .requirements
+            .iter()
+            .map(|r| &r.dependency.name)
+            .filter(|name| !dependents.contains(name))
+            .chain(self.unavailable.iter().copied())
+            .collect::<BTreeSet<_>>();
+        if conflicting.is_empty() {
+            // Dependency cycles do not have a leaf, fall back to listing everything.
+            self.requirements
+                .iter()
+                .map(|r| &r.dependency.name)
+                .collect()
+        } else {
+            conflicting
+        }
+    }
+
+    fn requirements_on<'s>(
+        &'s self,
+        package: &'s PackageName,
+    ) -> impl Iterator<Item = &'s Requirement<'a>> {
+        self.requirements
+            .iter()
+            .filter(move |r| r.dependency.name == *package)
+    }
+
+    /// Find the workspace member which pulled in the `requirement`, and its own dependency
+    /// which started the chain.
+    fn origin(&self, requirement: &Requirement<'a>) -> Option<(PackageId, &'a ManifestDependency)> {
+        let mut origin = (requirement.dependent, requirement.dependency);
+        while !self.is_member(&origin.0) {
+            origin = *self.required_by.get(&origin.0)?;
+        }
+        Some(origin)
+    }
+
+    fn fmt_requirement(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        requirement: &Requirement<'_>,
+    ) -> fmt::Result {
+        let dependent = &requirement.dependent;
+        write!(
+            f,
+            "\n- {} is required by {} v{}",
+            requirement.dependency, dependent.name, dependent.version
+        )?;
+        if self.is_member(dependent) {
+            f.write_str(" (workspace member)")?;
+        }
+        if requirement.unsatisfiable {
+            write!(
+                f,
+                ", but no versions of {} match",
+                requirement.dependency.name
+            )?;
+        }
+
+        let mut package_id = *dependent;
+        while let Some((parent, dependency)) = self.required_by.get(&package_id) {
+            write!(
+                f,
+                "\n  ... which satisfies dependency {dependency} of {} v{}",
+                parent.name, parent.version
+            )?;
+            if self.is_member(parent) {
+                f.write_str(" (workspace member)")?;
+            }
+            package_id = *parent;
+        }
+        Ok(())
+    }
+
+    /// Suggest the `Scarb.toml` edit which is the most likely to fix the conflict on `package`.
+    fn help(&self, package: &PackageName) -> Option<String> {
+        let requirements = self.requirements_on(package).collect_vec();
+
+        if let Some(requirement) = requirements.iter().find(|r| r.unsatisfiable) {
+            let (member, dependency) = self.origin(requirement)?;
+            return Some(if dependency.name == *package {
+                format!(
+                    "change the `{package}` dependency requirement in the Scarb.toml of \
+                    `{}`, so that it matches an available version",
+                    member.name
+                )
+            } else {
+                format!(
+                    "change the `{}` dependency requirement in the Scarb.toml of `{}`, \
+                    so that it selects a version which does not depend on `{}`",
+                    dependency.name, member.name, requirement.dependency
+                )
+            });
+        }
+
+        // If a workspace member depends on the package directly, it is easier to adjust this
+        // requirement to the ones coming from elsewhere, than the other way around.
+        if let Some(direct) = requirements.iter().find(|r| self.is_member(&r.dependent)) {
+            let other = requirements
+                .iter()
+                .find(|r| r.dependent != direct.dependent)?;
+            return Some(format!(
+                "change the `{package}` dependency requirement in the Scarb.toml of `{}`, \
+                so that it is compatible with `{}` required by `{} v{}`",
+                direct.dependent.name,
+                other.dependency,
+                other.dependent.name,
+                other.dependent.version
+            ));
+        }
+
+        let mut origins: BTreeMap<PackageName, BTreeSet<&PackageName>> = BTreeMap::new();
+        for requirement in &requirements {
+            let (member, dependency) = self.origin(requirement)?;
+            origins
+                .entry(member.name.clone())
+                .or_default()
+                .insert(&dependency.name);
+        }
+        let edits = origins
+            .into_iter()
+            .map(|(member, dependencies)| {
+                let noun = if dependencies.len() == 1 {
+                    "dependency requirement"
+                } else {
+                    "dependency requirements"
+                };
+                let dependencies = dependencies.iter().map(|d| format!("`{d}`")).join(" and ");
+                format!("the {dependencies} {noun} in the Scarb.toml of `{member}`")
+            })
+            .join(", or ");
+        Some(format!(
+            "change {edits}, so that all requirements on `{package}` can be satisfied by \
+            a single version"
+        ))
+    }
+}
+
+impl fmt::Display for FailureReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Version solving failed:")?;
+        let conflicting = self.conflicting_packages();
+        for package in &conflicting {
+            for requirement in self.requirements_on(package) {
+                self.fmt_requirement(f, requirement)?;
+            }
+            if self.unavailable.contains(package) {
+                write!(f, "\n- no versions of {package} are available")?;
+            }
+        }
+        writeln!(f)?;
+
+        let help = conflicting
+            .iter()
+            .filter_map(|package| self.help(package))
+            .collect_vec();
+        if !help.is_empty() {
+            writeln!(f)?;
+            for help in help {
+                writeln!(f, "help: {help}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// For every package version taking part in the failure, find the package and dependency
+/// through which it can be reached from workspace members in the fewest steps.
+///
+/// Only requirements from the derivation of the failure are followed, so that chains never go
+/// through dependencies which have nothing to do with it.
+fn required_by<'a>(
+    members: &[PackageId],
+    requirements: &[Requirement<'a>],
+) -> HashMap<PackageId, (PackageId, &'a ManifestDependency)> {
+    let mut required_by = HashMap::new();
+    let mut visited: HashSet<PackageId> = members.iter().copied().collect();
+    let mut queue: VecDeque<PackageId> = members.iter().copied().collect();
+    while let Some(package_id) = queue.pop_front() {
+        for requirement in requirements.iter().filter(|r| r.dependent == package_id) {
+            for &dependency_id in &requirement.candidates {
+                if visited.insert(dependency_id) {
+                    required_by.insert(dependency_id, (package_id, requirement.dependency));
+                    queue.push_back(dependency_id);
+                }
+            }
+        }
+    }
+    required_by
+}
@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::core::{PackageId, PackageName};
+use crate::resolver::incompatibility::{Incompatibility, IncompatibilityId, Relation};
+use crate::resolver::partial_solution::{PartialSolution, SatisfierSearch};
+
+/// Version solver state, implementing unit propagation and conflict resolution steps of
+/// the [PubGrub] algorithm.
+///
+/// Choosing package versions to try is left up to the caller, because it requires querying
+/// the [`Registry`](crate::core::registry::Registry).
+///
+/// [PubGrub]: https://github.com/dart-lang/pub/blob/master/doc/solver.md
+#[derive(Debug)]
+pub struct State {
+    roots: HashSet<PackageName>,
+    incompatibilities: Vec<Incompatibility>,
+    incompatibilities_of: HashMap<PackageName, Vec<IncompatibilityId>>,
+    pub partial_solution: PartialSolution,
+}
+
+impl State {
+    pub fn new(roots: impl IntoIterator<Item = PackageId>) -> Self {
+        let mut state = Self {
+            roots: HashSet::new(),
+            incompatibilities: Vec::new(),
+            incompatibilities_of: HashMap::new(),
+            partial_solution: PartialSolution::default(),
+        };
+        for root in roots {
+            state.roots.insert(root.name.clone());
+            state.add_incompatibility(Incompatibility::root(root));
+        }
+        state
+    }
+
+    pub fn incompatibility(&self, id: IncompatibilityId) -> &Incompatibility {
+        &self.incompatibilities[id]
+    }
+
+    pub fn add_incompatibility(&mut self, incompat: Incompatibility) -> IncompatibilityId {
+        let id = self.store(incompat);
+        self.index(id);
+        id
+    }
+
+    /// Put the incompatibility in the store without making it visible to unit propagation.
+    fn store(&mut self, incompat: Incompatibility) -> IncompatibilityId {
+        self.incompatibilities.push(incompat);
+        self.incompatibilities.len() - 1
+    }
+
+    fn index(&mut self, id: IncompatibilityId) {
+        for package in self.incompatibilities[id].packages() {
+            self.incompatibilities_of
+                .entry(package.clone())
+                .or_default()
+                .push(id);
+        }
+    }
+
+    /// Derive all assignments implied by the current partial solution, starting from
+    /// incompatibilities referring to the `package`.
+    ///
+    /// Returns the ID of an incompatibility proving that no solution exists on failure.
+    pub fn unit_propagation(&mut self, package: PackageName) -> Result<(), IncompatibilityId> {
+        let mut changed = vec![package];
+        while let Some(package) = changed.pop() {
+            let mut conflict = None;
+
+            let ids = self
+                .incompatibilities_of
+                .get(&package)
+                .cloned()
+                .unwrap_or_default();
+            // Evaluate newer incompatibilities first, they are usually more specific.
+            for id in ids.into_iter().rev() {
+                let incompat = &self.incompatibilities[id];
+                match self.partial_solution.relation(incompat) {
+                    Relation::Satisfied => {
+                        conflict = Some(id);
+                        break;
+                    }
+                    Relation::AlmostSatisfied(unsatisfied) => {
+                        let term = incompat.get(&unsatisfied).unwrap().negate();
+                        self.partial_solution
+                            .add_derivation(unsatisfied.clone(), term, id);
+                        changed.push(unsatisfied);
+                    }
+                    Relation::Contradicted | Relation::Inconclusive => {}
+                }
+            }
+
+            if let Some(conflict) = conflict {
+                let (unsatisfied, root_cause) = self.conflict_resolution(conflict)?;
+                // The root cause is now almost satisfied, with `unsatisfied` being the only
+                // remaining package, so its negation can be derived right away.
+                let term = self.incompatibilities[root_cause]
+                    .get(&unsatisfied)
+                    .unwrap()
+                    .negate();
+                self.partial_solution
+                    .add_derivation(unsatisfied.clone(), term, root_cause);
+                changed.clear();
+                changed.push(unsatisfied);
+            }
+        }
+        Ok(())
+    }
+
+    /// Learn from a conflict, and backtrack the partial solution to the state in which
+    /// the learned incompatibility is almost satisfied.
+    fn conflict_resolution(
+        &mut self,
+        conflict: IncompatibilityId,
+    ) -> Result<(PackageName, IncompatibilityId), IncompatibilityId> {
+        let mut current = conflict;
+        let mut learned = false;
+        loop {
+            let incompat = &self.incompatibilities[current];
+            if incompat.is_terminal(|package| self.roots.contains(package)) {
+                return Err(current);
+            }
+
+            let (package, search) = self.partial_solution.satisfier_search(incompat);
+            match search {
+                SatisfierSearch::DifferentDecisionLevels {
+                    previous_satisfier_level,
+                } => {
+                    self.partial_solution.backtrack(previous_satisfier_level);
+                    if learned {
+                        self.index(current);
+                    }
+                    return Ok((package, current));
+                }
+                SatisfierSearch::SameDecisionLevels { satisfier_cause } => {
+                    let prior_cause = Incompatibility::prior_cause(
+                        current,
+                        incompat,
+                        satisfier_cause,
+                        &self.incompatibilities[satisfier_cause],
+                        &package,
+                    );
+                    current = self.store(prior_cause);
+                    learned = true;
+                }
+            }
+        }
+    }
+}
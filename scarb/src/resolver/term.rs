@@ -0,0 +1,148 @@
+use std::collections::BTreeSet;
+
+use semver::Version;
+
+/// A set of package versions.
+///
+/// The resolver only ever chooses versions which were returned by [`Registry::query`] calls,
+/// so instead of modelling arbitrary version ranges, sets are represented either as an explicit
+/// list of versions, or as a complement of one. This representation is closed under all
+/// set operations needed by the solver.
+///
+/// [`Registry::query`]: crate::core::registry::Registry::query
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VersionSet {
+    /// Exactly the listed versions.
+    Only(BTreeSet<Version>),
+    /// All versions except the listed ones.
+    Except(BTreeSet<Version>),
+}
+
+impl VersionSet {
+    pub fn empty() -> Self {
+        Self::Only(BTreeSet::new())
+    }
+
+    pub fn singleton(version: Version) -> Self {
+        Self::Only(BTreeSet::from([version]))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Only(versions) if versions.is_empty())
+    }
+
+    pub fn contains(&self, version: &Version) -> bool {
+        match self {
+            Self::Only(versions) => versions.contains(version),
+            Self::Except(versions) => !versions.contains(version),
+        }
+    }
+
+    pub fn complement(&self) -> Self {
+        match self {
+            Self::Only(versions) => Self::Except(versions.clone()),
+            Self::Except(versions) => Self::Only(versions.clone()),
+        }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Only(a), Self::Only(b)) => Self::Only(a.intersection(b).cloned().collect()),
+            (Self::Only(a), Self::Except(b)) | (Self::Except(b), Self::Only(a)) => {
+                Self::Only(a.difference(b).cloned().collect())
+            }
+            (Self::Except(a), Self::Except(b)) => Self::Except(a.union(b).cloned().collect()),
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.complement()
+            .intersection(&other.complement())
+            .complement()
+    }
+}
+
+impl FromIterator<Version> for VersionSet {
+    fn from_iter<T: IntoIterator<Item = Version>>(iter: T) -> Self {
+        Self::Only(iter.into_iter().collect())
+    }
+}
+
+/// A statement about a package which may be true or false for a given selection of versions.
+///
+/// A positive term is satisfied if the package is selected and its version belongs to the set.
+/// A negative term is satisfied if the package is not selected, or its version does not belong
+/// to the set.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Term {
+    Positive(VersionSet),
+    Negative(VersionSet),
+}
+
+/// Relation of a [`Term`] to the intersection of other terms about the same package.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TermRelation {
+    /// All versions allowed by the other terms are allowed by this term.
+    Satisfied,
+    /// None of the versions allowed by the other terms are allowed by this term.
+    Contradicted,
+    Inconclusive,
+}
+
+impl Term {
+    /// A term which is satisfied by any selection, including not selecting the package at all.
+    pub fn any() -> Self {
+        Self::Negative(VersionSet::empty())
+    }
+
+    /// A term which is never satisfied.
+    pub fn empty() -> Self {
+        Self::Positive(VersionSet::empty())
+    }
+
+    pub fn exact(version: Version) -> Self {
+        Self::Positive(VersionSet::singleton(version))
+    }
+
+    pub fn is_positive(&self) -> bool {
+        matches!(self, Self::Positive(_))
+    }
+
+    pub fn negate(&self) -> Self {
+        match self {
+            Self::Positive(set) => Self::Negative(set.clone()),
+            Self::Negative(set) => Self::Positive(set.clone()),
+        }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Positive(a), Self::Positive(b)) => Self::Positive(a.intersection(b)),
+            (Self::Positive(p), Self::Negative(n)) | (Self::Negative(n), Self::Positive(p)) => {
+                Self::Positive(p.intersection(&n.complement()))
+            }
+            (Self::Negative(a), Self::Negative(b)) => Self::Negative(a.union(b)),
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.negate().intersection(&other.negate()).negate()
+    }
+
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        self.intersection(other) == *self
+    }
+
+    /// Check how this term relates to the intersection of all other terms known about
+    /// the package.
+    pub fn relation_with(&self, other_terms_intersection: &Self) -> TermRelation {
+        let full_intersection = self.intersection(other_terms_intersection);
+        if full_intersection == *other_terms_intersection {
+            TermRelation::Satisfied
+        } else if full_intersection == Self::empty() {
+            TermRelation::Contradicted
+        } else {
+            TermRelation::Inconclusive
+        }
+    }
+}
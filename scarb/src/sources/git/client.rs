@@ -6,16 +6,25 @@
 //! 2. Fetches and clones are always delegated to Git CLI.
 //! 3. There is no special GitHub fast-path, because in long-term we do not want to treat Git
 //!    repositories as source of super important information.
+//! 4. Tags and exact revisions can be fetched shallowly or partially, depending on
+//!    [`GitFetchMode`].
+//! 5. Submodules are fetched into their own [`GitDatabase`]s, just like top-level repositories.
 
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::path::Path;
 use std::process::Command;
 
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use camino::Utf8PathBuf;
+use itertools::Itertools;
+use tracing::debug;
+use url::Url;
 
+use scarb_ui::components::Status;
 use scarb_ui::Verbosity;
 
+use crate::core::global_config::GitFetchMode;
 use crate::core::{Config, GitReference, Package};
 use crate::flock::Filesystem;
 use crate::process::exec;
@@ -25,18 +34,20 @@ use super::canonical_url::CanonicalUrl;
 /// A Git remote repository that can be cloned into a local [`GitDatabase`].
 #[derive(Clone, Eq, PartialEq)]
 pub struct GitRemote {
-    url: CanonicalUrl,
+    /// The URL as written by the user, which is used for fetching.
+    url: Url,
+    canonical_url: CanonicalUrl,
 }
 
 impl fmt::Display for GitRemote {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.url)
+        write!(f, "{}", self.canonical_url)
     }
 }
 
 impl fmt::Debug for GitRemote {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "GitRemote({})", self.url)
+        write!(f, "GitRemote({})", self.canonical_url)
     }
 }
 
@@ -99,12 +110,25 @@ impl From<gix::ObjectId> for Rev {
 }
 
 impl GitRemote {
-    pub fn new(url: CanonicalUrl) -> Self {
-        Self { url }
+    pub fn new(url: &Url) -> Result<Self> {
+        Ok(Self {
+            url: url.clone(),
+            canonical_url: CanonicalUrl::new(url)?,
+        })
     }
 
     pub fn ident(&self) -> String {
-        self.url.ident()
+        self.canonical_url.ident()
+    }
+
+    /// Location of the [`GitDatabase`] of this remote in Scarb cache.
+    pub fn db_fs(&self, config: &Config) -> Filesystem {
+        config
+            .dirs()
+            .registry_dir()
+            .into_child("git")
+            .child("db")
+            .into_child(format!("{}.git", self.ident()))
     }
 
     #[tracing::instrument(level = "trace", skip(config))]
@@ -120,7 +144,7 @@ impl GitRemote {
         // If that can successfully load our revision then we've populated the database with the latest
         // version of `reference`, so return that database and the rev we resolve to.
         if let Some(db) = db {
-            db.fetch(self.url.as_str(), reference, config)
+            db.fetch(self.url.as_str(), reference, locked_rev, config)
                 .with_context(|| format!("failed to fetch into: {fs}"))?;
             match locked_rev {
                 Some(rev) => {
@@ -143,7 +167,7 @@ impl GitRemote {
             fs.recreate()?;
         }
         let db = GitDatabase::init_bare(self, fs)?;
-        db.fetch(self.url.as_str(), reference, config)
+        db.fetch(self.url.as_str(), reference, locked_rev, config)
             .with_context(|| format!("failed to clone into: {fs}"))?;
         let rev = match locked_rev {
             Some(rev) if db.contains(rev) => rev,
@@ -178,18 +202,89 @@ impl GitDatabase {
     }
 
     #[tracing::instrument(level = "trace", skip(config))]
-    fn fetch(&self, url: &str, reference: &GitReference, config: &Config) -> Result<()> {
+    fn fetch(
+        &self,
+        url: &str,
+        reference: &GitReference,
+        locked_rev: Option<Rev>,
+        config: &Config,
+    ) -> Result<()> {
         if !config.network_allowed() {
             bail!("cannot fetch from `{}` in offline mode", self.remote);
         }
 
+        let mode = self.fetch_mode(config.global_config()?.net.git_fetch);
+        if mode != GitFetchMode::Full {
+            if let Some(refspec) = collect_exact_refspec(reference) {
+                // Servers may refuse fetching commits which are not advertised as references,
+                // so failures of reduced fetches are not fatal.
+                match self.fetch_refspecs(url, &[refspec], false, mode, config) {
+                    Ok(()) => {
+                        let found = match locked_rev {
+                            Some(rev) => self.contains(rev),
+                            None => self.resolve(reference).is_ok(),
+                        };
+                        if found {
+                            return Ok(());
+                        }
+
+                        // The locked revision might not be reachable from the reference anymore,
+                        // so look for it in the whole repository.
+                        debug!(
+                            "revision not found after {mode:?} fetch, falling back to full fetch"
+                        );
+                    }
+                    Err(err) => {
+                        debug!("{mode:?} fetch failed, falling back to full fetch: {err:?}");
+                    }
+                }
+            }
+        }
+
         let (refspecs, fetch_tags) = collect_refspecs(reference);
+        self.fetch_refspecs(url, &refspecs, fetch_tags, GitFetchMode::Full, config)
+    }
+
+    /// Pick the fetch mode to use, so that previously fetched history is not discarded.
+    ///
+    /// Reduced fetches into a database which already contains full history would make it
+    /// shallow or partial, so they are only allowed for new databases or ones which are already
+    /// shallow or partial respectively.
+    fn fetch_mode(&self, configured: GitFetchMode) -> GitFetchMode {
+        match configured {
+            GitFetchMode::Shallow if self.repo.is_shallow() || self.is_empty() => {
+                GitFetchMode::Shallow
+            }
+            GitFetchMode::Partial if self.is_partial() || self.is_empty() => GitFetchMode::Partial,
+            _ => GitFetchMode::Full,
+        }
+    }
 
+    fn fetch_refspecs(
+        &self,
+        url: &str,
+        refspecs: &[String],
+        fetch_tags: bool,
+        mode: GitFetchMode,
+        config: &Config,
+    ) -> Result<()> {
         let mut cmd = git_command();
         cmd.arg("fetch");
         if fetch_tags {
             cmd.arg("--tags");
         }
+        match mode {
+            GitFetchMode::Shallow => {
+                cmd.arg("--depth=1");
+            }
+            GitFetchMode::Partial => {
+                cmd.arg("--filter=blob:none");
+            }
+            GitFetchMode::Full if self.repo.is_shallow() => {
+                cmd.arg("--unshallow");
+            }
+            GitFetchMode::Full => {}
+        }
         with_verbosity_flags(&mut cmd, config);
         // Handle force pushes.
         cmd.arg("--force");
@@ -202,11 +297,77 @@ impl GitDatabase {
     }
 
     pub fn copy_to(&self, fs: &Filesystem, rev: Rev, config: &Config) -> Result<GitCheckout<'_>> {
+        self.fetch_missing_blobs(rev, config)?;
         let checkout = GitCheckout::clone(self, fs, rev, config)?;
         checkout.reset(config)?;
+        checkout.update_submodules(config)?;
         Ok(checkout)
     }
 
+    /// Fetch contents of files of the given revision, which are missing in a partial database.
+    fn fetch_missing_blobs(&self, rev: Rev, config: &Config) -> Result<()> {
+        if !self.is_partial() {
+            return Ok(());
+        }
+
+        let stdout = git_output(
+            git_command()
+                .args(["rev-list", "--objects", "--missing=print", "--no-walk"])
+                .arg(rev.to_string())
+                .current_dir(self.repo.path()),
+        )?;
+        let missing = stdout
+            .lines()
+            .filter_map(|line| line.strip_prefix('?'))
+            .collect_vec();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        if !config.network_allowed() {
+            bail!(
+                "cannot fetch files of revision {rev} from `{}` in offline mode",
+                self.remote
+            );
+        }
+
+        // Pass object IDs in chunks, to stay within command line length limits.
+        for chunk in missing.chunks(512) {
+            let mut cmd = git_command();
+            cmd.args(["fetch", "--no-tags", "--no-write-fetch-head"]);
+            with_verbosity_flags(&mut cmd, config);
+            cmd.arg(self.remote.url.as_str());
+            cmd.args(chunk);
+            cmd.current_dir(self.repo.path());
+            exec(&mut cmd, config)?;
+        }
+        Ok(())
+    }
+
+    /// Checks if nothing has been fetched into this database yet.
+    fn is_empty(&self) -> bool {
+        let Ok(refs) = self.repo.references() else {
+            return false;
+        };
+        let Ok(mut refs) = refs.all() else {
+            return false;
+        };
+        refs.next().is_none()
+    }
+
+    /// Checks if this database has been fetched with a filter, so some objects may be missing.
+    fn is_partial(&self) -> bool {
+        let Ok(entries) = self.repo.path().join("objects/pack").read_dir() else {
+            return false;
+        };
+        entries.flatten().any(|entry| {
+            entry
+                .path()
+                .extension()
+                .map_or(false, |ext| ext == "promisor")
+        })
+    }
+
     pub fn contains(&self, rev: Rev) -> bool {
         let rev = rev.to_string();
         let rev = rev.as_bytes();
@@ -270,11 +431,22 @@ impl<'d> GitCheckout<'d> {
         cmd.args(["clone", "--local"]);
         with_verbosity_flags(&mut cmd, config);
         cmd.args(["--config", "core.autocrlf=false"]);
-        cmd.arg("--recurse-submodules");
         cmd.arg(db.repo.path());
         cmd.arg(&location);
         exec(&mut cmd, config)?;
 
+        // Git does not clone shallow repositories locally, and copies only branches and tags
+        // instead, so revisions fetched by their ID have to be fetched explicitly.
+        if db.repo.is_shallow() {
+            let mut cmd = git_command();
+            cmd.args(["fetch", "--depth=1"]);
+            with_verbosity_flags(&mut cmd, config);
+            cmd.arg(db.repo.path());
+            cmd.arg(rev.to_string());
+            cmd.current_dir(&location);
+            exec(&mut cmd, config)?;
+        }
+
         Ok(Self { db, location, rev })
     }
 
@@ -286,6 +458,129 @@ impl<'d> GitCheckout<'d> {
         cmd.current_dir(&self.location);
         exec(&mut cmd, config)
     }
+
+    /// Check out all submodules of this checkout, recursively.
+    ///
+    /// Submodules are checked out at revisions pinned by the checked out commit, ignoring any
+    /// branches configured in `.gitmodules`.
+    #[tracing::instrument(level = "trace", skip(config))]
+    fn update_submodules(&self, config: &Config) -> Result<()> {
+        for submodule in self.submodules()? {
+            self.update_submodule(&submodule, config)
+                .with_context(|| format!("failed to update submodule `{}`", submodule.path))?;
+        }
+        Ok(())
+    }
+
+    fn update_submodule(&self, submodule: &Submodule, config: &Config) -> Result<()> {
+        let remote = GitRemote::new(&submodule.url)?;
+        let db_fs = remote.db_fs(config);
+        let db = match GitDatabase::open(&remote, &db_fs).ok() {
+            Some(db) if db.contains(submodule.rev) => db,
+            db => {
+                // The actual error will be produced by `checkout`.
+                if config.network_allowed() {
+                    config
+                        .ui()
+                        .print(Status::new("Updating", &format!("git submodule {remote}")));
+                }
+
+                let reference = GitReference::Rev(submodule.rev.to_string().into());
+                let (db, _) =
+                    remote.checkout(&db_fs, db, &reference, Some(submodule.rev), config)?;
+                db
+            }
+        };
+
+        let fs = Filesystem::new(self.location.join(&submodule.path));
+        db.copy_to(&fs, submodule.rev, config)?;
+        Ok(())
+    }
+
+    /// List submodules of the checked out commit, reading their URLs from `.gitmodules` and
+    /// revisions from the commit tree.
+    fn submodules(&self) -> Result<Vec<Submodule>> {
+        if !self.location.join(".gitmodules").exists() {
+            return Ok(Vec::new());
+        }
+
+        // Submodules are recorded in the tree as entries of `commit` type.
+        let tree = git_output(
+            git_command()
+                .args(["ls-tree", "-r", "-z"])
+                .arg(self.rev.to_string())
+                .current_dir(&self.location),
+        )?;
+        let revs = tree
+            .split_terminator('\0')
+            .filter_map(|entry| {
+                let (info, path) = entry.split_once('\t')?;
+                match info.split(' ').collect_vec()[..] {
+                    [_, "commit", oid] => Some((path, oid)),
+                    _ => None,
+                }
+            })
+            .map(|(path, oid)| Ok((path.to_string(), Rev::try_from(oid.to_string())?)))
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        if revs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Output consists of `submodule.<name>.<key>\n<value>` entries.
+        let config = git_output(
+            git_command()
+                .args(["config", "--file", ".gitmodules", "--null", "--get-regexp"])
+                .arg(r"^submodule\..*\.(path|url)$")
+                .current_dir(&self.location),
+        )?;
+        let mut paths = HashMap::new();
+        let mut urls = HashMap::new();
+        for entry in config.split_terminator('\0') {
+            let Some((key, value)) = entry.split_once('\n') else {
+                continue;
+            };
+            let key = key.strip_prefix("submodule.").unwrap_or(key);
+            if let Some(name) = key.strip_suffix(".path") {
+                paths.insert(name, value);
+            } else if let Some(name) = key.strip_suffix(".url") {
+                urls.insert(name, value);
+            }
+        }
+
+        let parent_url = &self.db.remote.url;
+        revs.into_iter()
+            .map(|(path, rev)| {
+                let url = paths
+                    .iter()
+                    .find(|(_, p)| **p == path)
+                    .and_then(|(name, _)| urls.get(name))
+                    .ok_or_else(|| anyhow!("no URL found for submodule `{path}` in .gitmodules"))?;
+                let url = resolve_submodule_url(parent_url, url)?;
+                Ok(Submodule { path, url, rev })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+struct Submodule {
+    /// Path of the submodule, relative to the parent checkout.
+    path: String,
+    url: Url,
+    rev: Rev,
+}
+
+/// Resolve submodule URL, which can be relative to the original URL of the parent repository,
+/// like `../other.git`.
+fn resolve_submodule_url(parent_url: &Url, url: &str) -> Result<Url> {
+    let result = if url.starts_with("./") || url.starts_with("../") {
+        // Relative URLs are resolved as if the parent URL was a directory.
+        let base = format!("{}/", parent_url.as_str().trim_end_matches('/'));
+        Url::parse(&base).and_then(|base| base.join(url))
+    } else {
+        Url::parse(url)
+    };
+    result.with_context(|| format!("invalid submodule URL: {url}"))
 }
 
 /// Translate the [`GitReference`] into an actual list of Git _refspecs_ which need to be fetched.
@@ -326,6 +621,20 @@ fn collect_refspecs(reference: &GitReference) -> (Vec<String>, bool) {
     }
 }
 
+/// Translate the [`GitReference`] into a _refspec_, which can be fetched without any other
+/// references, if the reference is exact, i.e. it always points to the same commit.
+fn collect_exact_refspec(reference: &GitReference) -> Option<String> {
+    use GitReference::*;
+
+    match reference {
+        Tag(t) => Some(format!("+refs/tags/{0}:refs/remotes/origin/tags/{0}", t)),
+        Rev(rev) if gix::ObjectId::from_hex(rev.as_bytes()).is_ok() => {
+            Some(format!("+{0}:refs/commit/{0}", rev))
+        }
+        _ => None,
+    }
+}
+
 /// A wrapper over [`scarb::core::Package`] that provides functionality used to gather VCS info.
 pub struct PackageRepository {
     pkg: Package,
@@ -377,6 +686,18 @@ impl PackageRepository {
     }
 }
 
+/// Runs the Git command, capturing its standard output.
+fn git_output(cmd: &mut Command) -> Result<String> {
+    let output = cmd.output().context("could not execute process: git")?;
+    ensure!(
+        output.status.success(),
+        "process did not exit successfully: {}\n{}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    Ok(String::from_utf8(output.stdout)?)
+}
+
 fn git_command() -> Command {
     let mut cmd = Command::new("git");
 
@@ -404,3 +725,23 @@ fn with_verbosity_flags(cmd: &mut Command, config: &Config) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use url::Url;
+
+    use super::resolve_submodule_url;
+
+    #[test_case("https://github.com/example/repo", "../other.git" => "https://github.com/example/other.git"; "sibling")]
+    #[test_case("https://github.com/example/repo.git", "./sub" => "https://github.com/example/repo.git/sub"; "nested")]
+    #[test_case("https://github.com/Example/Repo/", "../Other" => "https://github.com/Example/Other"; "original case")]
+    #[test_case("https://example.com/a/b/repo", "../../c/other" => "https://example.com/a/c/other"; "many levels")]
+    #[test_case("file:///tmp/repo", "../other" => "file:///tmp/other"; "file")]
+    #[test_case("https://github.com/example/repo", "https://example.com/other.git" => "https://example.com/other.git"; "absolute")]
+    fn resolves_submodule_url(parent: &str, url: &str) -> String {
+        resolve_submodule_url(&Url::parse(parent).unwrap(), url)
+            .unwrap()
+            .to_string()
+    }
+}
@@ -1,12 +1,11 @@
 use std::{fmt, mem};
 
-use anyhow::{Context, Result};
+use anyhow::{ensure, Context, Result};
 use async_trait::async_trait;
 use tokio::sync::OnceCell;
 use tokio::task::spawn_blocking;
 use url::Url;
 
-use canonical_url::CanonicalUrl;
 use client::{GitRemote, Rev};
 use scarb_ui::components::Status;
 
@@ -15,6 +14,7 @@ use crate::core::{
     Config, GitReference, ManifestDependency, Package, PackageId, SourceId, Summary,
 };
 use crate::sources::git::client::GitDatabase;
+use crate::MANIFEST_FILE_NAME;
 
 use super::PathSource;
 
@@ -51,7 +51,6 @@ impl<'c> GitSource<'c> {
         source_id: SourceId,
         config: &'c Config,
     ) -> Result<Self> {
-        let canonical_url = CanonicalUrl::new(repo_url)?;
         let locked_rev: Option<Rev> = source_id
             .kind
             .as_git_source_spec()
@@ -60,7 +59,7 @@ impl<'c> GitSource<'c> {
         Ok(Self {
             source_id,
             config,
-            remote: GitRemote::new(canonical_url),
+            remote: GitRemote::new(repo_url)?,
             requested_reference,
             locked_rev,
             inner: OnceCell::new(),
@@ -99,7 +98,7 @@ impl<'c> GitSource<'c> {
 
             let git_fs = config.dirs().registry_dir().into_child("git");
 
-            let db_fs = git_fs.child("db").into_child(format!("{remote_ident}.git"));
+            let db_fs = remote.db_fs(config);
 
             let db = GitDatabase::open(&remote, &db_fs).ok();
             let (db, actual_rev) = match (db, locked_rev) {
@@ -137,9 +136,27 @@ impl<'c> GitSource<'c> {
                 .into_child(db.short_id_of(actual_rev)?);
 
             let checkout = db.copy_to(&checkout_fs, actual_rev, config)?;
+
+            let subdir = source_id
+                .kind
+                .as_git_source_spec()
+                .and_then(|spec| spec.subdir.clone());
             let source_id = source_id.with_precise(actual_rev.to_string())?;
 
-            let path_source = PathSource::recursive_at(&checkout.location, source_id, config);
+            let path_source = match subdir {
+                // If the subdirectory is specified, only the workspace located there is read,
+                // instead of searching the whole repository for packages.
+                Some(subdir) => {
+                    let manifest_path = checkout.location.join(&subdir).join(MANIFEST_FILE_NAME);
+                    ensure!(
+                        manifest_path.is_file(),
+                        "could not find `{MANIFEST_FILE_NAME}` in subdirectory `{subdir}` \
+                        of git repository {remote} at revision {actual_rev}"
+                    );
+                    PathSource::workspace_at(&manifest_path, source_id, config)
+                }
+                None => PathSource::recursive_at(&checkout.location, source_id, config),
+            };
 
             Ok(InnerState {
                 path_source,
@@ -1,9 +1,11 @@
 pub use git::*;
 pub use path::*;
 pub use registry::*;
+pub use replaced::*;
 pub use standard_lib::*;
 
 mod git;
 mod path;
 mod registry;
+mod replaced;
 mod standard_lib;
@@ -57,6 +57,20 @@ impl<'c> PathSource<'c> {
         }
     }
 
+    /// Reads packages of the workspace, which the manifest at `manifest_path` belongs to.
+    pub fn workspace_at(manifest_path: &Utf8Path, source_id: SourceId, config: &'c Config) -> Self {
+        Self {
+            source_id,
+            config,
+            packages: PackagesCell::new({
+                let manifest_path = manifest_path.to_path_buf();
+                move |source_id, config| {
+                    Self::fetch_workspace_at_root(&manifest_path, source_id, config)
+                }
+            }),
+        }
+    }
+
     pub fn recursive_at(path: &Utf8Path, source_id: SourceId, config: &'c Config) -> Self {
         Self {
             source_id,
@@ -97,7 +97,10 @@ impl<'c> Source for RegistrySource<'c> {
                     ManifestDependency::builder()
                         .name(index_dep.name.clone())
                         .version_req(DependencyVersionReq::from(index_dep.req.clone()))
-                        .source_id(self.source_id)
+                        .source_id(index_dep.source.unwrap_or(self.source_id))
+                        .optional(index_dep.optional)
+                        .features(index_dep.features.clone())
+                        .default_features(index_dep.default_features)
                         .build()
                 })
                 .collect();
@@ -107,7 +110,10 @@ impl<'c> Source for RegistrySource<'c> {
                 .dependencies(dependencies)
                 .target_kinds(HashSet::from_iter([TargetKind::LIB]))
                 .no_core(record.no_core)
+                .features(record.features.clone())
                 .checksum(Some(record.checksum.clone()))
+                .yanked(record.yanked)
+                .cairo_version(record.cairo_version.clone())
                 .build()
         };
 
@@ -124,17 +130,25 @@ impl<'c> Source for RegistrySource<'c> {
 
     #[tracing::instrument(level = "trace", skip(self))]
     async fn download(&self, id: PackageId) -> Result<Package> {
-        let (archive, checksum) = self
-            .client
-            .download_and_verify_with_cache(id)
-            .await
-            .with_context(|| format!("failed to download package: {id}"))?;
-
+        let (archive, checksum) = self.download_archive(id).await?;
         self.load_package(id, archive, checksum).await
     }
 }
 
 impl<'c> RegistrySource<'c> {
+    /// Download the `.tar.zst` tarball of the package and verify its checksum, without unpacking it.
+    pub async fn download_archive(&self, id: PackageId) -> Result<(FileLockGuard, Checksum)> {
+        self.client
+            .download_and_verify_with_cache(id)
+            .await
+            .with_context(|| format!("failed to download package: {id}"))
+    }
+
+    /// Open the `.tar.zst` tarball of the package if it has already been downloaded.
+    pub async fn cached_archive(&self, id: PackageId) -> Result<Option<FileLockGuard>> {
+        self.client.cached_archive(id).await
+    }
+
     /// Turn the downloaded `.tar.zst` tarball into a [`Package`].
     ///
     /// This method extracts the tarball into cache directory, and then loads it using
@@ -0,0 +1,87 @@
+use std::fmt;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+
+use crate::core::source::Source;
+use crate::core::{ManifestDependency, Package, PackageId, SourceId, Summary};
+use crate::sources::RegistrySource;
+
+/// Source which looks up packages in a registry used in place of another source.
+///
+/// Packages found in the replacement registry are reported as coming from the replaced source,
+/// so the replacement is not visible in the lockfile. Checksums of registry packages come from
+/// the replacement registry, so they are still verified against the lockfile.
+pub struct ReplacedSource<'c> {
+    to_replace: SourceId,
+    replace_with: SourceId,
+    inner: RegistrySource<'c>,
+}
+
+impl<'c> ReplacedSource<'c> {
+    pub fn new(to_replace: SourceId, replace_with: SourceId, inner: RegistrySource<'c>) -> Self {
+        Self {
+            to_replace,
+            replace_with,
+            inner,
+        }
+    }
+}
+
+impl<'c> ReplacedSource<'c> {
+    /// Report a summary found in the replacement registry as coming from the replaced source.
+    fn map_summary(&self, summary: Summary) -> Summary {
+        let mut summary = summary.map_source(self.replace_with, self.to_replace);
+        // Only registry packages are locked with checksums. Archives of other packages are
+        // created while vendoring them, so their checksums would not match the lockfile.
+        if !self.to_replace.is_registry() {
+            summary.unset_checksum();
+        }
+        summary
+    }
+}
+
+#[async_trait]
+impl<'c> Source for ReplacedSource<'c> {
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn query(&self, dependency: &ManifestDependency) -> Result<Vec<Summary>> {
+        let dependency = dependency.with_source_id(self.replace_with);
+        let summaries = self.inner.query(&dependency).await.with_context(|| {
+            format!(
+                "failed to query replaced source: {} (replaced with: {})",
+                self.to_replace, self.replace_with
+            )
+        })?;
+        Ok(summaries
+            .into_iter()
+            .map(|summary| self.map_summary(summary))
+            .collect())
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn download(&self, id: PackageId) -> Result<Package> {
+        let package = self
+            .inner
+            .download(id.with_source_id(self.replace_with))
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to download replaced package: {id} (replaced with: {})",
+                    self.replace_with
+                )
+            })?;
+
+        let mut manifest = package.manifest.clone();
+        manifest.summary = self.map_summary(manifest.summary.clone());
+        Ok(Package::new(id, package.manifest_path().into(), manifest))
+    }
+}
+
+impl<'c> fmt::Debug for ReplacedSource<'c> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReplacedSource")
+            .field("to_replace", &self.to_replace.to_string())
+            .field("replace_with", &self.replace_with.to_string())
+            .finish_non_exhaustive()
+    }
+}
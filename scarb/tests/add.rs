@@ -1,12 +1,17 @@
 #![allow(clippy::items_after_test_module)]
 
+use std::time::Duration;
+
 use assert_fs::prelude::*;
 use assert_fs::TempDir;
 use indoc::{formatdoc, indoc};
 use test_case::test_case;
 
+use scarb_test_support::command::Scarb;
 use scarb_test_support::manifest_edit::ManifestEditHarness;
-use scarb_test_support::project_builder::ProjectBuilder;
+use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
+use scarb_test_support::registry::http::HttpRegistry;
+use scarb_test_support::registry::local::LocalRegistry;
 
 #[test]
 fn registry_with_version() {
@@ -56,8 +61,125 @@ fn registry_with_caret_version_req() {
         .run();
 }
 
+/// Create a config directory, in which the default registry is replaced with the given source.
+fn config_replacing_default_registry(source: &str) -> TempDir {
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(&formatdoc! {r#"
+            [source."scarb.xyz"]
+            replace-with = "mirror"
+
+            [source.mirror]
+            {source}
+        "#})
+        .unwrap();
+    config
+}
+
+fn publish_bar(registry: &mut LocalRegistry, version: &str, cairo_version: Option<&str>) {
+    registry.publish(|t| {
+        let mut builder = ProjectBuilder::start().name("bar").version(version);
+        if let Some(cairo_version) = cairo_version {
+            builder = builder.cairo_version(cairo_version);
+        }
+        builder.build(t);
+    });
+}
+
 #[test]
 fn registry_without_version() {
+    let mut registry = LocalRegistry::create();
+    publish_bar(&mut registry, "1.0.0", None);
+    publish_bar(&mut registry, "1.2.0", None);
+    publish_bar(&mut registry, "2.0.0-rc.1", None);
+    let config = config_replacing_default_registry(&format!(
+        "local-registry = \"{}\"",
+        registry.t.path().display()
+    ));
+
+    ManifestEditHarness::offline()
+        .args(["add", "bar"])
+        .env("SCARB_CONFIG", config.path())
+        .input(indoc! {r#"
+            [package]
+            name = "hello"
+            version = "1.0.0"
+        "#})
+        .output(indoc! {r#"
+            [package]
+            name = "hello"
+            version = "1.0.0"
+
+            [dependencies]
+            bar = "^1.2.0"
+        "#})
+        .run();
+}
+
+#[test]
+fn registry_without_version_skips_yanked_and_incompatible() {
+    let mut registry = LocalRegistry::create();
+    publish_bar(&mut registry, "1.0.0", None);
+    publish_bar(&mut registry, "1.1.0", None);
+    publish_bar(&mut registry, "1.2.0", Some("99.0.0"));
+    let config = config_replacing_default_registry(&format!(
+        "local-registry = \"{}\"",
+        registry.t.path().display()
+    ));
+
+    Scarb::quick_snapbox()
+        .args(["yank", "bar@1.1.0", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .success();
+
+    ManifestEditHarness::offline()
+        .args(["add", "bar"])
+        .env("SCARB_CONFIG", config.path())
+        .input(indoc! {r#"
+            [package]
+            name = "hello"
+            version = "1.0.0"
+        "#})
+        .output(indoc! {r#"
+            [package]
+            name = "hello"
+            version = "1.0.0"
+
+            [dependencies]
+            bar = "^1.0.0"
+        "#})
+        .run();
+}
+
+#[test]
+fn registry_without_version_no_compatible_versions() {
+    let mut registry = LocalRegistry::create();
+    publish_bar(&mut registry, "1.0.0", Some("99.0.0"));
+    let config = config_replacing_default_registry(&format!(
+        "local-registry = \"{}\"",
+        registry.t.path().display()
+    ));
+
+    ManifestEditHarness::offline()
+        .args(["add", "bar"])
+        .env("SCARB_CONFIG", config.path())
+        .input(indoc! {r#"
+            [package]
+            name = "hello"
+            version = "1.0.0"
+        "#})
+        .failure()
+        .stdout_matches(indoc! {r#"
+            error: cannot find a version of package `bar` compatible with Cairo [..] in registry: registry+https://there-is-no-default-registry-yet.com/
+            help: specify package version requirement, for example: bar@1.0.0
+        "#})
+        .run();
+}
+
+#[test]
+fn registry_without_version_offline_without_cache() {
     ManifestEditHarness::offline()
         .args(["add", "dep"])
         .input(indoc! {r#"
@@ -67,7 +189,63 @@ fn registry_without_version() {
         "#})
         .failure()
         .stdout_matches(indoc! {r#"
-            error: please specify package version requirement, for example: dep@1.0.0
+            error: failed to lookup for `dep *` in registry: registry+https://there-is-no-default-registry-yet.com/
+            ...
+        "#})
+        .run();
+}
+
+#[test]
+fn registry_without_version_offline_uses_cached_index() {
+    let mut registry = HttpRegistry::serve();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .build(t);
+    });
+    let config = config_replacing_default_registry(&format!("registry = \"{registry}\""));
+    let cache = TempDir::new().unwrap();
+
+    // Populate the index cache.
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .dep("bar", Dep.version("1"))
+        .build(&t);
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .env("SCARB_CACHE", cache.path())
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success();
+
+    // Versions published later are not visible in offline mode.
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.1.0")
+            .build(t);
+    });
+
+    ManifestEditHarness::offline()
+        .args(["add", "bar"])
+        .env("SCARB_CONFIG", config.path())
+        .env("SCARB_CACHE", cache.path())
+        .input(indoc! {r#"
+            [package]
+            name = "hello"
+            version = "1.0.0"
+        "#})
+        .output(indoc! {r#"
+            [package]
+            name = "hello"
+            version = "1.0.0"
+
+            [dependencies]
+            bar = "^1.0.0"
         "#})
         .run();
 }
@@ -199,7 +377,10 @@ fn runs_resolver_if_network_is_allowed() {
         "#})
         .failure()
         .stdout_matches(indoc! {r#"
-            error: cannot find package dep
+            error: Version solving failed:
+            - dep ^1.0.0 ([..]) is required by hello v1.0.0 (workspace member), but no versions of dep match
+
+            help: change the `dep` dependency requirement in the Scarb.toml of `hello`, so that it matches an available version
         "#})
         .run();
 }
@@ -69,18 +69,18 @@ fn compile_with_syntax_error() {
         .build(&t);
 
     Scarb::quick_snapbox()
-        .arg("build") // TODO(#137): Change build to check for faster and lighter test.
+        .arg("check")
         .current_dir(&t)
         .assert()
         .code(1)
         .stdout_matches(indoc! {r#"
-               Compiling hello v0.1.0 ([..]Scarb.toml)
+                Checking hello v0.1.0 ([..]Scarb.toml)
             error: Skipped tokens. Expected: Const/Enum/ExternFunction/ExternType/Function/Impl/InlineMacro/Module/Struct/Trait/TypeAlias/Use or an attribute.
              --> [..]/lib.cairo:1:1
             not_a_keyword
             ^***********^
 
-            error: could not compile `hello` due to previous error
+            error: could not check `hello` due to previous error
         "#});
 }
 
@@ -106,6 +106,38 @@ fn compile_with_syntax_error_json() {
         "#});
 }
 
+#[test]
+fn compile_with_syntax_error_message_format_json() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("0.1.0")
+        .lib_cairo("not_a_keyword")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .args(["--message-format", "json", "check"])
+        .current_dir(&t)
+        .assert()
+        .code(1)
+        .stdout_matches(indoc! {r#"
+            {"status":"checking","message":"hello v0.1.0 ([..]Scarb.toml)"}
+            {"type":"diagnostic","severity":"error","message":"Skipped tokens. Expected: Const/Enum/ExternFunction/ExternType/Function/Impl/InlineMacro/Module/Struct/Trait/TypeAlias/Use or an attribute.","file":"[..]/lib.cairo","spans":[{"start":{"line":1,"column":1},"end":{"line":1,"column":13}}],"package_id":"hello 0.1.0 (path+file:[..]Scarb.toml)","compilation_unit_id":"hello-[..]","notes":[]}
+            {"type":"error","message":"could not check `hello` due to previous error"}
+        "#});
+
+    Scarb::quick_snapbox()
+        .args(["--json", "--message-format", "json", "build"])
+        .current_dir(&t)
+        .assert()
+        .code(1)
+        .stdout_matches(indoc! {r#"
+            {"status":"compiling","message":"hello v0.1.0 ([..]Scarb.toml)"}
+            {"type":"diagnostic","severity":"error",[..]}
+            {"type":"error","message":"could not compile `hello` due to previous error"}
+        "#});
+}
+
 #[test]
 fn compile_without_manifest() {
     let t = TempDir::new().unwrap();
@@ -276,7 +308,7 @@ fn compile_with_incompatible_cairo_version() {
         )
         .unwrap();
     Scarb::quick_snapbox()
-        .arg("build") // TODO(#137): Change build to check for faster and lighter test.
+        .arg("check")
         .current_dir(&t)
         .assert()
         .code(1)
@@ -821,3 +853,326 @@ fn warnings_can_be_disallowed() {
         error: could not compile [..] due to previous error
         "#});
 }
+
+#[test]
+fn incremental_build() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("dep")
+        .version("1.0.0")
+        .lib_cairo("fn f() -> felt252 { 1 }")
+        .build(&t.child("dep"));
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", Dep.path("dep"))
+        .lib_cairo("fn g() -> felt252 { dep::f() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..] Compiling hello v1.0.0 ([..]Scarb.toml)
+            [..]  Finished release target(s) in [..]
+        "#});
+    t.child("target/dev/.fingerprint")
+        .assert(predicates::path::is_dir());
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..]     Fresh hello v1.0.0 ([..]Scarb.toml)
+            [..]  Finished release target(s) in [..]
+        "#});
+
+    // Changes in dependency sources make the unit dirty.
+    t.child("dep/src/lib.cairo")
+        .write_str("fn f() -> felt252 { 2 }")
+        .unwrap();
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..] Compiling hello v1.0.0 ([..]Scarb.toml)
+            [..]  Finished release target(s) in [..]
+        "#});
+
+    // Each profile has separate fingerprints.
+    Scarb::quick_snapbox()
+        .args(["--release", "build"])
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..] Compiling hello v1.0.0 ([..]Scarb.toml)
+            [..]  Finished release target(s) in [..]
+        "#});
+}
+
+#[test]
+fn failed_build_is_not_fresh() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .lib_cairo("fn f() -> felt252 { 1 }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    t.child("src/lib.cairo")
+        .write_str("fn f() -> felt252 { y }")
+        .unwrap();
+
+    for _ in 0..2 {
+        Scarb::quick_snapbox()
+            .arg("build")
+            .current_dir(&t)
+            .assert()
+            .failure()
+            .stdout_matches(indoc! {r#"
+                [..] Compiling hello v1.0.0 ([..]Scarb.toml)
+                ...
+                error: could not compile `hello` due to previous error
+            "#});
+    }
+}
+
+#[test]
+fn deleted_artifact_is_rebuilt() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .lib_cairo("fn f() -> felt252 { 1 }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    fs::remove_file(t.child("target/dev/hello.sierra.json")).unwrap();
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..] Compiling hello v1.0.0 ([..]Scarb.toml)
+            [..]  Finished release target(s) in [..]
+        "#});
+    t.child("target/dev/hello.sierra.json")
+        .assert(predicates::path::exists());
+}
+
+#[test]
+fn warnings_are_replayed_for_fresh_units() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .lib_cairo(indoc! {r#"
+        fn hello() -> felt252 {
+            let a = 41;
+            let b = 42;
+            b
+        }
+    "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..]     Fresh hello v1.0.0 ([..]Scarb.toml)
+        warn: Unused variable. Consider ignoring by prefixing with `_`.
+         --> [..]lib.cairo:2:9
+            let a = 41;
+                ^
+
+            Finished release target(s) in [..] seconds
+        "#});
+}
+
+fn workspace_with_members(t: &TempDir, members: &[(&str, &str)]) {
+    for (name, code) in members {
+        ProjectBuilder::start()
+            .name(*name)
+            .version("1.0.0")
+            .lib_cairo(*code)
+            .build(&t.child(name));
+    }
+    let mut workspace = WorkspaceBuilder::start();
+    for (name, _) in members {
+        workspace = workspace.add_member(*name);
+    }
+    workspace.build(t);
+}
+
+#[test]
+fn parallel_build_output_is_ordered() {
+    let t = TempDir::new().unwrap();
+    workspace_with_members(
+        &t,
+        &[
+            ("first", "fn f() -> felt252 { 1 }"),
+            ("second", "fn f() -> felt252 { 2 }"),
+            ("third", "fn f() -> felt252 { 3 }"),
+        ],
+    );
+
+    Scarb::quick_snapbox()
+        .args(["build", "--jobs", "3"])
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..] Compiling first v1.0.0 ([..]Scarb.toml)
+            [..] Compiling second v1.0.0 ([..]Scarb.toml)
+            [..] Compiling third v1.0.0 ([..]Scarb.toml)
+            [..]  Finished release target(s) in [..]
+        "#});
+}
+
+#[test]
+fn build_stops_after_first_failure() {
+    let t = TempDir::new().unwrap();
+    workspace_with_members(
+        &t,
+        &[
+            ("first", "not_a_keyword"),
+            ("second", "fn f() -> felt252 { 2 }"),
+            ("third", "not_a_keyword"),
+        ],
+    );
+
+    Scarb::quick_snapbox()
+        .args(["build", "-j", "1"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+               Compiling first v1.0.0 ([..]Scarb.toml)
+            error: Skipped tokens. Expected: Const/Enum/ExternFunction/ExternType/Function/Impl/InlineMacro/Module/Struct/Trait/TypeAlias/Use or an attribute.
+             --> [..]/lib.cairo:1:1
+            not_a_keyword
+            ^***********^
+
+            error: could not compile `first` due to previous error
+        "#});
+}
+
+#[test]
+fn keep_going_reports_all_failures() {
+    let t = TempDir::new().unwrap();
+    workspace_with_members(
+        &t,
+        &[
+            ("first", "not_a_keyword"),
+            ("second", "fn f() -> felt252 { 2 }"),
+            ("third", "not_a_keyword"),
+        ],
+    );
+
+    for jobs in ["1", "3"] {
+        Scarb::quick_snapbox()
+            .args(["build", "--keep-going", "--jobs", jobs])
+            .current_dir(&t)
+            .assert()
+            .failure()
+            .stdout_matches(indoc! {r#"
+                   Compiling first v1.0.0 ([..]Scarb.toml)
+                error: Skipped tokens. Expected: Const/Enum/ExternFunction/ExternType/Function/Impl/InlineMacro/Module/Struct/Trait/TypeAlias/Use or an attribute.
+                 --> [..]/lib.cairo:1:1
+                not_a_keyword
+                ^***********^
+
+                   Compiling second v1.0.0 ([..]Scarb.toml)
+                   Compiling third v1.0.0 ([..]Scarb.toml)
+                error: Skipped tokens. Expected: Const/Enum/ExternFunction/ExternType/Function/Impl/InlineMacro/Module/Struct/Trait/TypeAlias/Use or an attribute.
+                 --> [..]/lib.cairo:1:1
+                not_a_keyword
+                ^***********^
+
+                error: could not compile `first` due to previous error
+                error: could not compile `third` due to previous error
+            "#});
+    }
+}
+
+#[test]
+fn build_jobs_from_global_config() {
+    let t = TempDir::new().unwrap();
+    workspace_with_members(
+        &t,
+        &[
+            ("first", "fn f() -> felt252 { 1 }"),
+            ("second", "fn f() -> felt252 { 2 }"),
+        ],
+    );
+
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(indoc! {r#"
+            [build]
+            jobs = 2
+        "#})
+        .unwrap();
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..] Compiling first v1.0.0 ([..]Scarb.toml)
+            [..] Compiling second v1.0.0 ([..]Scarb.toml)
+            [..]  Finished release target(s) in [..]
+        "#});
+
+    config
+        .child("config.toml")
+        .write_str(indoc! {r#"
+            [build]
+            jobs = 0
+        "#})
+        .unwrap();
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+            ...
+            [..]failed to parse `[..]config.toml`: `build.jobs` must be greater than 0
+            ...
+        "#});
+}
@@ -0,0 +1,127 @@
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use indoc::indoc;
+use predicates::prelude::*;
+
+use scarb_test_support::command::Scarb;
+use scarb_test_support::project_builder::ProjectBuilder;
+use scarb_test_support::workspace_builder::WorkspaceBuilder;
+
+#[test]
+fn check_simple() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("check")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..] Checking hello v1.0.0 ([..]Scarb.toml)
+            [..] Finished checking release target(s) in [..]
+        "#});
+
+    t.child("target/dev/hello.sierra.json")
+        .assert(predicate::path::missing());
+}
+
+#[test]
+fn check_reports_semantic_errors() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .lib_cairo("fn f() -> felt252 { missing() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("check")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+            [..] Checking hello v1.0.0 ([..]Scarb.toml)
+            error: [..]
+             --> [..]/lib.cairo:1:21
+            ...
+            error: could not check `hello` due to previous error
+        "#});
+}
+
+#[test]
+fn check_tests() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .lib_cairo(indoc! {r#"
+            fn f() -> felt252 { 42 }
+
+            #[cfg(test)]
+            mod tests {
+                fn g() -> felt252 { missing() }
+            }
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("check")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .args(["check", "--test"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+            [..] Checking test(hello_unittest) hello v1.0.0 ([..]Scarb.toml)
+            ...
+            error: could not check `hello` due to previous error
+        "#});
+}
+
+#[test]
+fn check_selected_packages() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("first")
+        .version("1.0.0")
+        .build(&t.child("first"));
+    ProjectBuilder::start()
+        .name("second")
+        .version("1.0.0")
+        .lib_cairo("not_a_keyword")
+        .build(&t.child("second"));
+    WorkspaceBuilder::start()
+        .add_member("first")
+        .add_member("second")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .args(["check", "--package", "first"])
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..] Checking first v1.0.0 ([..]Scarb.toml)
+            [..] Finished checking release target(s) in [..]
+        "#});
+
+    Scarb::quick_snapbox()
+        .args(["check", "--workspace"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+            [..] Checking first v1.0.0 ([..]Scarb.toml)
+            [..] Checking second v1.0.0 ([..]Scarb.toml)
+            ...
+            error: could not check `second` due to previous error
+        "#});
+}
@@ -0,0 +1,411 @@
+use std::collections::BTreeMap;
+
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use indoc::indoc;
+use itertools::Itertools;
+
+use scarb_metadata::{Cfg, Metadata, PackageMetadata};
+use scarb_test_support::command::{CommandExt, Scarb};
+use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
+
+fn metadata(t: &TempDir, args: &[&str]) -> Metadata {
+    Scarb::quick_snapbox()
+        .arg("--json")
+        .arg("metadata")
+        .arg("--format-version")
+        .arg("1")
+        .args(args)
+        .current_dir(t)
+        .stdout_json::<Metadata>()
+}
+
+fn packages_by_name(meta: &Metadata) -> BTreeMap<String, &PackageMetadata> {
+    meta.packages
+        .iter()
+        .map(|p| (p.name.clone(), p))
+        .collect::<BTreeMap<_, _>>()
+}
+
+fn feature_cfgs(meta: &Metadata, package: &str) -> Vec<String> {
+    let unit = meta
+        .compilation_units
+        .iter()
+        .find(|cu| cu.package.repr.starts_with(&format!("{package} ")) && cu.target.kind == "lib")
+        .unwrap();
+    unit.cfg
+        .iter()
+        .filter_map(|cfg| match cfg {
+            Cfg::KV(key, value) if key == "feature" => Some(value.clone()),
+            _ => None,
+        })
+        .sorted()
+        .collect()
+}
+
+fn components(meta: &Metadata, package: &str) -> Vec<String> {
+    let unit = meta
+        .compilation_units
+        .iter()
+        .find(|cu| cu.package.repr.starts_with(&format!("{package} ")) && cu.target.kind == "lib")
+        .unwrap();
+    unit.components
+        .iter()
+        .map(|c| c.name.clone())
+        .sorted()
+        .collect()
+}
+
+#[test]
+fn features_become_cfg_options() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .manifest_extra(indoc! {r#"
+            [features]
+            default = ["x"]
+            x = []
+            y = ["x"]
+            z = []
+        "#})
+        .build(&t);
+
+    let meta = metadata(&t, &[]);
+    assert_eq!(
+        feature_cfgs(&meta, "hello"),
+        vec!["hello/default", "hello/x"]
+    );
+
+    let meta = metadata(&t, &["--features", "y"]);
+    assert_eq!(
+        feature_cfgs(&meta, "hello"),
+        vec!["hello/default", "hello/x", "hello/y"]
+    );
+
+    let meta = metadata(&t, &["--no-default-features", "--features", "z"]);
+    assert_eq!(feature_cfgs(&meta, "hello"), vec!["hello/z"]);
+
+    let meta = metadata(&t, &["--all-features"]);
+    assert_eq!(
+        feature_cfgs(&meta, "hello"),
+        vec!["hello/default", "hello/x", "hello/y", "hello/z"]
+    );
+
+    let packages = packages_by_name(&meta);
+    let hello = packages["hello"];
+    assert_eq!(
+        hello.features,
+        BTreeMap::from_iter([
+            ("default".to_string(), vec!["x".to_string()]),
+            ("x".to_string(), vec![]),
+            ("y".to_string(), vec!["x".to_string()]),
+            ("z".to_string(), vec![]),
+        ])
+    );
+    assert_eq!(hello.enabled_features, vec!["default", "x", "y", "z"]);
+}
+
+#[test]
+fn feature_enables_code() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .lib_cairo(indoc! {r#"
+            #[cfg(feature: 'hello/x')]
+            fn f() -> felt252 { 42 }
+
+            fn main() -> felt252 { f() }
+        "#})
+        .manifest_extra(indoc! {r#"
+            [features]
+            x = []
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .failure();
+
+    Scarb::quick_snapbox()
+        .args(["build", "--features", "x"])
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..] Compiling hello v1.0.0 ([..]Scarb.toml)
+            [..]  Finished release target(s) in [..]
+        "#});
+}
+
+#[test]
+fn dependency_feature_enables_dependency_code() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("dep")
+        .lib_cairo(indoc! {r#"
+            #[cfg(feature: 'dep/x')]
+            fn f() -> felt252 { 42 }
+        "#})
+        .manifest_extra(indoc! {r#"
+            [features]
+            x = []
+        "#})
+        .build(&t.child("dep"));
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", Dep.path("../dep").features(["x"]))
+        .lib_cairo(indoc! {r#"
+            fn main() -> felt252 { dep::f() }
+        "#})
+        .manifest_extra(indoc! {r#"
+            [features]
+            y = []
+        "#})
+        .build(&t.child("hello"));
+    let hello = t.child("hello");
+
+    let meta = metadata(&hello, &["--features", "y"]);
+    assert_eq!(feature_cfgs(&meta, "hello"), vec!["dep/x", "hello/y"]);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&hello)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..] Compiling hello v1.0.0 ([..]Scarb.toml)
+            [..]  Finished release target(s) in [..]
+        "#});
+}
+
+#[test]
+fn same_feature_of_other_package_stays_disabled() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("dep")
+        .lib_cairo(indoc! {r#"
+            #[cfg(feature: 'dep/x')]
+            fn f() -> felt252 { missing() }
+
+            fn g() -> felt252 { 1 }
+        "#})
+        .manifest_extra(indoc! {r#"
+            [features]
+            x = []
+        "#})
+        .build(&t.child("dep"));
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", Dep.path("../dep"))
+        .lib_cairo(indoc! {r#"
+            #[cfg(feature: 'hello/x')]
+            fn f() -> felt252 { dep::g() }
+
+            fn main() -> felt252 { f() }
+        "#})
+        .manifest_extra(indoc! {r#"
+            [features]
+            x = []
+        "#})
+        .build(&t.child("hello"));
+    let hello = t.child("hello");
+
+    let meta = metadata(&hello, &["--features", "x"]);
+    assert_eq!(feature_cfgs(&meta, "hello"), vec!["hello/x"]);
+
+    Scarb::quick_snapbox()
+        .args(["build", "--features", "x"])
+        .current_dir(&hello)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+            [..] Compiling hello v1.0.0 ([..]Scarb.toml)
+            [..]  Finished release target(s) in [..]
+        "#});
+}
+
+#[test]
+fn optional_dependency_is_activated_by_feature() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start().name("dep").build(&t.child("dep"));
+    ProjectBuilder::start()
+        .name("hello")
+        .dep("dep", Dep.path("../dep").optional())
+        .manifest_extra(indoc! {r#"
+            [features]
+            with_dep = ["dep"]
+        "#})
+        .build(&t.child("hello"));
+    let hello = t.child("hello");
+
+    let meta = Scarb::quick_snapbox()
+        .args(["--json", "metadata", "--format-version", "1"])
+        .current_dir(&hello)
+        .stdout_json::<Metadata>();
+    assert!(!packages_by_name(&meta).contains_key("dep"));
+    assert_eq!(components(&meta, "hello"), vec!["core", "hello"]);
+
+    let meta = Scarb::quick_snapbox()
+        .args(["--json", "metadata", "--format-version", "1"])
+        .args(["--features", "with_dep"])
+        .current_dir(&hello)
+        .stdout_json::<Metadata>();
+    assert!(packages_by_name(&meta).contains_key("dep"));
+    assert_eq!(components(&meta, "hello"), vec!["core", "dep", "hello"]);
+    assert_eq!(
+        feature_cfgs(&meta, "hello"),
+        vec!["hello/dep", "hello/with_dep"]
+    );
+
+    // Optional dependencies can also be enabled directly by their names.
+    let meta = Scarb::quick_snapbox()
+        .args(["--json", "metadata", "--format-version", "1"])
+        .args(["--features", "dep"])
+        .current_dir(&hello)
+        .stdout_json::<Metadata>();
+    assert_eq!(components(&meta, "hello"), vec!["core", "dep", "hello"]);
+
+    // Optional dependencies are locked regardless of enabled features.
+    hello
+        .child("Scarb.lock")
+        .assert(predicates::str::contains(r#"name = "dep""#));
+}
+
+#[test]
+fn dependency_features_are_unified() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("common")
+        .manifest_extra(indoc! {r#"
+            [features]
+            default = ["a"]
+            a = []
+            b = []
+            c = []
+        "#})
+        .build(&t.child("common"));
+    ProjectBuilder::start()
+        .name("first")
+        .dep("common", Dep.path("../common").default_features(false))
+        .build(&t.child("first"));
+    ProjectBuilder::start()
+        .name("second")
+        .dep("common", Dep.path("../common").features(["b"]))
+        .manifest_extra(indoc! {r#"
+            [features]
+            extra = ["common/c"]
+        "#})
+        .build(&t.child("second"));
+    ProjectBuilder::start()
+        .name("hello")
+        .dep("first", Dep.path("../first"))
+        .dep("second", Dep.path("../second").features(["extra"]))
+        .build(&t.child("hello"));
+
+    let meta = Scarb::quick_snapbox()
+        .args(["--json", "metadata", "--format-version", "1"])
+        .current_dir(t.child("hello"))
+        .stdout_json::<Metadata>();
+    let packages = packages_by_name(&meta);
+    assert_eq!(
+        packages["common"].enabled_features,
+        vec!["a", "b", "c", "default"]
+    );
+    assert_eq!(packages["second"].enabled_features, vec!["extra"]);
+    assert!(packages["first"].enabled_features.is_empty());
+}
+
+#[test]
+fn unknown_feature() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .manifest_extra(indoc! {r#"
+            [features]
+            x = []
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .args(["build", "--features", "y"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+            error: no workspace member has feature `y`
+        "#});
+
+    // Invalid invocations must not write the lockfile.
+    t.child("Scarb.lock").assert(predicates::path::missing());
+}
+
+#[test]
+fn unknown_dependency_feature() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start().name("dep").build(&t.child("dep"));
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", Dep.path("../dep").features(["x"]))
+        .build(&t.child("hello"));
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(t.child("hello"))
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+            error: package `dep v[..] ([..])` does not have feature `x`
+        "#});
+}
+
+#[test]
+fn feature_includes_unknown_value() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .manifest_extra(indoc! {r#"
+            [features]
+            x = ["y"]
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+            error: failed to parse manifest at: [..]/Scarb.toml
+
+            Caused by:
+                feature `x` includes `y`, which is neither a feature nor an optional dependency
+        "#});
+}
+
+#[test]
+fn optional_dev_dependency() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start().name("dep").build(&t.child("dep"));
+    ProjectBuilder::start()
+        .name("hello")
+        .dev_dep("dep", Dep.path("../dep").optional())
+        .build(&t.child("hello"));
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(t.child("hello"))
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+            error: failed to parse manifest at: [..]/Scarb.toml
+
+            Caused by:
+                dev-dependency (dep) cannot be optional
+        "#});
+}
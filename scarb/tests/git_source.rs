@@ -1,15 +1,17 @@
 use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 use assert_fs::prelude::*;
 use assert_fs::TempDir;
 use gix::refs::transaction::PreviousValue;
-use indoc::indoc;
+use indoc::{formatdoc, indoc};
 use scarb_metadata::Metadata;
 
 use scarb_test_support::command::{CommandExt, Scarb};
 use scarb_test_support::fsx::ChildPathEx;
 use scarb_test_support::gitx;
+use scarb_test_support::gitx::GitProject;
 use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
 
 #[test]
@@ -198,7 +200,147 @@ fn fetch_with_short_ssh_git() {
         "#});
 }
 
-// TODO(#133): Add tests with submodules.
+#[test]
+fn submodule() {
+    let sub = gitx::new("sub", |t| {
+        ProjectBuilder::start()
+            .name("sub")
+            .lib_cairo("fn hello() -> felt252 { 11111111111101 }")
+            .build(&t)
+    });
+
+    let dep = gitx::new("dep", |t| {
+        ProjectBuilder::start()
+            .name("dep")
+            .dep("sub", Dep.path("vendor/sub"))
+            .lib_cairo("fn hello() -> felt252 { sub::hello() }")
+            .build(&t)
+    });
+    dep.add_submodule("vendor/sub", &sub.url(), &sub);
+
+    // Submodule is pinned to the revision from the time it has been added.
+    sub.change_file("src/lib.cairo", "fn hello() -> felt252 { 11111111111102 }");
+
+    let cache_dir = TempDir::new().unwrap();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", &dep)
+        .lib_cairo("fn world() -> felt252 { dep::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .env("SCARB_CACHE", cache_dir.path())
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..]  Updating git repository file://[..]/dep
+        [..]  Updating git submodule file://[..]/sub
+        [..] Compiling hello v1.0.0 ([..])
+        [..]  Finished release target(s) in [..]
+        "#});
+
+    t.child("target/dev/hello.sierra.json")
+        .assert(predicates::str::contains("11111111111101"));
+
+    // Submodule databases are stored next to the database of the parent repository.
+    assert_eq!(
+        fs::read_dir(cache_dir.child("registry/git/db"))
+            .unwrap()
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn submodule_relative_url() {
+    let sub = gitx::new("sub", |t| {
+        ProjectBuilder::start()
+            .name("sub")
+            .lib_cairo("fn hello() -> felt252 { 42 }")
+            .build(&t)
+    });
+
+    let dep = gitx::new("dep", |t| {
+        ProjectBuilder::start()
+            .name("dep")
+            .dep("sub", Dep.path("vendor/sub"))
+            .lib_cairo("fn hello() -> felt252 { sub::hello() }")
+            .build(&t)
+    });
+    // Both repositories live in separate temporary directories, next to each other.
+    let sub_dir = sub.t.path().file_name().unwrap().to_str().unwrap();
+    dep.add_submodule("vendor/sub", &format!("../../{sub_dir}/sub"), &sub);
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", &dep)
+        .lib_cairo("fn world() -> felt252 { dep::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..]  Updating git repository file://[..]/dep
+        [..]  Updating git submodule file://[..]/sub
+        [..] Compiling hello v1.0.0 ([..])
+        [..]  Finished release target(s) in [..]
+        "#});
+}
+
+#[test]
+fn nested_submodules() {
+    let inner = gitx::new("inner", |t| {
+        ProjectBuilder::start()
+            .name("inner")
+            .lib_cairo("fn hello() -> felt252 { 42 }")
+            .build(&t)
+    });
+
+    let sub = gitx::new("sub", |t| {
+        t.child("README.md").write_str("Vendored code.").unwrap();
+    });
+    sub.add_submodule("inner", &inner.url(), &inner);
+
+    let dep = gitx::new("dep", |t| {
+        ProjectBuilder::start()
+            .name("dep")
+            .dep("inner", Dep.path("vendor/sub/inner"))
+            .lib_cairo("fn hello() -> felt252 { inner::hello() }")
+            .build(&t)
+    });
+    dep.add_submodule("vendor/sub", &sub.url(), &sub);
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", &dep)
+        .lib_cairo("fn world() -> felt252 { dep::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..]  Updating git repository file://[..]/dep
+        [..]  Updating git submodule file://[..]/sub
+        [..]  Updating git submodule file://[..]/inner
+        [..] Compiling hello v1.0.0 ([..])
+        [..]  Finished release target(s) in [..]
+        "#});
+}
 
 #[test]
 fn stale_cached_version() {
@@ -244,7 +386,7 @@ fn stale_cached_version() {
         .assert()
         .success()
         .stdout_matches(indoc! {r#"
-        [..] Compiling hello v1.0.0 ([..])
+        [..]     Fresh hello v1.0.0 ([..])
         [..]  Finished release target(s) in [..]
         "#});
 
@@ -322,6 +464,7 @@ fn stale_cached_version_update() {
         .success()
         .stdout_matches(indoc! {r#"
         [..]  Updating git repository file://[..]/dep
+        [..]  Updating dep v[..] (git+file://[..]/dep#[..]) -> #[..]
         "#});
 
     Scarb::quick_snapbox()
@@ -459,3 +602,442 @@ fn transitive_path_dep() {
     assert!(pkgs["dep0"].starts_with("git+"));
     assert!(pkgs["dep1"].starts_with("git+"));
 }
+
+#[test]
+fn subdir() {
+    let git_dep = gitx::new("dep1", |t| {
+        ProjectBuilder::start()
+            .name("dep1")
+            .lib_cairo("fn hello() -> felt252 { 42 }")
+            .build(&t.child("old"));
+        ProjectBuilder::start()
+            .name("dep1")
+            .lib_cairo("fn hello() -> felt252 { 53 }\nfn world() -> felt252 { 0 }")
+            .build(&t.child("crates/new"));
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep1", git_dep.with("subdir", "./crates/new/"))
+        .lib_cairo("fn world() -> felt252 { dep1::world() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..]  Updating git repository file://[..]/dep1
+        [..] Compiling hello v1.0.0 ([..])
+        [..]  Finished release target(s) in [..]
+        "#});
+
+    let lockfile = t.child("Scarb.lock").read_to_string();
+    assert!(lockfile.contains("?subdir=crates%2Fnew#"));
+
+    // Locked source is reused on subsequent runs.
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Compiling hello v1.0.0 ([..])
+        [..]  Finished release target(s) in [..]
+        "#});
+}
+
+#[test]
+fn subdir_without_manifest() {
+    let git_dep = gitx::new("dep1", |t| {
+        ProjectBuilder::start()
+            .name("dep1")
+            .lib_cairo("fn hello() -> felt252 { 42 }")
+            .build(&t);
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep1", git_dep.with("subdir", "src"))
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        [..]  Updating git repository file://[..]/dep1
+        error: could not find `Scarb.toml` in subdirectory `src` of git repository file://[..]/dep1 at revision [..]
+        "#});
+}
+
+#[test]
+fn subdir_outside_repository() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep(
+            "dep1",
+            Dep.with("git", "https://github.com/example/dep1.git")
+                .with("subdir", "crates/../../dep2"),
+        )
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: failed to parse manifest at: [..]/Scarb.toml
+
+        Caused by:
+            0: dependency (dep1) has invalid `subdir`: crates/../../dep2
+            1: path must not point outside the repository
+        "#});
+}
+
+#[test]
+fn subdir_without_git() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep1", Dep.version("1.0.0").with("subdir", "crates/dep1"))
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: failed to parse manifest at: [..]/Scarb.toml
+
+        Caused by:
+            dependency (dep1) is non-Git, but provides `subdir`
+        "#});
+}
+
+fn git_db(cache_dir: &TempDir) -> PathBuf {
+    let dbs = fs::read_dir(cache_dir.child("registry/git/db"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(dbs.len(), 1);
+    dbs.into_iter().next().unwrap()
+}
+
+fn commits_count(db: &Path) -> usize {
+    let output = gitx::git_command()
+        .args(["rev-list", "--all", "--count"])
+        .current_dir(db)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap()
+}
+
+fn three_commits_dep() -> GitProject {
+    let dep = gitx::new("dep", |t| {
+        ProjectBuilder::start()
+            .name("dep")
+            .lib_cairo("fn hello() -> felt252 { 11111111111101 }")
+            .build(&t)
+    });
+    dep.change_file("src/lib.cairo", "fn hello() -> felt252 { 11111111111102 }");
+    dep.tag("v2");
+    dep.change_file("src/lib.cairo", "fn hello() -> felt252 { 11111111111103 }");
+    dep
+}
+
+fn git_fetch_config(mode: &str) -> TempDir {
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(&formatdoc! {r#"
+            [net]
+            git-fetch = "{mode}"
+        "#})
+        .unwrap();
+    config
+}
+
+#[test]
+fn shallow_fetch_tag() {
+    let remote = three_commits_dep().bare_clone();
+    let cache_dir = TempDir::new().unwrap();
+    let config = git_fetch_config("shallow");
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", remote.with("tag", "v2"))
+        .lib_cairo("fn world() -> felt252 { dep::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .env("SCARB_CACHE", cache_dir.path())
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    t.child("target/dev/hello.sierra.json")
+        .assert(predicates::str::contains("11111111111102"));
+
+    let db = git_db(&cache_dir);
+    assert!(db.join("shallow").exists());
+    assert_eq!(commits_count(&db), 1);
+}
+
+#[test]
+fn shallow_fetch_exact_rev() {
+    let dep = three_commits_dep();
+    let rev = dep.rev_parse("HEAD~2");
+    let remote = dep.bare_clone();
+    let cache_dir = TempDir::new().unwrap();
+    let config = git_fetch_config("shallow");
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", remote.with("rev", rev))
+        .lib_cairo("fn world() -> felt252 { dep::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .env("SCARB_CACHE", cache_dir.path())
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    t.child("target/dev/hello.sierra.json")
+        .assert(predicates::str::contains("11111111111101"));
+
+    let db = git_db(&cache_dir);
+    assert!(db.join("shallow").exists());
+    assert_eq!(commits_count(&db), 1);
+}
+
+#[test]
+fn shallow_fetch_falls_back_to_full_fetch_for_inexact_rev() {
+    let dep = three_commits_dep();
+    let short_rev = dep.rev_parse("HEAD~2")[..8].to_string();
+    let remote = dep.bare_clone();
+    let cache_dir = TempDir::new().unwrap();
+    let config = git_fetch_config("shallow");
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", remote.with("tag", "v2"))
+        .lib_cairo("fn world() -> felt252 { dep::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CACHE", cache_dir.path())
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    assert!(git_db(&cache_dir).join("shallow").exists());
+
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", remote.with("rev", short_rev))
+        .lib_cairo("fn world() -> felt252 { dep::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .env("SCARB_CACHE", cache_dir.path())
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    t.child("target/dev/hello.sierra.json")
+        .assert(predicates::str::contains("11111111111101"));
+
+    let db = git_db(&cache_dir);
+    assert!(!db.join("shallow").exists());
+    assert_eq!(commits_count(&db), 3);
+}
+
+#[test]
+fn shallow_fetch_falls_back_to_full_fetch_for_missing_locked_rev() {
+    let dep = three_commits_dep();
+    let remote = dep.bare_clone();
+
+    let config = git_fetch_config("shallow");
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", remote.with("tag", "v2"))
+        .lib_cairo("fn world() -> felt252 { dep::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    // Move the tag, so that the locked revision is not the tagged commit anymore.
+    dep.git(["tag", "-f", "-a", "v2", "-m", "moved tag"]);
+    dep.git(["push", "--force", remote.url().as_str(), "refs/tags/v2"]);
+
+    let cache_dir = TempDir::new().unwrap();
+    Scarb::quick_snapbox()
+        .arg("build")
+        .env("SCARB_CACHE", cache_dir.path())
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    t.child("target/dev/hello.sierra.json")
+        .assert(predicates::str::contains("11111111111102"));
+
+    let db = git_db(&cache_dir);
+    assert!(!db.join("shallow").exists());
+}
+
+#[test]
+fn shallow_fetch_falls_back_to_full_fetch_if_rev_is_not_allowed() {
+    let dep = three_commits_dep();
+    let rev = dep.rev_parse("HEAD~2");
+    let remote = dep.bare_clone();
+    // Without protocol v2, the server refuses to send commits which are not advertised
+    // as references.
+    remote.git(["config", "uploadpack.allowAnySHA1InWant", "false"]);
+    let cache_dir = TempDir::new().unwrap();
+    let config = git_fetch_config("shallow");
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", remote.with("rev", rev))
+        .lib_cairo("fn world() -> felt252 { dep::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .env("SCARB_CACHE", cache_dir.path())
+        .env("SCARB_CONFIG", config.path())
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "protocol.version")
+        .env("GIT_CONFIG_VALUE_0", "0")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    t.child("target/dev/hello.sierra.json")
+        .assert(predicates::str::contains("11111111111101"));
+
+    let db = git_db(&cache_dir);
+    assert!(!db.join("shallow").exists());
+    assert_eq!(commits_count(&db), 3);
+}
+
+#[test]
+fn full_fetch_by_default() {
+    let remote = three_commits_dep().bare_clone();
+    let cache_dir = TempDir::new().unwrap();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", remote.with("tag", "v2"))
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CACHE", cache_dir.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    let db = git_db(&cache_dir);
+    assert!(!db.join("shallow").exists());
+    assert_eq!(commits_count(&db), 2);
+}
+
+#[test]
+fn partial_fetch_configured() {
+    let remote = three_commits_dep().bare_clone();
+    remote.git(["config", "uploadpack.allowFilter", "true"]);
+    let cache_dir = TempDir::new().unwrap();
+
+    let config = git_fetch_config("partial");
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", remote.with("tag", "v2"))
+        .lib_cairo("fn world() -> felt252 { dep::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .env("SCARB_CACHE", cache_dir.path())
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    t.child("target/dev/hello.sierra.json")
+        .assert(predicates::str::contains("11111111111102"));
+
+    let db = git_db(&cache_dir);
+    assert!(!db.join("shallow").exists());
+    assert_eq!(commits_count(&db), 2);
+    let has_promisor_pack = fs::read_dir(db.join("objects/pack")).unwrap().any(|entry| {
+        entry
+            .unwrap()
+            .path()
+            .extension()
+            .map_or(false, |ext| ext == "promisor")
+    });
+    assert!(has_promisor_pack);
+
+    // Files of the checked out revision are kept in the cache.
+    fs::remove_dir_all(t.child("target")).unwrap();
+    Scarb::quick_snapbox()
+        .args(["--offline", "build"])
+        .env("SCARB_CACHE", cache_dir.path())
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    t.child("target/dev/hello.sierra.json")
+        .assert(predicates::str::contains("11111111111102"));
+}
@@ -8,7 +8,7 @@ use indoc::indoc;
 
 use scarb_test_support::command::Scarb;
 use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
-use scarb_test_support::registry::http::HttpRegistry;
+use scarb_test_support::registry::http::{HttpRegistry, TOKEN};
 
 #[test]
 fn usage() {
@@ -369,6 +369,310 @@ fn caching() {
     expected.assert_eq(&registry.logs());
 }
 
+#[test]
+fn publish() {
+    let registry = HttpRegistry::serve();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("bar")
+        .version("1.0.0")
+        .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify", "--index"])
+        .arg(registry.to_string())
+        .env("SCARB_REGISTRY_TOKEN", TOKEN)
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Packaging bar v1.0.0 ([..])
+        ...
+        [..] Uploading bar v1.0.0 (registry+http://[..])
+        "#});
+
+    let records = registry
+        .child("index/3/b/bar.json")
+        .assert_is_json::<serde_json::Value>();
+    assert_eq!(records[0]["v"], "1.0.0");
+    registry
+        .child("bar-1.0.0.tar.zst")
+        .assert(predicates::path::is_file());
+    assert!(registry.logs().contains(indoc! {r#"
+        POST /api/v1/publish
+        accept: */*
+        accept-encoding: gzip, br, deflate
+        authorization: Bearer scarb-test-token
+    "#}));
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Downloading bar v1.0.0 ([..])
+        [..] Compiling foo v0.1.0 ([..]Scarb.toml)
+        [..]  Finished release target(s) in [..]
+        "#});
+}
+
+#[test]
+fn publish_without_token() {
+    let registry = HttpRegistry::serve();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("bar")
+        .version("1.0.0")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify", "--index"])
+        .arg(registry.to_string())
+        .env_remove("SCARB_REGISTRY_TOKEN")
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        ...
+        [..] Uploading bar v1.0.0 (registry+http://[..])
+        error: no authentication token found for registry: registry+http://[..]
+        help: run `scarb login --index http://[..]/` or set the `SCARB_REGISTRY_TOKEN` environment variable
+        "#});
+
+    assert!(!registry.logs().contains("POST /api/v1/publish"));
+}
+
+#[test]
+fn publish_rejected_by_registry() {
+    let registry = HttpRegistry::serve();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("bar")
+        .version("1.0.0")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify", "--index"])
+        .arg(registry.to_string())
+        .env("SCARB_REGISTRY_TOKEN", "invalid-token")
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        ...
+        [..] Uploading bar v1.0.0 (registry+http://[..])
+        error: the registry responded with an error (HTTP 403 Forbidden): invalid authentication token
+        "#});
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify", "--index"])
+        .arg(registry.to_string())
+        .env("SCARB_REGISTRY_TOKEN", TOKEN)
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify", "--index"])
+        .arg(registry.to_string())
+        .env("SCARB_REGISTRY_TOKEN", TOKEN)
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        ...
+        [..] Uploading bar v1.0.0 (registry+http://[..])
+        error: the registry responded with an error (HTTP 400 Bad Request): package `bar` version `1.0.0` already exists
+        "#});
+}
+
+#[test]
+fn download_many_packages() {
+    let mut registry = HttpRegistry::serve();
+    let names = ["dep0", "dep1", "dep2", "dep3", "dep4"];
+    for name in names {
+        registry.publish(|t| {
+            ProjectBuilder::start()
+                .name(name)
+                .version("1.0.0")
+                .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+                .build(t);
+        });
+    }
+
+    let t = TempDir::new().unwrap();
+    let mut project = ProjectBuilder::start().name("foo").version("0.1.0");
+    for name in names {
+        project = project.dep(name, Dep.version("1").registry(&registry));
+    }
+    project.build(&t);
+
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(indoc! {r#"
+            [net]
+            jobs = 2
+        "#})
+        .unwrap();
+
+    let output = Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+
+    // Packages are downloaded concurrently, so the order of messages is not deterministic.
+    for name in names {
+        assert!(
+            stdout.contains(&format!("Downloading {name} v1.0.0")),
+            "{stdout}"
+        );
+    }
+    let logs = registry.logs();
+    for name in names {
+        assert!(
+            logs.contains(&format!("GET /{name}-1.0.0.tar.zst")),
+            "{logs}"
+        );
+    }
+}
+
+#[test]
+fn retry_spurious_errors() {
+    let mut registry = HttpRegistry::serve();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+            .build(t);
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .build(&t);
+
+    registry.fail_next_requests(2);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        warn: spurious network error (3 tries remaining): server responded with HTTP 503 Service Unavailable
+        warn: spurious network error (2 tries remaining): server responded with HTTP 503 Service Unavailable
+        [..] Downloading bar v1.0.0 ([..])
+        "#});
+}
+
+#[test]
+fn retry_limit() {
+    let mut registry = HttpRegistry::serve();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+            .build(t);
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .build(&t);
+
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(indoc! {r#"
+            [net]
+            retry = 1
+        "#})
+        .unwrap();
+
+    registry.fail_next_requests(2);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        warn: spurious network error (1 tries remaining): server responded with HTTP 503 Service Unavailable
+        error: failed to lookup for `bar ^1 (registry+http://[..])` in registry: registry+http://[..]
+
+        Caused by:
+        ...
+        [..]HTTP status server error (503 Service Unavailable) for url (http://[..]/config.json)
+        "#});
+}
+
+#[test]
+fn zero_timeout_is_rejected() {
+    let registry = HttpRegistry::serve();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .build(&t);
+
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(indoc! {r#"
+            [net]
+            timeout = 0
+        "#})
+        .unwrap();
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        ...
+        [..]failed to parse `[..]config.toml`: `net.timeout` must be greater than 0
+        ...
+        "#});
+}
+
 // TODO(mkaput): Test errors properly when package is in index, but tarball is missing.
 // TODO(mkaput): Test interdependencies.
 // TODO(mkaput): Test offline mode, including with some cache prepopulated.
@@ -1,3 +1,5 @@
+use std::fs;
+
 use assert_fs::prelude::*;
 use assert_fs::TempDir;
 use indoc::{formatdoc, indoc};
@@ -217,7 +219,7 @@ fn publish() {
 }
 
 #[test]
-fn publish_overwrites_existing() {
+fn publish_rejects_existing_version() {
     let index = TempDir::new().unwrap();
 
     let t = TempDir::new().unwrap();
@@ -236,18 +238,21 @@ fn publish_overwrites_existing() {
         .assert()
         .success();
 
+    let expected_records = json!([
+        {
+            "v": "1.0.0",
+            "deps": [],
+            "cksum": "sha256:b34e1202407e1a9b743f261cdc27723d0344619a6dc3058bdacd9b17f6106027",
+        }
+    ]);
+
     assert_eq!(
         index
             .child("index/fo/ob/foobar.json")
             .assert_is_json::<serde_json::Value>(),
-        json!([
-            {
-                "v": "1.0.0",
-                "deps": [],
-                "cksum": "sha256:b34e1202407e1a9b743f261cdc27723d0344619a6dc3058bdacd9b17f6106027",
-            }
-        ])
+        expected_records
     );
+    let archive = fs::read(index.child("foobar-1.0.0.tar.zst")).unwrap();
 
     let t = TempDir::new().unwrap();
     ProjectBuilder::start()
@@ -258,23 +263,29 @@ fn publish_overwrites_existing() {
 
     Scarb::quick_snapbox()
         .arg("publish")
+        .arg("--no-verify")
         .arg("--index")
         .arg(Url::from_directory_path(&index).unwrap().to_string())
         .current_dir(&t)
         .assert()
-        .success();
+        .failure()
+        .stdout_matches(indoc! {r#"
+        [..] Packaging foobar v1.0.0 ([..])
+        ...
+        [..] Uploading foobar v1.0.0 (registry+file://[..])
+        error: package `foobar` version `1.0.0` already exists
+        "#});
 
+    // Neither the index nor the archive have been touched.
     assert_eq!(
         index
             .child("index/fo/ob/foobar.json")
             .assert_is_json::<serde_json::Value>(),
-        json!([
-            {
-                "v": "1.0.0",
-                "deps": [],
-                "cksum": "sha256:4682a7645acf0b342556778a0544e2871ae910efc609d89ab3bedf9491219ab5",
-            }
-        ])
+        expected_records
+    );
+    assert_eq!(
+        fs::read(index.child("foobar-1.0.0.tar.zst")).unwrap(),
+        archive
     );
 }
 
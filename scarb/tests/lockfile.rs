@@ -11,6 +11,7 @@ use snapbox::cmd::Command;
 
 use scarb_test_support::cargo::cargo_bin;
 use scarb_test_support::command::Scarb;
+use scarb_test_support::gitx;
 use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
 use scarb_test_support::registry::local::LocalRegistry;
 use test_for_each_example::test_for_each_example;
@@ -79,3 +80,134 @@ fn store_checksum_for_registry_dependencies() {
     t.child("Scarb.lock")
         .assert(predicates::str::contains(r#"checksum = ""#));
 }
+
+#[test]
+fn locked_fails_if_lockfile_needs_update() {
+    let mut registry = LocalRegistry::create();
+    for version in ["1.0.0", "1.1.0"] {
+        registry.publish(|t| {
+            ProjectBuilder::start()
+                .name("bar")
+                .version(version)
+                .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+                .build(t);
+        });
+    }
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("=1.0.0").registry(&registry))
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .args(["--locked", "fetch"])
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1.1.0").registry(&registry))
+        .build(&t);
+
+    let lockfile = t.child("Scarb.lock");
+    let before = fs::read_to_string(&lockfile).unwrap();
+
+    Scarb::quick_snapbox()
+        .args(["--locked", "fetch"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: the lockfile [..]Scarb.lock needs to be updated, but --locked was passed to prevent this
+        following packages would change:
+          Updating bar v1.0.0 (registry+file://[..]) -> v1.1.0
+        help: run the command without --locked to update the lockfile
+        "#});
+
+    assert_eq!(fs::read_to_string(&lockfile).unwrap(), before);
+}
+
+#[test]
+fn locked_does_not_create_lockfile() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .args(["--locked", "fetch"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: the lockfile [..]Scarb.lock needs to be updated, but --locked was passed to prevent this
+        following packages would change:
+          Adding foo v0.1.0
+        help: run the command without --locked to update the lockfile
+        "#});
+
+    t.child("Scarb.lock").assert(predicates::path::missing());
+}
+
+#[test]
+fn locked_reads_read_only_lockfile() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    let lockfile = t.child("Scarb.lock");
+    let mut permissions = fs::metadata(&lockfile).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&lockfile, permissions.clone()).unwrap();
+
+    Scarb::quick_snapbox()
+        .args(["--locked", "fetch"])
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(&lockfile, permissions).unwrap();
+}
+
+#[test]
+fn frozen_implies_offline() {
+    let dep = gitx::new("dep", |t| ProjectBuilder::start().name("dep").build(&t));
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("dep", &dep)
+        .build(&t);
+
+    let output = Scarb::quick_snapbox()
+        .args(["--frozen", "fetch"])
+        .current_dir(&t)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("in offline mode"));
+    t.child("Scarb.lock").assert(predicates::path::missing());
+}
@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use indoc::indoc;
+
+use scarb_test_support::command::Scarb;
+use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
+use scarb_test_support::registry::http::{HttpRegistry, TOKEN};
+
+#[test]
+fn login_and_logout() {
+    let config = TempDir::new().unwrap();
+
+    Scarb::quick_snapbox()
+        .args(["login", "--index", "https://example.com/index/", "secret"])
+        .env("SCARB_CONFIG", config.path())
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Login token for `https://example.com/index/` saved
+        "#});
+
+    let credentials = config.child("credentials.toml");
+    let contents: toml::Value = toml::from_str(&credentials.read_to_string()).unwrap();
+    assert_eq!(
+        contents["registries"]["https://example.com/index"]["token"].as_str(),
+        Some("secret")
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = credentials.metadata().unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // Tokens are keyed by canonical URLs, so the trailing slash does not matter.
+    Scarb::quick_snapbox()
+        .args(["logout", "--index", "https://example.com/index"])
+        .env("SCARB_CONFIG", config.path())
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Logout token for `https://example.com/index` has been removed from local storage
+        "#});
+
+    let contents: toml::Value = toml::from_str(&credentials.read_to_string()).unwrap();
+    assert!(contents["registries"].as_table().unwrap().is_empty());
+
+    Scarb::quick_snapbox()
+        .args(["logout", "--index", "https://example.com/index"])
+        .env("SCARB_CONFIG", config.path())
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Logout not currently logged in to `https://example.com/index`
+        "#});
+}
+
+#[test]
+fn login_reads_token_from_stdin() {
+    let config = TempDir::new().unwrap();
+
+    Scarb::quick_snapbox()
+        .args(["login", "--index", "https://example.com/index/"])
+        .env("SCARB_CONFIG", config.path())
+        .stdin("secret\n")
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        please paste the token for https://example.com/index/ below
+        [..] Login token for `https://example.com/index/` saved
+        "#});
+
+    let contents: toml::Value =
+        toml::from_str(&config.child("credentials.toml").read_to_string()).unwrap();
+    assert_eq!(
+        contents["registries"]["https://example.com/index"]["token"].as_str(),
+        Some("secret")
+    );
+}
+
+#[test]
+fn login_rejects_empty_token() {
+    let config = TempDir::new().unwrap();
+
+    Scarb::quick_snapbox()
+        .args(["login", "--index", "https://example.com/index/"])
+        .env("SCARB_CONFIG", config.path())
+        .stdin("\n")
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        please paste the token for https://example.com/index/ below
+        error: registry token must not be empty
+        "#});
+
+    config
+        .child("credentials.toml")
+        .assert(predicates::path::missing());
+}
+
+#[test]
+fn publish_with_stored_token() {
+    let registry = HttpRegistry::serve();
+    let config = TempDir::new().unwrap();
+
+    Scarb::quick_snapbox()
+        .args(["login", "--index"])
+        .arg(registry.to_string())
+        .arg(TOKEN)
+        .env("SCARB_CONFIG", config.path())
+        .assert()
+        .success();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("bar")
+        .version("1.0.0")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify", "--index"])
+        .arg(registry.to_string())
+        .env("SCARB_CONFIG", config.path())
+        .env_remove("SCARB_REGISTRY_TOKEN")
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success();
+
+    registry
+        .child("bar-1.0.0.tar.zst")
+        .assert(predicates::path::is_file());
+}
+
+#[test]
+fn auth_required_registry() {
+    let mut registry = HttpRegistry::serve_authenticated();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+            .build(t);
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env_remove("SCARB_REGISTRY_TOKEN")
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: failed to lookup for `bar ^1 (registry+http://[..])` in registry: registry+http://[..]
+
+        Caused by:
+        [..]no authentication token found for registry: registry+http://[..]
+        ...
+        "#});
+
+    // The token from the environment is only sent to registries explicitly targeted by the user,
+    // never to registries of dependencies.
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_REGISTRY_TOKEN", TOKEN)
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: failed to lookup for `bar ^1 (registry+http://[..])` in registry: registry+http://[..]
+
+        Caused by:
+        [..]no authentication token found for registry: registry+http://[..]
+        ...
+        "#});
+    assert!(!registry.logs().contains("authorization:"));
+
+    let config = TempDir::new().unwrap();
+    Scarb::quick_snapbox()
+        .args(["login", "--index"])
+        .arg(registry.to_string())
+        .arg(TOKEN)
+        .env("SCARB_CONFIG", config.path())
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .env_remove("SCARB_REGISTRY_TOKEN")
+        .current_dir(&t)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Downloading bar v1.0.0 ([..])
+        "#});
+
+    let logs = registry.logs();
+    assert!(logs.contains(indoc! {r#"
+        GET /index/3/b/bar.json
+        accept: */*
+        accept-encoding: gzip, br, deflate
+        authorization: Bearer scarb-test-token
+    "#}));
+    assert!(logs.contains(indoc! {r#"
+        GET /bar-1.0.0.tar.zst
+        accept: */*
+        accept-encoding: gzip, br, deflate
+        authorization: Bearer scarb-test-token
+    "#}));
+}
@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 
 use assert_fs::prelude::*;
-use indoc::indoc;
+use indoc::{formatdoc, indoc};
 use serde_json::json;
 
 use scarb_metadata::{Cfg, DepKind, ManifestMetadataBuilder, Metadata, PackageMetadata};
@@ -1199,3 +1199,58 @@ fn includes_edition() {
     }
     panic!("Package not found in metadata!");
 }
+
+#[test]
+fn includes_publish() {
+    let t = assert_fs::TempDir::new().unwrap();
+    t.child("Scarb.toml")
+        .write_str(indoc! {r#"
+            [workspace]
+            members = ["private", "internal", "public"]
+
+            [workspace.package]
+            publish = false
+
+            [registries.internal]
+            index = "https://internal.example.com/index/"
+        "#})
+        .unwrap();
+    for (name, publish) in [
+        ("private", "publish.workspace = true"),
+        (
+            "internal",
+            r#"publish = ["internal", "https://other.example.com/index/"]"#,
+        ),
+        ("public", ""),
+    ] {
+        let t = t.child(name);
+        t.child("Scarb.toml")
+            .write_str(&formatdoc! {r#"
+                [package]
+                name = "{name}"
+                version = "0.1.0"
+                {publish}
+            "#})
+            .unwrap();
+        ProjectBuilder::start().just_code(&t);
+    }
+
+    let metadata = Scarb::quick_snapbox()
+        .arg("--json")
+        .arg("metadata")
+        .arg("--format-version")
+        .arg("1")
+        .current_dir(&t)
+        .stdout_json::<Metadata>();
+
+    let packages = packages_by_name(metadata);
+    assert_eq!(packages["private"].publish, Some(vec![]));
+    assert_eq!(
+        packages["internal"].publish,
+        Some(vec![
+            "https://internal.example.com/index/".to_string(),
+            "https://other.example.com/index/".to_string(),
+        ])
+    );
+    assert_eq!(packages["public"].publish, None);
+}
@@ -389,6 +389,51 @@ fn generated_manifest() {
     );
 }
 
+#[test]
+fn generated_manifest_expands_registry_names() {
+    let mut registry = LocalRegistry::create();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("registry_dep")
+            .version("1.0.0")
+            .build(t);
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("registry_dep", Dep.version("1.0.0").registry("internal"))
+        .manifest_extra(formatdoc! {r#"
+            [registries.internal]
+            index = "{registry}"
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("package")
+        .arg("--no-verify")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    PackageChecker::assert(&t.child("target/package/hello-1.0.0.tar.zst")).file_matches_nl(
+        "Scarb.toml",
+        indoc! {r#"
+            # Code generated by scarb package -p hello; DO NOT EDIT.
+            ...
+            [package]
+            name = "hello"
+            version = "1.0.0"
+            edition = "2023_01"
+
+            [dependencies.registry_dep]
+            version = "^1.0.0"
+            registry = "file://[..]"
+        "#},
+    );
+}
+
 #[test]
 fn workspace() {
     let t = TempDir::new().unwrap();
@@ -1220,6 +1265,159 @@ fn ignore_whitelist_pattern() {
         "#}));
 }
 
+#[test]
+fn include_overrides_ignore_files() {
+    let t = TempDir::new().unwrap();
+    t.child("Scarb.toml")
+        .write_str(indoc! {r#"
+            [package]
+            name = "foo"
+            version = "1.0.0"
+            include = ["src/", "generated/*.cairo"]
+        "#})
+        .unwrap();
+    ProjectBuilder::start()
+        .src("generated/bar.cairo", "fn bar() {}")
+        .src("generated/bar.txt", "")
+        .src("src/.hidden", "")
+        .src("notes.txt", "")
+        .just_code(&t);
+
+    t.child(".scarbignore")
+        .write_str(indoc! {r#"
+            generated/
+        "#})
+        .unwrap();
+
+    Scarb::quick_snapbox()
+        .arg("package")
+        .arg("--list")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_eq(unix_paths_to_os_lossy(indoc! {r#"
+            VERSION
+            Scarb.orig.toml
+            Scarb.toml
+            generated/bar.cairo
+            src/.hidden
+            src/lib.cairo
+        "#}));
+}
+
+#[test]
+fn exclude_globs() {
+    let t = TempDir::new().unwrap();
+    t.child("Scarb.toml")
+        .write_str(indoc! {r#"
+            [package]
+            name = "foo"
+            version = "1.0.0"
+            exclude = ["fixtures", "*.bak"]
+        "#})
+        .unwrap();
+    ProjectBuilder::start()
+        .src("fixtures/big.json", "{}")
+        .src("src/fixtures/big.json", "{}")
+        .src("src/lib.cairo.bak", "")
+        .src("data.json", "{}")
+        .just_code(&t);
+
+    Scarb::quick_snapbox()
+        .arg("package")
+        .arg("--list")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_eq(unix_paths_to_os_lossy(indoc! {r#"
+            VERSION
+            Scarb.orig.toml
+            Scarb.toml
+            data.json
+            src/lib.cairo
+        "#}));
+}
+
+#[test]
+fn exclude_takes_precedence_over_include() {
+    let t = TempDir::new().unwrap();
+    t.child("Scarb.toml")
+        .write_str(indoc! {r#"
+            [package]
+            name = "foo"
+            version = "1.0.0"
+            include = ["src/"]
+            exclude = ["src/skip.cairo"]
+        "#})
+        .unwrap();
+    ProjectBuilder::start()
+        .src("src/skip.cairo", "")
+        .src("other.cairo", "")
+        .just_code(&t);
+
+    Scarb::quick_snapbox()
+        .arg("package")
+        .arg("--no-verify")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    PackageChecker::assert(&t.child("target/package/foo-1.0.0.tar.zst"))
+        .contents(&["VERSION", "Scarb.orig.toml", "Scarb.toml", "src/lib.cairo"])
+        .file_eq_nl(
+            "Scarb.toml",
+            indoc! {r#"
+                # Code generated by scarb package -p foo; DO NOT EDIT.
+                #
+                # When uploading packages to the registry Scarb will automatically
+                # "normalize" Scarb.toml files for maximal compatibility
+                # with all versions of Scarb and also rewrite `path` dependencies
+                # to registry dependencies.
+                #
+                # If you are reading this file be aware that the original Scarb.toml
+                # will likely look very different (and much more reasonable).
+                # See Scarb.orig.toml for the original contents.
+
+                [package]
+                name = "foo"
+                version = "1.0.0"
+                edition = "2023_01"
+                include = ["src/"]
+                exclude = ["src/skip.cairo"]
+
+                [dependencies]
+            "#},
+        );
+}
+
+#[test]
+fn invalid_include_pattern() {
+    let t = TempDir::new().unwrap();
+    t.child("Scarb.toml")
+        .write_str(indoc! {r#"
+            [package]
+            name = "foo"
+            version = "1.0.0"
+            include = ["src/{"]
+        "#})
+        .unwrap();
+    ProjectBuilder::start().just_code(&t);
+
+    Scarb::quick_snapbox()
+        .arg("package")
+        .arg("--list")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+            error: failed to list source files in: [..]
+
+            Caused by:
+                0: invalid `include` pattern: src/{
+                1: [..]
+        "#});
+}
+
 #[test]
 fn no_lib_target() {
     let t = TempDir::new().unwrap();
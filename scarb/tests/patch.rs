@@ -0,0 +1,217 @@
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use indoc::{formatdoc, indoc};
+use predicates::prelude::*;
+
+use scarb_test_support::command::Scarb;
+use scarb_test_support::gitx;
+use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
+use scarb_test_support::registry::local::LocalRegistry;
+
+#[test]
+fn patch_registry_dependency_with_path() {
+    let mut registry = LocalRegistry::create();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .lib_cairo("fn f() -> felt252 { 0 }")
+            .build(t);
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("bar")
+        .version("1.0.0")
+        .lib_cairo("fn patched() -> felt252 { 1 }")
+        .build(&t.child("bar"));
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .lib_cairo("fn f() -> felt252 { bar::patched() }")
+        .manifest_extra(formatdoc! {r#"
+            [patch."{registry}"]
+            bar = {{ path = "bar" }}
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Compiling foo v0.1.0 ([..]Scarb.toml)
+        [..]  Finished release target(s) in [..]
+        "#});
+
+    t.child("Scarb.lock").assert(indoc! {r#"
+        # Code generated by scarb DO NOT EDIT.
+        version = 1
+
+        [[package]]
+        name = "bar"
+        version = "1.0.0"
+
+        [[package]]
+        name = "foo"
+        version = "0.1.0"
+        dependencies = [
+         "bar",
+        ]
+
+        [[patch]]
+        name = "bar"
+        version = "1.0.0"
+    "#});
+}
+
+#[test]
+fn patch_git_dependency_with_fork() {
+    let upstream = gitx::new("dep", |t| {
+        ProjectBuilder::start()
+            .name("dep")
+            .version("1.0.0")
+            .lib_cairo("fn hello() -> felt252 { 42 }")
+            .build(&t)
+    });
+    let fork = gitx::new("dep_fork", |t| {
+        ProjectBuilder::start()
+            .name("dep")
+            .version("1.0.0")
+            .lib_cairo("fn forked() -> felt252 { 53 }")
+            .build(&t)
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep("dep", &upstream)
+        .lib_cairo("fn world() -> felt252 { dep::forked() }")
+        .manifest_extra(formatdoc! {r#"
+            [patch."{upstream}"]
+            dep = {{ git = "{fork}" }}
+        "#, upstream = upstream.url(), fork = fork.url()})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..]  Updating git repository file://[..]/dep_fork
+        [..] Compiling hello v1.0.0 ([..]Scarb.toml)
+        [..]  Finished release target(s) in [..]
+        "#});
+
+    t.child("Scarb.lock").assert(
+        predicate::str::is_match(
+            r#"\[\[patch]]\nname = "dep"\nversion = "1.0.0"\nsource = "git\+file://.+/dep_fork#[0-9a-f]+"\n"#,
+        )
+        .unwrap(),
+    );
+
+    // Patches stay locked to the same revision.
+    fork.change_file("src/lib.cairo", "fn changed() -> felt252 { 64 }");
+    Scarb::quick_snapbox()
+        .arg("build")
+        .current_dir(&t)
+        .assert()
+        .success();
+}
+
+#[test]
+fn unused_patch() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("bar")
+        .version("1.0.0")
+        .build(&t.child("bar"));
+    ProjectBuilder::start()
+        .name("baz")
+        .version("1.0.0")
+        .build(&t.child("baz"));
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .manifest_extra(indoc! {r#"
+            [patch."scarb.xyz"]
+            baz = { path = "baz" }
+            bar = { path = "bar" }
+        "#})
+        .build(&t);
+
+    // Warnings are reported in a stable order.
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        warn: patch `bar` ([..]) was not used in the dependency graph
+        warn: patch `baz` ([..]) was not used in the dependency graph
+        "#});
+
+    t.child("Scarb.lock")
+        .assert(predicate::str::contains("[[patch]]").not());
+}
+
+#[test]
+fn patch_in_non_root_member_is_ignored() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("first")
+        .manifest_extra(indoc! {r#"
+            [patch."scarb.xyz"]
+            bar = { path = "bar" }
+        "#})
+        .build(&t.child("first"));
+    ProjectBuilder::start()
+        .name("bar")
+        .build(&t.child("first/bar"));
+    t.child("Scarb.toml")
+        .write_str(indoc! {r#"
+            [workspace]
+            members = ["first"]
+        "#})
+        .unwrap();
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        warn: patch for the non root package will be ignored, specify patch at the workspace root:
+        package:   [..]first[..]Scarb.toml
+        workspace: [..]Scarb.toml
+        "#});
+}
+
+#[test]
+fn patch_pointing_to_same_source() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .manifest_extra(indoc! {r#"
+            [patch."scarb.xyz"]
+            bar = "1.0.0"
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: failed to parse manifest at: [..]/Scarb.toml
+
+        Caused by:
+            patch for `bar` in `scarb.xyz` points to the same source, but patches must point to different sources
+        "#});
+}
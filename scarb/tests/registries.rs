@@ -0,0 +1,381 @@
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use indoc::{formatdoc, indoc};
+
+use scarb_test_support::command::Scarb;
+use scarb_test_support::fsx::ChildPathEx;
+use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
+use scarb_test_support::registry::local::LocalRegistry;
+
+fn publish_bar(registry: &mut LocalRegistry) {
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+            .build(t);
+    });
+}
+
+#[test]
+fn dependency_from_named_registry() {
+    let mut registry = LocalRegistry::create();
+    publish_bar(&mut registry);
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry("internal"))
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .manifest_extra(formatdoc! {r#"
+            [registries.internal]
+            index = "{registry}"
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    // The lockfile refers to the registry by its URL, not the name.
+    let lockfile = t.child("Scarb.lock").read_to_string();
+    assert!(lockfile.contains(&formatdoc! {r#"
+        [[package]]
+        name = "bar"
+        version = "1.0.0"
+        source = "registry+{registry}"
+    "#}));
+}
+
+#[test]
+fn registry_from_global_config() {
+    let mut registry = LocalRegistry::create();
+    publish_bar(&mut registry);
+
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(&formatdoc! {r#"
+            [registries.internal]
+            index = "{registry}"
+        "#})
+        .unwrap();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry("internal"))
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    let lockfile = t.child("Scarb.lock").read_to_string();
+    assert!(lockfile.contains(&format!(r#"source = "registry+{registry}""#)));
+}
+
+#[test]
+fn workspace_registry_overrides_global_config() {
+    let mut registry = LocalRegistry::create();
+    publish_bar(&mut registry);
+
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(indoc! {r#"
+            [registries.internal]
+            index = "https://there-is-no-such-registry.example.com/"
+        "#})
+        .unwrap();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry("internal"))
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .manifest_extra(formatdoc! {r#"
+            [registries.internal]
+            index = "{registry}"
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+}
+
+#[test]
+fn undefined_registry() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry("internal"))
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: failed to parse manifest at: [..]/Scarb.toml
+
+        Caused by:
+            dependency (bar) refers to registry `internal`, which is not defined in the `[registries]` table
+        "#});
+}
+
+#[test]
+fn multiple_default_registries() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .manifest_extra(indoc! {r#"
+            [registries.a]
+            index = "https://a.example.com/"
+            default = true
+
+            [registries.b]
+            index = "https://b.example.com/"
+            default = true
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: failed to parse manifest at: [..]/Scarb.toml
+
+        Caused by:
+            only one registry can be marked as default, but both `a` and `b` are
+        "#});
+}
+
+#[test]
+fn publish_to_named_registry() {
+    let mut internal = LocalRegistry::create();
+    publish_bar(&mut internal);
+    let public = LocalRegistry::create();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry("internal"))
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .manifest_extra(formatdoc! {r#"
+            [registries.internal]
+            index = "{internal}"
+
+            [registries.public]
+            index = "{public}"
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify", "--registry", "public"])
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Packaging foo v0.1.0 ([..])
+        ...
+        [..] Uploading foo v0.1.0 (registry+file://[..])
+        "#});
+
+    public
+        .t
+        .child("index/3/f/foo.json")
+        .assert(predicates::path::is_file());
+    internal
+        .t
+        .child("index/3/f/foo.json")
+        .assert(predicates::path::missing());
+}
+
+#[test]
+fn publish_to_default_registry() {
+    let registry = LocalRegistry::create();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .manifest_extra(formatdoc! {r#"
+            [registries.internal]
+            index = "{registry}"
+            default = true
+        "#})
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify"])
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    registry
+        .t
+        .child("index/3/f/foo.json")
+        .assert(predicates::path::is_file());
+}
+
+#[test]
+fn publish_without_registry() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: no registry to publish to
+        help: pass `--index` or `--registry`, or mark one of `[registries]` as default
+        "#});
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify", "--registry", "internal"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: registry `internal` is not defined in the `[registries]` table
+        "#});
+}
+
+#[test]
+fn publish_false() {
+    let registry = LocalRegistry::create();
+
+    let t = TempDir::new().unwrap();
+    t.child("Scarb.toml")
+        .write_str(indoc! {r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            publish = false
+        "#})
+        .unwrap();
+    ProjectBuilder::start().just_code(&t);
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify", "--index"])
+        .arg(&registry.url)
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: package `foo` cannot be published, because `publish` is set to `false` in its manifest
+        "#});
+
+    Scarb::quick_snapbox()
+        .args(["package", "--no-verify"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: package `foo` cannot be packaged, because `publish` is set to `false` in its manifest
+        "#});
+
+    registry
+        .t
+        .child("index/3/f/foo.json")
+        .assert(predicates::path::missing());
+}
+
+#[test]
+fn publish_to_allowed_registries_only() {
+    let internal = LocalRegistry::create();
+    let public = LocalRegistry::create();
+
+    let t = TempDir::new().unwrap();
+    t.child("Scarb.toml")
+        .write_str(&formatdoc! {r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            publish = ["internal"]
+
+            [registries.internal]
+            index = "{internal}"
+
+            [registries.public]
+            index = "{public}"
+        "#})
+        .unwrap();
+    ProjectBuilder::start().just_code(&t);
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify", "--registry", "public"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: package `foo` cannot be published to registry: [..]
+        help: registries allowed by the `publish` field are: file://[..]
+        "#});
+
+    Scarb::quick_snapbox()
+        .args(["publish", "--no-verify", "--registry", "internal"])
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    internal
+        .t
+        .child("index/3/f/foo.json")
+        .assert(predicates::path::is_file());
+    public
+        .t
+        .child("index/3/f/foo.json")
+        .assert(predicates::path::missing());
+}
+
+#[test]
+fn publish_to_undefined_registry() {
+    let t = TempDir::new().unwrap();
+    t.child("Scarb.toml")
+        .write_str(indoc! {r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            publish = ["internal"]
+        "#})
+        .unwrap();
+    ProjectBuilder::start().just_code(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: failed to parse manifest at: [..]/Scarb.toml
+
+        Caused by:
+            `publish` refers to registry `internal`, which is not defined in the `[registries]` table
+        "#});
+}
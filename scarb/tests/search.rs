@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use assert_fs::prelude::*;
+use indoc::{formatdoc, indoc};
+
+use scarb_test_support::command::Scarb;
+use scarb_test_support::project_builder::ProjectBuilder;
+use scarb_test_support::registry::http::HttpRegistry;
+use scarb_test_support::registry::local::LocalRegistry;
+
+fn local_registry() -> LocalRegistry {
+    let mut registry = LocalRegistry::create();
+    for (name, version, description) in [
+        ("foo", "1.0.0", Some("The foo package.")),
+        ("foo", "1.1.0", Some("The foo package, improved.")),
+        ("foobar", "0.1.0", None),
+        ("bar", "1.0.0", Some("The bar package.")),
+    ] {
+        registry.publish(|t| {
+            let mut manifest = formatdoc! {r#"
+                [package]
+                name = "{name}"
+                version = "{version}"
+            "#};
+            if let Some(description) = description {
+                manifest.push_str(&format!("description = \"{description}\"\n"));
+            }
+            t.child("Scarb.toml").write_str(&manifest).unwrap();
+            ProjectBuilder::start()
+                .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+                .just_code(t);
+        });
+    }
+    registry
+}
+
+#[test]
+fn search_local_registry() {
+    let registry = local_registry();
+
+    Scarb::quick_snapbox()
+        .args(["search", "foo", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        foo = "1.1.0"       # The foo package, improved.
+        foobar = "0.1.0"
+        "#});
+}
+
+#[test]
+fn search_limit() {
+    let registry = local_registry();
+
+    Scarb::quick_snapbox()
+        .args(["search", "ba", "--limit", "1", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        bar = "1.0.0"    # The bar package.
+        "#});
+}
+
+#[test]
+fn search_skips_yanked_versions() {
+    let registry = local_registry();
+
+    Scarb::quick_snapbox()
+        .args(["yank", "foo@1.1.0", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .args(["search", "foo", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        foo = "1.0.0"       # The foo package.
+        foobar = "0.1.0"
+        "#});
+}
+
+#[test]
+fn search_no_results() {
+    let registry = local_registry();
+
+    Scarb::quick_snapbox()
+        .args(["search", "baz", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .success()
+        .stdout_eq("");
+}
+
+#[test]
+fn search_json_output() {
+    let registry = local_registry();
+
+    Scarb::quick_snapbox()
+        .args(["--json", "search", "bar", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        {"name":"bar","version":"1.0.0","description":"The bar package."}
+        {"name":"foobar","version":"0.1.0"}
+        "#});
+}
+
+#[test]
+fn search_http_registry() {
+    let mut registry = HttpRegistry::serve();
+    for (name, version) in [("foo", "1.0.0"), ("foo", "1.1.0"), ("bar", "1.0.0")] {
+        registry.publish(|t| {
+            ProjectBuilder::start().name(name).version(version).build(t);
+        });
+    }
+
+    Scarb::quick_snapbox()
+        .args(["search", "foo", "--index"])
+        .arg(registry.to_string())
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        foo = "1.1.0"
+        "#});
+
+    assert!(registry
+        .logs()
+        .contains("GET /api/v1/search?q=foo&limit=10"));
+}
@@ -0,0 +1,272 @@
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use indoc::{formatdoc, indoc};
+
+use scarb_test_support::command::Scarb;
+use scarb_test_support::fsx::ChildPathEx;
+use scarb_test_support::gitx;
+use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
+use scarb_test_support::registry::local::LocalRegistry;
+
+fn mirror_with_bar(body: &str) -> LocalRegistry {
+    let mut registry = LocalRegistry::create();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .lib_cairo(format!("fn f() -> felt252 {{ {body} }}"))
+            .build(t);
+    });
+    registry
+}
+
+fn config_with_mirror(mirror: &LocalRegistry) -> TempDir {
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(&formatdoc! {r#"
+            [source."scarb.xyz"]
+            replace-with = "mirror"
+
+            [source.mirror]
+            local-registry = "{}"
+        "#, mirror.t.path().display()})
+        .unwrap();
+    config
+}
+
+#[test]
+fn replace_default_registry() {
+    let mirror = mirror_with_bar("0");
+    let config = config_with_mirror(&mirror);
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1"))
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    // The lockfile still refers to the default registry.
+    let lockfile = t.child("Scarb.lock").read_to_string();
+    assert!(lockfile.contains(indoc! {r#"
+        [[package]]
+        name = "bar"
+        version = "1.0.0"
+        source = "registry+https://there-is-no-default-registry-yet.com/"
+        checksum = "sha256:"#}));
+}
+
+#[test]
+fn replacement_checksums_are_verified() {
+    let mirror = mirror_with_bar("0");
+    let config = config_with_mirror(&mirror);
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1"))
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    // A "mirror" with different contents of the same package version.
+    let other_mirror = mirror_with_bar("1");
+    let config = config_with_mirror(&other_mirror);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: checksum for `bar v1.0.0 ([..])` changed between lock files
+        ...
+        "#});
+}
+
+#[test]
+fn replace_registry_with_url() {
+    let mirror = mirror_with_bar("0");
+
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(&formatdoc! {r#"
+            [source.internal]
+            registry = "https://internal.example.com/index/"
+            replace-with = "mirror"
+
+            [source.mirror]
+            registry = "{mirror}"
+        "#})
+        .unwrap();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep(
+            "bar",
+            Dep.version("1")
+                .registry("https://internal.example.com/index/"),
+        )
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    let lockfile = t.child("Scarb.lock").read_to_string();
+    assert!(lockfile.contains(r#"source = "registry+https://internal.example.com/index/""#));
+}
+
+#[test]
+fn replace_git_with_git() {
+    let git_dep = gitx::new("dep1", |t| {
+        ProjectBuilder::start()
+            .name("dep1")
+            .lib_cairo("fn hello() -> felt252 { 42 }")
+            .build(&t)
+    });
+
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(&formatdoc! {r#"
+            [source.upstream]
+            git = "https://github.com/example/dep1.git"
+            replace-with = "mirror"
+
+            [source.mirror]
+            git = "{git_dep}"
+        "#})
+        .unwrap();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep(
+            "dep1",
+            Dep.with("git", "https://github.com/example/dep1.git"),
+        )
+        .lib_cairo("fn world() -> felt252 { dep1::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..]  Updating git repository file://[..]/dep1
+        "#});
+
+    let lockfile = t.child("Scarb.lock").read_to_string();
+    assert!(lockfile.contains(r#"source = "git+https://github.com/example/dep1.git#"#));
+}
+
+#[test]
+fn replace_git_with_registry() {
+    let mut registry = LocalRegistry::create();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("dep1")
+            .version("1.0.0")
+            .lib_cairo("fn hello() -> felt252 { 42 }")
+            .build(t);
+    });
+
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(&formatdoc! {r#"
+            [source.upstream]
+            git = "https://github.com/example/dep1.git"
+            replace-with = "vendored"
+
+            [source.vendored]
+            local-registry = "{}"
+        "#, registry.t.path().display()})
+        .unwrap();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("hello")
+        .version("1.0.0")
+        .dep(
+            "dep1",
+            Dep.with("git", "https://github.com/example/dep1.git"),
+        )
+        .lib_cairo("fn world() -> felt252 { dep1::hello() }")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    let lockfile = t.child("Scarb.lock").read_to_string();
+    assert!(lockfile.contains(indoc! {r#"
+        [[package]]
+        name = "dep1"
+        version = "1.0.0"
+        source = "git+https://github.com/example/dep1.git"
+    "#}));
+}
+
+#[test]
+fn invalid_replacement() {
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(indoc! {r#"
+            [source."scarb.xyz"]
+            replace-with = "mirror"
+        "#})
+        .unwrap();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1"))
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: [..]
+        ...
+        [..]failed to replace source `scarb.xyz`
+        [..]source `mirror` is not defined
+        "#});
+}
@@ -0,0 +1,238 @@
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use indoc::indoc;
+
+use scarb_test_support::command::Scarb;
+use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
+use scarb_test_support::registry::local::LocalRegistry;
+
+fn publish(registry: &mut LocalRegistry, name: &str, version: &str) {
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name(name)
+            .version(version)
+            .lib_cairo("fn f() -> felt252 { 0 }")
+            .build(t);
+    });
+}
+
+#[test]
+fn update_selected_package() {
+    let mut registry = LocalRegistry::create();
+    publish(&mut registry, "bar", "1.0.0");
+    publish(&mut registry, "baz", "1.0.0");
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .dep("baz", Dep.version("1").registry(&registry))
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    publish(&mut registry, "bar", "1.1.0");
+    publish(&mut registry, "baz", "1.1.0");
+
+    Scarb::quick_snapbox()
+        .args(["update", "bar"])
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..]  Updating bar v1.0.0 (registry+file://[..]) -> v1.1.0
+        "#});
+
+    let lockfile = t.child("Scarb.lock");
+    lockfile.assert(predicates::str::contains(indoc! {r#"
+        [[package]]
+        name = "bar"
+        version = "1.1.0"
+    "#}));
+    lockfile.assert(predicates::str::contains(indoc! {r#"
+        [[package]]
+        name = "baz"
+        version = "1.0.0"
+    "#}));
+
+    Scarb::quick_snapbox()
+        .arg("update")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..]  Updating baz v1.0.0 (registry+file://[..]) -> v1.1.0
+        "#});
+}
+
+#[test]
+fn update_selected_package_keeps_other_packages_locked() {
+    let mut registry = LocalRegistry::create();
+    let url = registry.url.clone();
+    publish(&mut registry, "bar", "1.0.0");
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("baz")
+            .version("1.0.0")
+            .dep("bar", Dep.version("1.0").registry(&url))
+            .lib_cairo("fn f() -> felt252 { 0 }")
+            .build(t);
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .dep("baz", Dep.version("1").registry(&registry))
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    // The newest `baz` would require updating `bar` as well.
+    publish(&mut registry, "bar", "1.1.0");
+    for (version, bar_req) in [("1.0.1", "1.0"), ("1.1.0", "1.1")] {
+        registry.publish(|t| {
+            ProjectBuilder::start()
+                .name("baz")
+                .version(version)
+                .dep("bar", Dep.version(bar_req).registry(&url))
+                .lib_cairo("fn f() -> felt252 { 0 }")
+                .build(t);
+        });
+    }
+
+    Scarb::quick_snapbox()
+        .args(["update", "baz"])
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..]  Updating baz v1.0.0 (registry+file://[..]) -> v1.0.1
+        "#});
+
+    let lockfile = t.child("Scarb.lock");
+    lockfile.assert(predicates::str::contains(indoc! {r#"
+        [[package]]
+        name = "bar"
+        version = "1.0.0"
+    "#}));
+    lockfile.assert(predicates::str::contains(indoc! {r#"
+        [[package]]
+        name = "baz"
+        version = "1.0.1"
+    "#}));
+}
+
+#[test]
+fn update_to_precise_version() {
+    let mut registry = LocalRegistry::create();
+    publish(&mut registry, "bar", "1.0.0");
+    publish(&mut registry, "bar", "1.1.0");
+    publish(&mut registry, "bar", "1.2.0");
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .args(["update", "bar", "--precise", "1.1.0"])
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..]  Updating bar v1.2.0 (registry+file://[..]) -> v1.1.0
+        "#});
+
+    t.child("Scarb.lock")
+        .assert(predicates::str::contains(indoc! {r#"
+            [[package]]
+            name = "bar"
+            version = "1.1.0"
+        "#}));
+
+    // The precise version stays locked on subsequent builds.
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    t.child("Scarb.lock")
+        .assert(predicates::str::contains(indoc! {r#"
+            [[package]]
+            name = "bar"
+            version = "1.1.0"
+        "#}));
+}
+
+#[test]
+fn precise_version_not_matching_requirement() {
+    let mut registry = LocalRegistry::create();
+    publish(&mut registry, "bar", "1.0.0");
+    publish(&mut registry, "bar", "2.0.0");
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .args(["update", "bar", "--precise", "2.0.0"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: cannot update `bar` to `2.0.0`, because it does not match version requirements of packages depending on it
+        "#});
+}
+
+#[test]
+fn package_not_in_lockfile() {
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .args(["update", "bar"])
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: package `bar` not found in lockfile
+        "#});
+}
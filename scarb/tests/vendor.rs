@@ -0,0 +1,285 @@
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use indoc::indoc;
+
+use scarb_test_support::command::Scarb;
+use scarb_test_support::fsx::ChildPathEx;
+use scarb_test_support::gitx;
+use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
+use scarb_test_support::registry::local::LocalRegistry;
+
+/// Run `scarb vendor` and write the printed `[source]` config into a new config directory.
+fn vendor(t: &TempDir) -> TempDir {
+    let output = Scarb::quick_snapbox()
+        .arg("vendor")
+        .current_dir(t)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+
+    let (_, source_config) = stdout
+        .split_once("Scarb config directory:\n")
+        .expect("vendor should print source replacement config");
+
+    let config = TempDir::new().unwrap();
+    config
+        .child("config.toml")
+        .write_str(source_config)
+        .unwrap();
+    config
+}
+
+#[test]
+fn vendor_registry_dependency() {
+    let mut registry = LocalRegistry::create();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+            .build(t);
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("vendor")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Vendoring bar v1.0.0 (registry+file://[..])
+        To use vendored sources, add this to the `config.toml` file in Scarb config directory:
+
+        [source."[..]"]
+        registry = "file://[..]"
+        replace-with = "vendored-sources"
+
+        [source."vendored-sources"]
+        local-registry = "[..]vendor"
+        "#});
+
+    t.child("vendor/bar-1.0.0.tar.zst")
+        .assert(predicates::path::is_file());
+    t.child("vendor/index/3/b/bar.json")
+        .assert(predicates::path::is_file());
+
+    // Vendored archives are copied verbatim, so their checksums match the original registry.
+    assert_eq!(
+        t.child("vendor/index/3/b/bar.json").read_to_string(),
+        registry.t.child("index/3/b/bar.json").read_to_string(),
+    );
+}
+
+#[test]
+fn build_from_vendored_sources() {
+    let mut registry = LocalRegistry::create();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+            .build(t);
+    });
+
+    let git_dep = gitx::new("dep1", |t| {
+        ProjectBuilder::start()
+            .name("dep1")
+            .lib_cairo("fn hello() -> felt252 { 42 }")
+            .build(&t)
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .dep("dep1", &git_dep)
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() + dep1::hello() }"#)
+        .build(&t);
+
+    let config = vendor(&t);
+    let lockfile = t.child("Scarb.lock").read_to_string();
+
+    // Original sources are no longer available.
+    drop(registry);
+    drop(git_dep);
+
+    Scarb::quick_snapbox()
+        .arg("build")
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    assert_eq!(t.child("Scarb.lock").read_to_string(), lockfile);
+}
+
+#[test]
+fn vendor_inactive_optional_dependency() {
+    let mut registry = LocalRegistry::create();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+            .build(t);
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry).optional())
+        .manifest_extra(indoc! {r#"
+            [features]
+            with_bar = ["bar"]
+        "#})
+        .build(&t);
+
+    let config = vendor(&t);
+    t.child("vendor/bar-1.0.0.tar.zst")
+        .assert(predicates::path::is_file());
+
+    // Original sources are no longer available.
+    drop(registry);
+
+    Scarb::quick_snapbox()
+        .args(["build", "--features", "with_bar"])
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+}
+
+#[test]
+fn vendored_index_keeps_dependency_sources() {
+    let mut registry = LocalRegistry::create();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+            .build(t);
+    });
+
+    let git_dep = gitx::new("dep1", |t| {
+        ProjectBuilder::start()
+            .name("dep1")
+            .dep("bar", Dep.version("1").registry(&registry))
+            .lib_cairo("fn hello() -> felt252 { bar::f() }")
+            .build(&t)
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("dep1", &git_dep)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("vendor")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    let index = t.child("vendor/index/de/p1/dep1.json").read_to_string();
+    assert!(index.contains(&format!(r#""source":"registry+{registry}""#)));
+}
+
+#[test]
+fn build_locked_git_dependency_from_vendored_sources() {
+    let git_dep = gitx::new("dep1", |t| {
+        ProjectBuilder::start()
+            .name("dep1")
+            .lib_cairo("fn hello() -> felt252 { 42 }")
+            .build(&t)
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("dep1", &git_dep)
+        .lib_cairo(r#"fn f() -> felt252 { dep1::hello() }"#)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success();
+    let lockfile = t.child("Scarb.lock").read_to_string();
+    // Git packages are locked without checksums.
+    assert!(!lockfile.contains("checksum"));
+
+    let config = vendor(&t);
+    drop(git_dep);
+
+    // The vendored archive has a checksum, which must not be compared with the lockfile.
+    Scarb::quick_snapbox()
+        .args(["--locked", "build"])
+        .env("SCARB_CONFIG", config.path())
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    assert_eq!(t.child("Scarb.lock").read_to_string(), lockfile);
+}
+
+#[test]
+fn vendor_different_git_revision_of_same_version() {
+    let git_dep = gitx::new("dep1", |t| {
+        ProjectBuilder::start()
+            .name("dep1")
+            .lib_cairo("fn hello() -> felt252 { 42 }")
+            .build(&t)
+    });
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("dep1", &git_dep)
+        .build(&t);
+
+    vendor(&t);
+    let index = t.child("vendor/index/de/p1/dep1.json").read_to_string();
+
+    // Vendoring the same revision again is fine.
+    vendor(&t);
+
+    git_dep.change_file("src/lib.cairo", "fn hello() -> felt252 { 53 }");
+    Scarb::quick_snapbox()
+        .arg("update")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .arg("vendor")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        [..] Vendoring dep1 v1.0.0 ([..])
+        error: failed to vendor package: dep1 v1.0.0 ([..])
+
+        Caused by:
+            [..]package `dep1` version `1.0.0` is already stored in this registry with different contents
+        "#});
+
+    // The previously vendored revision is kept intact.
+    assert_eq!(
+        t.child("vendor/index/de/p1/dep1.json").read_to_string(),
+        index
+    );
+}
@@ -0,0 +1,226 @@
+use std::time::Duration;
+
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use indoc::indoc;
+use serde_json::json;
+
+use scarb_test_support::command::Scarb;
+use scarb_test_support::fsx::ChildPathEx;
+use scarb_test_support::project_builder::{Dep, DepBuilder, ProjectBuilder};
+use scarb_test_support::registry::http::{HttpRegistry, TOKEN};
+use scarb_test_support::registry::local::LocalRegistry;
+
+fn publish_bar(registry: &mut LocalRegistry, version: &str) {
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version(version)
+            .lib_cairo(r#"fn f() -> felt252 { 0 }"#)
+            .build(t);
+    });
+}
+
+#[test]
+fn yank_and_undo() {
+    let mut registry = LocalRegistry::create();
+    publish_bar(&mut registry, "1.0.0");
+
+    Scarb::quick_snapbox()
+        .args(["yank", "bar@1.0.0", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Yanking bar v1.0.0 (registry+file://[..])
+        "#});
+
+    let records = registry
+        .t
+        .child("index/3/b/bar.json")
+        .assert_is_json::<serde_json::Value>();
+    assert_eq!(records[0]["yanked"], json!(true));
+
+    Scarb::quick_snapbox()
+        .args(["yank", "bar@1.0.0", "--undo", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Unyanking bar v1.0.0 (registry+file://[..])
+        "#});
+
+    let records = registry
+        .t
+        .child("index/3/b/bar.json")
+        .assert_is_json::<serde_json::Value>();
+    assert!(records[0].get("yanked").is_none());
+}
+
+#[test]
+fn yank_missing_version() {
+    let mut registry = LocalRegistry::create();
+    publish_bar(&mut registry, "1.0.0");
+
+    Scarb::quick_snapbox()
+        .args(["yank", "bar@2.0.0", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        [..] Yanking bar v2.0.0 (registry+file://[..])
+        error: failed to yank package: bar v2.0.0 (registry+file://[..])
+
+        Caused by:
+            package `bar` version `2.0.0` not found in registry
+        "#});
+}
+
+#[test]
+fn yanked_versions_are_skipped() {
+    let mut registry = LocalRegistry::create();
+    publish_bar(&mut registry, "1.0.0");
+    publish_bar(&mut registry, "1.1.0");
+
+    Scarb::quick_snapbox()
+        .args(["yank", "bar@1.1.0", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .success();
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_eq("");
+
+    let lockfile = t.child("Scarb.lock").read_to_string();
+    assert!(lockfile.contains(indoc! {r#"
+        [[package]]
+        name = "bar"
+        version = "1.0.0"
+    "#}));
+}
+
+#[test]
+fn locked_yanked_version_is_used() {
+    let mut registry = LocalRegistry::create();
+    publish_bar(&mut registry, "1.0.0");
+
+    let t = TempDir::new().unwrap();
+    ProjectBuilder::start()
+        .name("foo")
+        .version("0.1.0")
+        .dep("bar", Dep.version("1").registry(&registry))
+        .lib_cairo(r#"fn f() -> felt252 { bar::f() }"#)
+        .build(&t);
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .args(["yank", "bar@1.0.0", "--index"])
+        .arg(&registry.url)
+        .assert()
+        .success();
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        warn: package `bar v1.0.0 (registry+file://[..])` is yanked, but it is used because it is locked in Scarb.lock
+        "#});
+
+    // Without the lockfile, the yanked version cannot be selected.
+    t.child("Scarb.lock").assert(predicates::path::is_file());
+    std::fs::remove_file(t.child("Scarb.lock")).unwrap();
+
+    Scarb::quick_snapbox()
+        .arg("fetch")
+        .current_dir(&t)
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        error: Version solving failed:
+        - bar ^1 is required by foo v0.1.0 ([..]), but no versions of bar match
+        ...
+        "#});
+}
+
+#[test]
+fn yank_http_registry() {
+    let mut registry = HttpRegistry::serve();
+    registry.publish(|t| {
+        ProjectBuilder::start()
+            .name("bar")
+            .version("1.0.0")
+            .build(t);
+    });
+
+    Scarb::quick_snapbox()
+        .args(["yank", "bar@1.0.0", "--index"])
+        .arg(registry.to_string())
+        .env("SCARB_REGISTRY_TOKEN", TOKEN)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Yanking bar v1.0.0 (registry+http://[..])
+        "#});
+
+    let records = registry
+        .child("index/3/b/bar.json")
+        .assert_is_json::<serde_json::Value>();
+    assert_eq!(records[0]["yanked"], json!(true));
+    assert!(registry
+        .logs()
+        .contains("PUT /api/v1/packages/bar/1.0.0/yank"));
+
+    Scarb::quick_snapbox()
+        .args(["yank", "bar@1.0.0", "--undo", "--index"])
+        .arg(registry.to_string())
+        .env("SCARB_REGISTRY_TOKEN", TOKEN)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout_matches(indoc! {r#"
+        [..] Unyanking bar v1.0.0 (registry+http://[..])
+        "#});
+
+    let records = registry
+        .child("index/3/b/bar.json")
+        .assert_is_json::<serde_json::Value>();
+    assert!(records[0].get("yanked").is_none());
+    assert!(registry
+        .logs()
+        .contains("DELETE /api/v1/packages/bar/1.0.0/yank"));
+
+    Scarb::quick_snapbox()
+        .args(["yank", "bar@2.0.0", "--index"])
+        .arg(registry.to_string())
+        .env("SCARB_REGISTRY_TOKEN", TOKEN)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stdout_matches(indoc! {r#"
+        [..] Yanking bar v2.0.0 (registry+http://[..])
+        error: failed to yank package: bar v2.0.0 (registry+http://[..])
+
+        Caused by:
+            the registry responded with an error (HTTP 404 Not Found): package `bar` version `2.0.0` not found
+        "#});
+}
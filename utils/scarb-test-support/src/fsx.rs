@@ -67,10 +67,12 @@ impl ChildPathEx for ChildPath {
         fs::read_to_string(self.path()).unwrap()
     }
 
+    /// List names of entries in this directory, skipping hidden ones (like `.fingerprint`).
     fn files(&self) -> Vec<String> {
         self.read_dir()
             .unwrap()
             .map(|entry| entry.unwrap().file_name().to_string_lossy().into())
+            .filter(|name: &String| !name.starts_with('.'))
             .sorted()
             .collect()
     }
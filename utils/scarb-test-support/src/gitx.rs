@@ -42,6 +42,52 @@ impl GitProject {
     pub fn tag(&self, name: &str) {
         self.git(["tag", "-a", name, "-m", "test tag"])
     }
+
+    /// Add the `submodule` repository as a submodule at `path`, pinned to its current `HEAD`.
+    ///
+    /// The `url` is recorded in `.gitmodules` as is, so it can be relative.
+    pub fn add_submodule(&self, path: &str, url: &str, submodule: &GitProject) {
+        let rev = submodule.rev_parse("HEAD");
+        let path_key = format!("submodule.{path}.path");
+        let url_key = format!("submodule.{path}.url");
+        let cacheinfo = format!("160000,{rev},{path}");
+        self.git(["config", "--file", ".gitmodules", path_key.as_str(), path]);
+        self.git(["config", "--file", ".gitmodules", url_key.as_str(), url]);
+        self.git(["update-index", "--add", "--cacheinfo", cacheinfo.as_str()]);
+        self.git(["add", ".gitmodules"]);
+        self.git(["commit", "-m", "add submodule"]);
+    }
+
+    /// Create a bare clone of this repository, to be used as a remote that packages are
+    /// fetched from.
+    pub fn bare_clone(&self) -> GitProject {
+        // NOTE: The `.git` suffix is not added to the repository path, because Scarb strips it
+        //   from repository URLs.
+        let name = self.name.clone();
+        let t = TempDir::new().unwrap();
+        let p = t.child(&name);
+        git(
+            self,
+            [
+                "clone",
+                "--bare",
+                self.p.to_str().unwrap(),
+                p.to_str().unwrap(),
+            ],
+        );
+        GitProject { name, t, p }
+    }
+
+    /// Get the ID of the commit pointed to by the given revision.
+    pub fn rev_parse(&self, rev: &str) -> String {
+        let output = git_command()
+            .args(["rev-parse", rev])
+            .current_dir(self.p.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
 }
 
 impl fmt::Display for GitProject {
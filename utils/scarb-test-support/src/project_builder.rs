@@ -5,7 +5,7 @@ use assert_fs::fixture::ChildPath;
 use assert_fs::prelude::*;
 use camino::Utf8PathBuf;
 use semver::Version;
-use toml_edit::{Document, Item, Value};
+use toml_edit::{Array, Document, Item, Value};
 
 use scarb_build_metadata::CAIRO_VERSION;
 use to_version::ToVersion;
@@ -173,6 +173,22 @@ pub trait DepBuilder {
     fn registry(&self, registry: impl ToString) -> DepWith<'_, Self> {
         self.with("registry", registry.to_string())
     }
+
+    fn optional(&self) -> DepWith<'_, Self> {
+        self.with("optional", true)
+    }
+
+    fn features<S: ToString>(&self, features: impl IntoIterator<Item = S>) -> DepWith<'_, Self> {
+        let features = features
+            .into_iter()
+            .map(|f| f.to_string())
+            .collect::<Array>();
+        self.with("features", features)
+    }
+
+    fn default_features(&self, default_features: bool) -> DepWith<'_, Self> {
+        self.with("default-features", default_features)
+    }
 }
 
 pub struct Dep;
@@ -1,12 +1,26 @@
+use std::collections::HashMap;
 use std::fmt;
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use assert_fs::fixture::ChildPath;
 use assert_fs::prelude::*;
 use assert_fs::TempDir;
+use axum::body::Bytes;
+use axum::extract::{Path as RoutePath, Query, State};
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::http::{HeaderMap, Request, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
 use once_cell::sync::Lazy;
-use serde_json::json;
+use semver::Version;
+use serde_json::{json, Value};
 use tokio::runtime;
+use tower_http::services::ServeDir;
 
 use crate::registry::local::LocalRegistry;
 use crate::simple_http_server::SimpleHttpServer;
@@ -21,9 +35,13 @@ static RUNTIME: Lazy<runtime::Runtime> = Lazy::new(|| {
         .unwrap()
 });
 
+/// Authentication token accepted by [`HttpRegistry`].
+pub const TOKEN: &str = "scarb-test-token";
+
 pub struct HttpRegistry {
     local: LocalRegistry,
     url: String,
+    failures: Arc<AtomicUsize>,
 
     // This needs to be stored here so that it's dropped properly.
     server: SimpleHttpServer,
@@ -31,17 +49,46 @@ pub struct HttpRegistry {
 
 impl HttpRegistry {
     pub fn serve() -> Self {
+        Self::serve_impl(false)
+    }
+
+    /// Serve a registry which requires authentication with [`TOKEN`] for all requests
+    /// except fetching `config.json`.
+    pub fn serve_authenticated() -> Self {
+        Self::serve_impl(true)
+    }
+
+    fn serve_impl(auth_required: bool) -> Self {
         let local = LocalRegistry::create();
+        let failures = Arc::new(AtomicUsize::new(0));
         let server = {
             let _guard = RUNTIME.enter();
-            SimpleHttpServer::serve(local.t.path().to_owned())
+            let mut app = Router::new()
+                .route("/api/v1/publish", post(publish))
+                .route(
+                    "/api/v1/packages/:name/:version/yank",
+                    put(yank).delete(unyank),
+                )
+                .route("/api/v1/search", get(search))
+                .with_state(local.t.path().to_owned())
+                .fallback_service(ServeDir::new(local.t.path()));
+            if auth_required {
+                app = app.layer(middleware::from_fn(require_auth));
+            }
+            app = app.layer(middleware::from_fn_with_state(
+                failures.clone(),
+                fail_requests,
+            ));
+            SimpleHttpServer::serve_router(app)
         };
         let url = server.url();
 
         let config = json!({
             "version": 1,
+            "api": format!("{url}api/v1"),
             "dl": format!("{url}{{package}}-{{version}}.tar.zst"),
-            "index": format!("{url}index/{{prefix}}/{{package}}.json")
+            "index": format!("{url}index/{{prefix}}/{{package}}.json"),
+            "auth-required": auth_required,
         });
         local
             .t
@@ -49,7 +96,17 @@ impl HttpRegistry {
             .write_str(&serde_json::to_string(&config).unwrap())
             .unwrap();
 
-        Self { local, url, server }
+        Self {
+            local,
+            url,
+            failures,
+            server,
+        }
+    }
+
+    /// Respond to the next `n` requests with `503 Service Unavailable`.
+    pub fn fail_next_requests(&self, n: usize) {
+        self.failures.store(n, Ordering::SeqCst);
     }
 
     pub fn publish(&mut self, f: impl FnOnce(&TempDir)) -> &mut Self {
@@ -87,3 +144,251 @@ impl fmt::Display for HttpRegistry {
         fmt::Display::fmt(&self.url, f)
     }
 }
+
+type ApiResponse = (StatusCode, Json<Value>);
+
+fn is_authorized(headers: &HeaderMap) -> bool {
+    let expected = format!("Bearer {TOKEN}");
+    headers.get(AUTHORIZATION).and_then(|v| v.to_str().ok()) == Some(expected.as_str())
+}
+
+async fn require_auth<B>(request: Request<B>, next: Next<B>) -> Response {
+    if request.uri().path() == "/config.json" || is_authorized(request.headers()) {
+        next.run(request).await
+    } else {
+        StatusCode::UNAUTHORIZED.into_response()
+    }
+}
+
+async fn fail_requests<B>(
+    State(failures): State<Arc<AtomicUsize>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let should_fail = failures
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+        .is_ok();
+    if should_fail {
+        StatusCode::SERVICE_UNAVAILABLE.into_response()
+    } else {
+        next.run(request).await
+    }
+}
+
+fn api_error(status: StatusCode, detail: impl fmt::Display) -> ApiResponse {
+    let body = json!({ "errors": [{ "detail": detail.to_string() }] });
+    (status, Json(body))
+}
+
+/// Minimal implementation of the `{api}/publish` endpoint, storing packages in the same layout
+/// as the local registry does.
+async fn publish(State(root): State<PathBuf>, headers: HeaderMap, body: Bytes) -> ApiResponse {
+    if !is_authorized(&headers) {
+        return api_error(StatusCode::FORBIDDEN, "invalid authentication token");
+    }
+
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let Some(mut parts) = parse_multipart(content_type, &body) else {
+        return api_error(StatusCode::BAD_REQUEST, "malformed multipart request");
+    };
+    let (Some(metadata), Some(tarball)) = (parts.remove("metadata"), parts.remove("package"))
+    else {
+        return api_error(
+            StatusCode::BAD_REQUEST,
+            "missing `metadata` or `package` field",
+        );
+    };
+    let Ok(Value::Object(mut record)) = serde_json::from_slice::<Value>(&metadata) else {
+        return api_error(StatusCode::BAD_REQUEST, "malformed package metadata");
+    };
+
+    let name = record
+        .remove("name")
+        .and_then(|v| v.as_str().map(String::from));
+    let version = record
+        .get("version")
+        .and_then(|v| v.as_str().map(String::from));
+    let (Some(name), Some(version)) = (name, version) else {
+        return api_error(StatusCode::BAD_REQUEST, "missing package name or version");
+    };
+    record.remove("metadata");
+
+    let tarball_path = root.join(format!("{name}-{version}.tar.zst"));
+    if tarball_path.exists() {
+        return api_error(
+            StatusCode::BAD_REQUEST,
+            format!("package `{name}` version `{version}` already exists"),
+        );
+    }
+    fs::write(tarball_path, tarball).unwrap();
+
+    let records_path = records_path(&root, &name);
+    let mut records = read_records(&records_path);
+    records.push(Value::Object(record));
+    write_records(&records_path, &records);
+
+    (StatusCode::OK, Json(json!({})))
+}
+
+async fn yank(
+    State(root): State<PathBuf>,
+    RoutePath((name, version)): RoutePath<(String, String)>,
+    headers: HeaderMap,
+) -> ApiResponse {
+    set_yanked(&root, &headers, &name, &version, true)
+}
+
+async fn unyank(
+    State(root): State<PathBuf>,
+    RoutePath((name, version)): RoutePath<(String, String)>,
+    headers: HeaderMap,
+) -> ApiResponse {
+    set_yanked(&root, &headers, &name, &version, false)
+}
+
+fn set_yanked(
+    root: &Path,
+    headers: &HeaderMap,
+    name: &str,
+    version: &str,
+    yanked: bool,
+) -> ApiResponse {
+    if !is_authorized(headers) {
+        return api_error(StatusCode::FORBIDDEN, "invalid authentication token");
+    }
+
+    let records_path = records_path(root, name);
+    let mut records = read_records(&records_path);
+    let Some(record) = records.iter_mut().find(|record| record["v"] == version) else {
+        return api_error(
+            StatusCode::NOT_FOUND,
+            format!("package `{name}` version `{version}` not found"),
+        );
+    };
+    let record = record.as_object_mut().unwrap();
+    if yanked {
+        record.insert("yanked".to_string(), Value::Bool(true));
+    } else {
+        record.remove("yanked");
+    }
+    write_records(&records_path, &records);
+
+    (StatusCode::OK, Json(json!({})))
+}
+
+/// Minimal implementation of the `{api}/search` endpoint, matching package names only.
+async fn search(
+    State(root): State<PathBuf>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResponse {
+    let query = params.get("q").cloned().unwrap_or_default();
+    let limit = params
+        .get("limit")
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(10);
+
+    let mut packages = records_files(&root.join("index"))
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            if !name.contains(&query) {
+                return None;
+            }
+            let version = read_records(&path)
+                .iter()
+                .filter(|record| record.get("yanked") != Some(&Value::Bool(true)))
+                .filter_map(|record| Version::parse(record["v"].as_str()?).ok())
+                .max()?;
+            Some((name, version))
+        })
+        .collect::<Vec<_>>();
+    packages.sort();
+    packages.truncate(limit);
+
+    let packages = packages
+        .into_iter()
+        .map(|(name, version)| json!({ "name": name, "version": version.to_string() }))
+        .collect::<Vec<_>>();
+    (StatusCode::OK, Json(json!({ "packages": packages })))
+}
+
+fn records_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .flat_map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                records_files(&path)
+            } else {
+                vec![path]
+            }
+        })
+        .collect()
+}
+
+fn records_path(root: &Path, name: &str) -> PathBuf {
+    root.join("index")
+        .join(pkg_prefix(name))
+        .join(format!("{name}.json"))
+}
+
+fn read_records(records_path: &Path) -> Vec<Value> {
+    fs::read(records_path)
+        .map(|bytes| serde_json::from_slice(&bytes).unwrap())
+        .unwrap_or_default()
+}
+
+fn write_records(records_path: &Path, records: &[Value]) {
+    fs::create_dir_all(records_path.parent().unwrap()).unwrap();
+    fs::write(records_path, serde_json::to_vec(records).unwrap()).unwrap();
+}
+
+/// Parse `multipart/form-data` body into a map from field names to their contents.
+fn parse_multipart(content_type: &str, body: &[u8]) -> Option<HashMap<String, Vec<u8>>> {
+    let boundary = content_type
+        .strip_prefix("multipart/form-data")?
+        .split(';')
+        .find_map(|param| param.trim().strip_prefix("boundary="))?
+        .trim_matches('"');
+    let delimiter = format!("\r\n--{boundary}");
+
+    // Prepend line break, so that the first delimiter looks like all the others.
+    let mut body = [b"\r\n".as_slice(), body].concat();
+    let mut fields = HashMap::new();
+    let start = find(&body, delimiter.as_bytes())? + delimiter.len();
+    body.drain(..start);
+    loop {
+        // Closing delimiter is followed by `--`.
+        if body.starts_with(b"--") {
+            return Some(fields);
+        }
+        let part_end = find(&body, delimiter.as_bytes())?;
+        let part = body[..part_end].strip_prefix(b"\r\n")?;
+        let headers_end = find(part, b"\r\n\r\n")?;
+        let headers = std::str::from_utf8(&part[..headers_end]).ok()?;
+        let name = headers.split("name=\"").nth(1)?.split('"').next()?;
+        fields.insert(name.to_string(), part[headers_end + 4..].to_vec());
+        body.drain(..part_end + delimiter.len());
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn pkg_prefix(name: &str) -> String {
+    match name.len() {
+        1 => "1".to_string(),
+        2 => "2".to_string(),
+        3 => format!("3/{}", &name[..1]),
+        _ => format!("{}/{}", &name[0..2], &name[2..4]),
+    }
+}
@@ -46,13 +46,17 @@ pub struct HttpLog {
 
 impl SimpleHttpServer {
     pub fn serve(dir: PathBuf) -> Self {
+        Self::serve_router(Router::new().fallback_service(ServeDir::new(dir)))
+    }
+
+    /// Serve requests with the given router, usually falling back to serving files.
+    pub fn serve_router(app: Router) -> Self {
         let (ct, ctrx) = tokio::sync::oneshot::channel::<()>();
 
         let print_logs = Arc::new(AtomicBool::new(false));
         let logs: LogsStore = Default::default();
 
-        let app = Router::new()
-            .fallback_service(ServeDir::new(dir))
+        let app = app
             .layer(middleware::from_fn(set_etag))
             .layer(middleware::from_fn_with_state(
                 (logs.clone(), print_logs.clone()),
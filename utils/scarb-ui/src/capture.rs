@@ -0,0 +1,112 @@
+use std::cell::RefCell;
+
+#[cfg(doc)]
+use super::Ui;
+
+thread_local! {
+    static CAPTURE: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Output of [`Ui`] messages printed within a [`capture`] call.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CapturedOutput(String);
+
+impl CapturedOutput {
+    /// Returns `true` if no messages have been captured.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Print all captured messages to the standard output, in the order they were captured.
+    pub fn print(self) {
+        if !self.is_empty() {
+            write(&self.0);
+        }
+    }
+}
+
+impl From<String> for CapturedOutput {
+    /// Recreate output captured earlier, for example to print it again.
+    fn from(output: String) -> Self {
+        Self(output)
+    }
+}
+
+impl AsRef<str> for CapturedOutput {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Run `f`, buffering all [`Ui`] messages printed by it on the current thread instead of writing
+/// them to the standard output.
+///
+/// This is useful when running tasks concurrently, so that messages of each task can be printed
+/// afterwards as a contiguous block, without being interleaved with messages of other tasks.
+/// Interactive widgets are not captured.
+/// Captures can be nested, and the output of the inner capture is not visible to the outer one,
+/// unless [`CapturedOutput::print`] is called.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, CapturedOutput) {
+    struct Guard(Option<String>);
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            // Restore the outer capture, even if `f` panics.
+            CAPTURE.with(|capture| capture.replace(self.0.take()));
+        }
+    }
+
+    let guard = Guard(CAPTURE.with(|capture| capture.replace(Some(String::new()))));
+    let result = f();
+    let output = CAPTURE
+        .with(|capture| capture.replace(None))
+        .unwrap_or_default();
+    drop(guard);
+    (result, CapturedOutput(output))
+}
+
+/// Write a line of text to the standard output, or to the active capture buffer.
+pub(crate) fn write_line(text: &str) {
+    write(&format!("{text}\n"));
+}
+
+fn write(text: &str) {
+    let captured = CAPTURE.with(|capture| match capture.borrow_mut().as_mut() {
+        Some(buf) => {
+            buf.push_str(text);
+            true
+        }
+        None => false,
+    });
+    if !captured {
+        print!("{text}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{capture, write_line};
+
+    #[test]
+    fn capture_output() {
+        let (result, output) = capture(|| {
+            write_line("first");
+            let ((), inner) = capture(|| write_line("inner"));
+            write_line("second");
+            inner.print();
+            42
+        });
+        assert_eq!(result, 42);
+        assert_eq!(output.as_ref(), "first\nsecond\ninner\n");
+    }
+
+    #[test]
+    fn capture_is_restored_after_panic() {
+        let ((), outer) = capture(|| {
+            let result = std::panic::catch_unwind(|| capture(|| panic!("boom")));
+            assert!(result.is_err());
+            write_line("after");
+        });
+        assert_eq!(outer.as_ref(), "after\n");
+    }
+}
@@ -0,0 +1,85 @@
+use serde::{Serialize, Serializer};
+
+use crate::components::TypedMessage;
+use crate::Message;
+
+/// Severity of a [`DiagnosticMessage`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    /// An error, which makes the compilation fail.
+    Error,
+    /// A warning, which does not make the compilation fail unless warnings are disallowed.
+    Warning,
+}
+
+/// A position in a source file, both line and column numbers are 1-based.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct DiagnosticPosition {
+    /// Line number, starting from 1.
+    pub line: usize,
+    /// Column number, starting from 1.
+    pub column: usize,
+}
+
+/// A range of source code pointed to by a [`DiagnosticMessage`], both ends are inclusive.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct DiagnosticSpan {
+    /// Position of the first character of the span.
+    pub start: DiagnosticPosition,
+    /// Position of the last character of the span.
+    pub end: DiagnosticPosition,
+}
+
+/// A compiler diagnostic.
+///
+/// In text mode, the diagnostic is printed as rendered by the compiler, prefixed with
+/// its severity.
+/// In JSON mode, all details of the diagnostic are serialized:
+/// ```json
+/// {
+///   "type": "diagnostic",
+///   "severity": "error",
+///   "message": "Identifier not found.",
+///   "file": "/path/to/src/lib.cairo",
+///   "spans": [{"start": {"line": 1, "column": 5}, "end": {"line": 1, "column": 7}}],
+///   "package_id": "hello 0.1.0 (path+file:///path/to/Scarb.toml)",
+///   "compilation_unit_id": "hello-gb5mbc32d8bke",
+///   "notes": []
+/// }
+/// ```
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename = "diagnostic")]
+pub struct DiagnosticMessage {
+    /// Severity of this diagnostic.
+    pub severity: DiagnosticSeverity,
+    /// The message proper, without location information.
+    pub message: String,
+    /// Path to the file this diagnostic points to, if it has a location.
+    pub file: Option<String>,
+    /// Ranges of source code this diagnostic points to.
+    pub spans: Vec<DiagnosticSpan>,
+    /// ID of the package which the file belongs to.
+    pub package_id: Option<String>,
+    /// ID of the compilation unit which emitted this diagnostic.
+    pub compilation_unit_id: Option<String>,
+    /// Additional notes attached to this diagnostic.
+    pub notes: Vec<String>,
+    /// Full text of this diagnostic, used in text mode.
+    #[serde(skip)]
+    pub rendered: String,
+}
+
+impl Message for DiagnosticMessage {
+    fn text(self) -> String {
+        let (ty, style) = match self.severity {
+            DiagnosticSeverity::Error => ("error", "red"),
+            DiagnosticSeverity::Warning => ("warn", "yellow"),
+        };
+        TypedMessage::styled(ty, style, &self.rendered).text()
+    }
+
+    fn structured<S: Serializer>(self, ser: S) -> Result<S::Ok, S::Error> {
+        self.serialize(ser)
+    }
+}
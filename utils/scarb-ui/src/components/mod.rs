@@ -1,13 +1,17 @@
 //! This module provides various ready to use message types and widgets for use with
 //! a [`Ui`][crate::Ui].
 
+pub use diagnostic::*;
 pub use machine::*;
+pub use progress::*;
 pub use spinner::*;
 pub use status::*;
 pub use typed::*;
 pub use value::*;
 
+mod diagnostic;
 mod machine;
+mod progress;
 mod spinner;
 mod status;
 mod typed;
@@ -0,0 +1,74 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
+
+use crate::Widget;
+
+/// Progress bar widget informing about the number of completed items out of a known total,
+/// along with the amount of data transferred so far.
+pub struct Progress {
+    message: String,
+    total: u64,
+}
+
+impl Progress {
+    /// Create a new [`Progress`] with the given message and total number of items.
+    pub fn new(message: impl Into<String>, total: u64) -> Self {
+        Self {
+            message: message.into(),
+            total,
+        }
+    }
+
+    fn default_style() -> ProgressStyle {
+        ProgressStyle::with_template(
+            "{spinner:.cyan} {prefix} [{bar:25}] {pos}/{len}, {msg} {elapsed}",
+        )
+        .unwrap()
+        .progress_chars("=> ")
+    }
+}
+
+/// Allows reporting progress of the associated [`Progress`], and finishes it when dropped.
+///
+/// The handle can be shared between concurrently running tasks.
+pub struct ProgressHandle {
+    pb: ProgressBar,
+    bytes: AtomicU64,
+}
+
+impl ProgressHandle {
+    /// Mark the given number of items as completed.
+    pub fn inc(&self, delta: u64) {
+        self.pb.inc(delta);
+    }
+
+    /// Add the given number of bytes to the amount of data transferred so far.
+    pub fn inc_bytes(&self, delta: u64) {
+        let bytes = self.bytes.fetch_add(delta, Ordering::Relaxed) + delta;
+        self.pb.set_message(HumanBytes(bytes).to_string());
+    }
+}
+
+impl Drop for ProgressHandle {
+    fn drop(&mut self) {
+        self.pb.finish_and_clear()
+    }
+}
+
+impl Widget for Progress {
+    type Handle = ProgressHandle;
+
+    fn text(self) -> Self::Handle {
+        let pb = ProgressBar::new(self.total)
+            .with_style(Progress::default_style())
+            .with_prefix(self.message)
+            .with_message(HumanBytes(0).to_string());
+        pb.enable_steady_tick(Duration::from_millis(120));
+        ProgressHandle {
+            pb,
+            bytes: AtomicU64::new(0),
+        }
+    }
+}
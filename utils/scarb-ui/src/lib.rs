@@ -27,6 +27,7 @@ pub use indicatif::{
     HumanFloatCount,
 };
 
+pub use capture::*;
 pub use message::*;
 pub use verbosity::*;
 pub use widget::*;
@@ -34,13 +35,14 @@ pub use widget::*;
 use crate::components::TypedMessage;
 
 pub mod args;
+mod capture;
 pub mod components;
 mod message;
 mod verbosity;
 mod widget;
 
 /// The requested format of output (either textual or JSON).
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, ValueEnum)]
 pub enum OutputFormat {
     /// Render human-readable messages and interactive widgets.
     #[default]
@@ -1,5 +1,7 @@
 use serde::Serializer;
 
+use crate::capture::write_line;
+
 #[cfg(doc)]
 use super::Ui;
 
@@ -30,7 +32,7 @@ pub trait Message {
     {
         let text = self.text();
         if !text.is_empty() {
-            println!("{text}");
+            write_line(&text);
         }
     }
 
@@ -61,7 +63,7 @@ pub trait Message {
                     // UNSAFE: JSON is always UTF-8 encoded.
                     String::from_utf8_unchecked(buf)
                 };
-                println!("{string}");
+                write_line(&string);
             }
             Err(err) => {
                 if err.to_string() != JSON_SKIP_MESSAGE {
@@ -47,12 +47,14 @@ const sidebar = {
       items: [
         p("Compilation model", "/docs/reference/compilation-model"),
         p("Conditional compilation", "/docs/reference/conditional-compilation"),
+        p("Features", "/docs/reference/features"),
         p("Global directories", "/docs/reference/global-directories"),
         p("Manifest", "/docs/reference/manifest"),
         p("Lockfile", "/docs/reference/lockfile"),
         p("Workspaces", "/docs/reference/workspaces"),
         p("Profiles", "/docs/reference/profiles"),
         p("Scripts", "/docs/reference/scripts"),
+        p("Source replacement", "/docs/reference/source-replacement"),
         p("Specifying dependencies", "/docs/reference/specifying-dependencies"),
         p("Targets", "/docs/reference/targets"),
       ],
@@ -68,7 +70,10 @@ const sidebar = {
     },
     {
       text: "Registries",
-      items: [p("Package tarball", "/docs/registries/package-tarball")],
+      items: [
+        p("Package tarball", "/docs/registries/package-tarball"),
+        p("Registry API", "/docs/registries/registry-api"),
+      ],
     },
     {
       text: "Appendices",